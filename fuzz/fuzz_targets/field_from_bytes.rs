@@ -0,0 +1,22 @@
+// field_from_bytes.rs - Runs arbitrary bytes through the field decoder, run with `cargo fuzz run field_from_bytes`
+
+#![no_main]
+
+use libfuzzer_sys::fuzz_target;
+use apedb::dbio::dbfield::Field;
+
+fuzz_target!(|data: &[u8]|
+{
+    // None of these may panic, no matter how broken the data is
+    let _ = Field::split_bytes(data);
+    let _ = Field::from_stream(data);
+
+    if let Ok((field, field_len)) = Field::from_bytes_with_len(data)
+    {
+        assert!(field_len <= data.len());
+
+        // Anything that decodes has to survive being encoded and decoded again
+        let encoded = field.to_bytes().expect("Decoded field failed to encode!");
+        assert_eq!(Field::from_bytes(&encoded).expect("Encoded field failed to decode!"), field);
+    }
+});
@@ -3,6 +3,30 @@
 
 
 use std::error::Error;
+use std::cmp::Ordering;
+use std::fmt;
+use std::ops::{Add, Sub, Neg, Mul, Div, Rem};
+use std::str::FromStr;
+use std::time::{SystemTime, UNIX_EPOCH};
+use simple_error::*;
+use crate::dbio::dbfield::Field;
+use crate::dbio::dbuuid::UuidV4;
+
+
+
+// Constants!
+//
+
+
+
+const I_LONG_LENGTH: usize = 0x7F; // Magnitudes this many bytes long or longer have their length stored separately
+const I_DECIMAL_GROUP: u64 = 10_000_000_000_000_000_000; // 10^19, the biggest power of ten that fits in a u64
+pub const D_MAX_PRECISION: u32 = 1000; // The most digits a decimal may have
+pub const BIN_INLINE_MAX: usize = 128; // Binary values longer than this are moved out of the field into overflow chunks
+const DATE_MIN_DAYS: i64 = -719528; // 0000-01-01, the first day RFC 3339 can write
+const DATE_MAX_DAYS: i64 = 2932896; // 9999-12-31, the last day RFC 3339 can write
+const SECONDS_PER_DAY: i64 = 86400;
+const NANOS_PER_SECOND: u32 = 1_000_000_000;
 
 
 
@@ -13,12 +37,236 @@ use std::error::Error;
 
 // apetypes::Type - Enum for the different types of data that can be stored in the database.
 //
-#[derive(Debug, Clone, PartialEq, PartialOrd)]
+#[derive(Debug, Clone, PartialEq)]
 pub enum Type
 {
     I(Option<I>), // Integer
     S(Option<S>), // String
     B(Option<B>), // Boolean
+    F(Option<F>), // Floating point
+    D(Option<D>), // Fixed point decimal
+    Bin(Option<Bin>), // Opaque binary data
+    T(Option<T>), // UTC instant
+    Date(Option<Date>), // Calendar date
+    List(Option<Vec<Type>>), // List of values, which may be of different types
+    Doc(Option<Vec<Field>>), // Document of fields, such as an embedded address
+    Ref(Option<UuidV4>), // Reference to another entry
+}
+
+impl Type
+{
+    // apetypes::Type::sort_class() - Get the byte that decides the order of values of different types
+    //
+    // This is the type byte used on disk, apart from booleans which are stored as 'B' or 'b' but always sort as 'B'.
+    pub fn sort_class(&self) -> u8
+    {
+        match self
+        {
+            Type::I(_) => return b'I',
+            Type::S(_) => return b'S',
+            Type::B(_) => return b'B',
+            Type::F(_) => return b'F',
+            Type::D(_) => return b'D',
+            Type::Bin(_) => return b'X',
+            Type::T(_) => return b'T',
+            Type::Date(_) => return b'A',
+            Type::List(_) => return b'L',
+            Type::Doc(_) => return b'O',
+            Type::Ref(_) => return b'R',
+        }
+    }
+
+    // apetypes::Type::is_null() - Check if the value is null
+    //
+    pub fn is_null(&self) -> bool
+    {
+        match self
+        {
+            Type::I(value) => return value.is_none(),
+            Type::S(value) => return value.is_none(),
+            Type::B(value) => return value.is_none(),
+            Type::F(value) => return value.is_none(),
+            Type::D(value) => return value.is_none(),
+            Type::Bin(value) => return value.is_none(),
+            Type::T(value) => return value.is_none(),
+            Type::Date(value) => return value.is_none(),
+            Type::List(value) => return value.is_none(),
+            Type::Doc(value) => return value.is_none(),
+            Type::Ref(value) => return value.is_none(),
+        }
+    }
+
+    // apetypes::Type::null() - Get the null of the type with the given sort class, see sort_class()
+    //
+    // ARGUMENTS:
+    //  sort_class: u8 - The sort class of the type
+    pub fn null(sort_class: u8) -> Option<Type>
+    {
+        match sort_class
+        {
+            b'I' => return Some(Type::I(None)),
+            b'S' => return Some(Type::S(None)),
+            b'B' => return Some(Type::B(None)),
+            b'F' => return Some(Type::F(None)),
+            b'D' => return Some(Type::D(None)),
+            b'X' => return Some(Type::Bin(None)),
+            b'T' => return Some(Type::T(None)),
+            b'A' => return Some(Type::Date(None)),
+            b'L' => return Some(Type::List(None)),
+            b'O' => return Some(Type::Doc(None)),
+            b'R' => return Some(Type::Ref(None)),
+            _ => return None,
+        }
+    }
+}
+
+impl PartialOrd for Type
+{
+    // Values of the same type compare by value, anything else compares by type so the order matches the one on disk.
+    // Nulls sort before every value, like NULLS FIRST in SQL, and nulls of the same type are equal to each other.
+    fn partial_cmp(&self, other: &Type) -> Option<Ordering>
+    {
+        match (self.is_null(), other.is_null())
+        {
+            (true, true) => return self.sort_class().partial_cmp(&other.sort_class()),
+            (true, false) => return Some(Ordering::Less),
+            (false, true) => return Some(Ordering::Greater),
+            (false, false) => {}
+        }
+
+        match (self, other)
+        {
+            (Type::I(a), Type::I(b)) => return a.partial_cmp(b),
+            (Type::S(a), Type::S(b)) => return a.partial_cmp(b),
+            (Type::B(a), Type::B(b)) => return a.partial_cmp(b),
+            (Type::F(a), Type::F(b)) => return a.partial_cmp(b),
+            (Type::D(a), Type::D(b)) => return a.partial_cmp(b),
+            (Type::Bin(a), Type::Bin(b)) => return a.partial_cmp(b),
+            (Type::T(a), Type::T(b)) => return a.partial_cmp(b),
+            (Type::Date(a), Type::Date(b)) => return a.partial_cmp(b),
+            (Type::List(a), Type::List(b)) => return a.partial_cmp(b),
+            (Type::Ref(a), Type::Ref(b)) => return a.partial_cmp(b),
+            (Type::Doc(Some(a)), Type::Doc(Some(b))) =>
+            {
+                // Field by field, by ID and then by value, and a document that runs out first sorts first
+                for (field_a, field_b) in a.iter().zip(b.iter())
+                {
+                    match field_a.id.cmp(&field_b.id)
+                    {
+                        Ordering::Equal => {}
+                        ordering => return Some(ordering),
+                    }
+
+                    match field_a.value.partial_cmp(&field_b.value)?
+                    {
+                        Ordering::Equal => {}
+                        ordering => return Some(ordering),
+                    }
+                }
+
+                return Some(a.len().cmp(&b.len()));
+            }
+            _ => return self.sort_class().partial_cmp(&other.sort_class()),
+        }
+    }
+}
+
+// apetypes::Bin - Database binary type, either stored inline in the field or in a chain of overflow chunks
+//
+// Only inline values are ordered by their contents, they always sort before overflowed values, which are ordered by
+// where their chain starts and then by length. See ChunkyFile::bin_writer() and ChunkyFile::bin_reader() for working
+// with overflowed values.
+#[derive(Debug, Clone, PartialEq, PartialOrd)]
+pub enum Bin
+{
+    Inline(Vec<u8>), // The data itself
+    Overflow { chunk: u64, length: u64 }, // The position of the first overflow chunk and the length of the data
+}
+
+impl Bin
+{
+    // apetypes::Bin::new() - Create a new inline Bin, use ChunkyFile::add_bin() to move big values out of the field
+    //
+    // Values longer than BIN_INLINE_MAX can't be stored inline, List::add_entry() moves them out when the entry is written.
+    // ARGUMENTS:
+    //  data: &[u8] - The data to store
+    pub fn new(data: &[u8]) -> Bin
+    {
+        return Bin::Inline(data.to_vec());
+    }
+
+    // apetypes::Bin::len() - Get the length of the data, wherever it is stored
+    //
+    pub fn len(&self) -> u64
+    {
+        match self
+        {
+            Bin::Inline(data) => return data.len() as u64,
+            Bin::Overflow { length, .. } => return *length,
+        }
+    }
+
+    // apetypes::Bin::is_empty() - Check if there is no data
+    //
+    pub fn is_empty(&self) -> bool
+    {
+        return self.len() == 0;
+    }
+
+    // apetypes::Bin::to_bytes() - Convert a Bin to a byte array
+    //
+    // Inline values are 0x00 followed by the data, overflowed values are 0x01 followed by the chunk and length as u64s.
+    pub fn to_bytes(&self) -> Vec<u8>
+    {
+        let mut data = Vec::<u8>::new();
+
+        match self
+        {
+            Bin::Inline(inline) =>
+            {
+                data.push(0x00);
+                data.extend_from_slice(inline);
+            }
+            Bin::Overflow { chunk, length } =>
+            {
+                data.push(0x01);
+                data.extend_from_slice(&chunk.to_be_bytes());
+                data.extend_from_slice(&length.to_be_bytes());
+            }
+        }
+
+        return data;
+    }
+
+    // apetypes::Bin::from_bytes() - Convert a byte array to a Bin
+    //
+    // ARGUMENTS:
+    //  bytes: &[u8] - The byte array to convert
+    pub fn from_bytes(bytes: &[u8]) -> Result<Bin, Box<dyn Error>>
+    {
+        match bytes.first()
+        {
+            Some(0x00) if bytes.len() > BIN_INLINE_MAX + 1 =>
+            {
+                bail!("Inline binary value is longer than {} bytes!", BIN_INLINE_MAX);
+            }
+            Some(0x00) =>
+            {
+                return Ok(Bin::Inline(bytes[1..].to_vec()));
+            }
+            Some(0x01) if bytes.len() == 17 =>
+            {
+                let chunk = u64::from_be_bytes(bytes[1..9].try_into().expect("Slice of incorrect size when reading an overflow chunk, you shouldn't see this!"));
+                let length = u64::from_be_bytes(bytes[9..17].try_into().expect("Slice of incorrect size when reading an overflow length, you shouldn't see this!"));
+
+                return Ok(Bin::Overflow { chunk: chunk, length: length });
+            }
+            _ =>
+            {
+                bail!("Invalid binary value!");
+            }
+        }
+    }
 }
 
 // Structs!
@@ -48,6 +296,13 @@ impl S
         };
     }
 
+    // apetypes::S::as_str() - Get the string stored in the S
+    //
+    pub fn as_str(&self) -> &str
+    {
+        return &self.string;
+    }
+
     // apetypes::S::to_bytes() - Convert the S to a byte array
     //
     pub fn to_bytes(&self) -> Vec<u8>
@@ -71,13 +326,15 @@ impl S
     }
 }
 
-// apetypes::I - Database integer type
+// apetypes::I - Database integer type, an arbitrary precision two's complement integer
 //
-#[derive(Debug, Clone, PartialEq, PartialOrd)]
+// The value is most_significant followed by every trailing limb, most significant limb first. The integer is kept
+// normalized, so the top limb is never just sign extension of the limb below it.
+#[derive(Debug, Clone, PartialEq, Eq)]
 pub struct I
 {
     most_significant: i64,
-    //trailing: Vec<u64>,
+    trailing: Vec<u64>,
 }
 
 impl I
@@ -91,17 +348,200 @@ impl I
         return I
         {
             most_significant: integer,
-            //trailing: Vec::<u64>::new(),
+            trailing: Vec::<u64>::new(),
+        };
+    }
+
+    // apetypes::I::is_negative() - Check if the integer is below zero
+    //
+    pub fn is_negative(&self) -> bool
+    {
+        return self.most_significant < 0;
+    }
+
+    // apetypes::I::to_i64() - Get the integer as an i64, returns None if it doesn't fit
+    //
+    pub fn to_i64(&self) -> Option<i64>
+    {
+        if self.trailing.len() > 0
+        {
+            return None;
+        }
+
+        return Some(self.most_significant);
+    }
+
+    // apetypes::I::checked_div() - Divide, rounding towards zero, returns None when dividing by zero
+    //
+    // ARGUMENTS:
+    //  divisor: &I - The integer to divide by
+    pub fn checked_div(&self, divisor: &I) -> Option<I>
+    {
+        return self.checked_div_rem(divisor).map(|(quotient, _)| quotient);
+    }
+
+    // apetypes::I::checked_rem() - Get the remainder of a division, with the sign of self, returns None when dividing by zero
+    //
+    // ARGUMENTS:
+    //  divisor: &I - The integer to divide by
+    pub fn checked_rem(&self, divisor: &I) -> Option<I>
+    {
+        return self.checked_div_rem(divisor).map(|(_, remainder)| remainder);
+    }
+
+    // apetypes::I::checked_div_rem() - Get the quotient and remainder of a division, returns None when dividing by zero
+    //
+    // ARGUMENTS:
+    //  divisor: &I - The integer to divide by
+    fn checked_div_rem(&self, divisor: &I) -> Option<(I, I)>
+    {
+        let (negative_a, magnitude_a) = self.to_sign_magnitude();
+        let (negative_b, magnitude_b) = divisor.to_sign_magnitude();
+
+        if magnitude_b.len() == 0
+        {
+            return None;
+        }
+
+        let (quotient, remainder) = magnitude_div_rem(&magnitude_a, &magnitude_b);
+
+        return Some
+        (
+            (
+                I::from_sign_magnitude(negative_a != negative_b, quotient),
+                I::from_sign_magnitude(negative_a, remainder)
+            )
+        );
+    }
+
+    // apetypes::I::to_sign_magnitude() - Split the integer into a sign and a magnitude, least significant limb first
+    //
+    fn to_sign_magnitude(&self) -> (bool, Vec<u64>)
+    {
+        let mut limbs = Vec::<u64>::with_capacity(self.trailing.len() + 1);
+
+        for limb in self.trailing.iter().rev()
+        {
+            limbs.push(*limb);
+        }
+
+        limbs.push(self.most_significant as u64);
+
+        let negative = self.is_negative();
+
+        if negative
+        {
+            twos_complement(&mut limbs);
+        }
+
+        trim_magnitude(&mut limbs);
+
+        return (negative, limbs);
+    }
+
+    // apetypes::I::from_sign_magnitude() - Build a normalized integer from a sign and a magnitude, least significant limb first
+    //
+    // ARGUMENTS:
+    //  negative: bool - Whether the integer is below zero
+    //  magnitude: Vec<u64> - The magnitude of the integer
+    fn from_sign_magnitude(negative: bool, mut magnitude: Vec<u64>) -> I
+    {
+        trim_magnitude(&mut magnitude);
+
+        // Zero has no sign
+        if magnitude.len() == 0
+        {
+            return I::new(0);
+        }
+
+        // Make room for the sign bit, then turn the magnitude into two's complement
+        magnitude.push(0);
+
+        if negative
+        {
+            twos_complement(&mut magnitude);
+        }
+
+        // Drop the top limbs that only repeat the sign of the limb below them
+        while magnitude.len() > 1
+        {
+            let top = magnitude[magnitude.len() - 1];
+            let below_negative = (magnitude[magnitude.len() - 2] >> 63) == 1;
+
+            if (top == 0 && !below_negative) || (top == u64::MAX && below_negative)
+            {
+                magnitude.pop();
+            }
+            else
+            {
+                break;
+            }
+        }
+
+        let most_significant = magnitude.pop().expect("Integer without limbs! You shouldn't see this!") as i64;
+        magnitude.reverse();
+
+        return I
+        {
+            most_significant: most_significant,
+            trailing: magnitude,
         };
     }
 
-    // apetypes::I::to_bytes() - Convert an I to a byte array
+    // apetypes::I::to_bytes() - Convert an I to a byte array that sorts the same way as the integer when compared byte by byte
     //
+    // Layout: a header byte followed by the magnitude, big endian with no leading zeros. Positive integers have a header
+    // of 0x80 + the magnitude length, negative integers 0x7F - the magnitude length and a bitwise inverted magnitude.
+    // Magnitudes of 127 bytes or more use a header of 0xFF (or 0x00) followed by the length as a u64 (inverted if negative).
     pub fn to_bytes(&self) -> Vec<u8>
     {
-        let mut data = Vec::<u8>::new();
+        let (negative, magnitude) = self.to_sign_magnitude();
 
-        data.extend_from_slice(&self.most_significant.to_be_bytes());
+        let mut magnitude_data = Vec::<u8>::with_capacity(magnitude.len() * 8);
+
+        for limb in magnitude.iter().rev()
+        {
+            magnitude_data.extend_from_slice(&limb.to_be_bytes());
+        }
+
+        // Strip the leading zeros so the length decides the order first
+        let leading_zeros = magnitude_data.iter().take_while(|byte| **byte == 0).count();
+        magnitude_data.drain(..leading_zeros);
+
+        let length = magnitude_data.len();
+        let mut data = Vec::<u8>::with_capacity(length + 9);
+
+        if negative
+        {
+            if length < I_LONG_LENGTH
+            {
+                data.push(0x7F - length as u8);
+            }
+            else
+            {
+                data.push(0x00);
+                data.extend_from_slice(&(!(length as u64)).to_be_bytes());
+            }
+
+            for byte in magnitude_data
+            {
+                data.push(!byte);
+            }
+        }
+        else
+        {
+            if length < I_LONG_LENGTH
+            {
+                data.push(0x80 + length as u8);
+            }
+            else
+            {
+                data.push(0xFF);
+                data.extend_from_slice(&(length as u64).to_be_bytes());
+            }
+
+            data.extend_from_slice(&magnitude_data);
+        }
 
         return data;
     }
@@ -112,43 +552,1641 @@ impl I
     //  bytes: &[u8] - The byte array to convert
     pub fn from_bytes(bytes: &[u8]) -> Result<I, Box<dyn Error>>
     {
-        return Ok
-        (
-            I
+        if bytes.len() == 0
+        {
+            bail!("Integer is missing its header!");
+        }
+
+        let header = bytes[0];
+        let negative = header < 0x80;
+        let mut i: usize = 1;
+
+        let length = match header
+        {
+            0x00 | 0xFF =>
             {
-                most_significant: i64::from_be_bytes(bytes[0..8].try_into().unwrap()),
-                //trailing: Vec::<u64>::new(),
+                if bytes.len() < 9
+                {
+                    bail!("Integer is missing its length!");
+                }
+
+                let length = u64::from_be_bytes(bytes[1..9].try_into().expect("Slice of incorrect size when reading an integer length, you shouldn't see this!"));
+                i = 9;
+
+                if negative { !length } else { length }
             }
-        );
+            _ =>
+            {
+                if negative { (0x7F - header) as u64 } else { (header - 0x80) as u64 }
+            }
+        };
+
+        if (bytes.len() - i) as u64 != length
+        {
+            bail!("Integer should be {} bytes long, but is {}!", length, bytes.len() - i);
+        }
+
+        // Rebuild the limbs from the least significant end
+        let mut magnitude = Vec::<u64>::with_capacity(bytes.len() / 8 + 1);
+        let mut limb_data = [0u8; 8];
+
+        for chunk in bytes[i..].rchunks(8)
+        {
+            limb_data.fill(0);
+            limb_data[8 - chunk.len()..].copy_from_slice(chunk);
+
+            let limb = u64::from_be_bytes(limb_data);
+
+            if negative
+            {
+                // Only the bytes that were actually stored are inverted
+                magnitude.push(!limb & (u64::MAX >> (8 * (8 - chunk.len()))));
+            }
+            else
+            {
+                magnitude.push(limb);
+            }
+        }
+
+        return Ok(I::from_sign_magnitude(negative, magnitude));
     }
 }
 
-// apetypes::B - Database boolean type
-//
-#[derive(Debug, Clone, PartialEq, PartialOrd)]
-pub struct B
+impl From<i64> for I
 {
-    boolean: bool, // The boolean value
+    fn from(integer: i64) -> I
+    {
+        return I::new(integer);
+    }
 }
 
-impl B
+impl Ord for I
 {
-    // apetypes::B::new(bool) - Create a new boolean
-    //
-    // ARGUMENTS:
-    //  boolean: bool - The boolean boolean to store
-    pub fn new(boolean: bool) -> B
+    fn cmp(&self, other: &I) -> Ordering
     {
-        return B
+        let (negative_a, magnitude_a) = self.to_sign_magnitude();
+        let (negative_b, magnitude_b) = other.to_sign_magnitude();
+
+        match (negative_a, negative_b)
         {
-            boolean: boolean,
-        };
+            (false, true) => return Ordering::Greater,
+            (true, false) => return Ordering::Less,
+            (false, false) => return magnitude_cmp(&magnitude_a, &magnitude_b),
+            (true, true) => return magnitude_cmp(&magnitude_b, &magnitude_a),
+        }
     }
+}
 
-    // apetypes::B::is_true() - Check if the boolean is true
-    //
-    pub fn is_true(&self) -> bool
+impl PartialOrd for I
+{
+    fn partial_cmp(&self, other: &I) -> Option<Ordering>
     {
-        return self.boolean;
+        return Some(self.cmp(other));
+    }
+}
+
+impl Add for &I
+{
+    type Output = I;
+
+    fn add(self, other: &I) -> I
+    {
+        let (negative_a, magnitude_a) = self.to_sign_magnitude();
+        let (negative_b, magnitude_b) = other.to_sign_magnitude();
+
+        if negative_a == negative_b
+        {
+            return I::from_sign_magnitude(negative_a, magnitude_add(&magnitude_a, &magnitude_b));
+        }
+
+        // Different signs, so subtract the smaller magnitude from the bigger one and keep its sign
+        match magnitude_cmp(&magnitude_a, &magnitude_b)
+        {
+            Ordering::Less => return I::from_sign_magnitude(negative_b, magnitude_sub(&magnitude_b, &magnitude_a)),
+            _ => return I::from_sign_magnitude(negative_a, magnitude_sub(&magnitude_a, &magnitude_b)),
+        }
+    }
+}
+
+impl Sub for &I
+{
+    type Output = I;
+
+    fn sub(self, other: &I) -> I
+    {
+        return self + &(-other);
+    }
+}
+
+impl Neg for &I
+{
+    type Output = I;
+
+    fn neg(self) -> I
+    {
+        let (negative, magnitude) = self.to_sign_magnitude();
+
+        return I::from_sign_magnitude(!negative, magnitude);
+    }
+}
+
+impl Mul for &I
+{
+    type Output = I;
+
+    fn mul(self, other: &I) -> I
+    {
+        let (negative_a, magnitude_a) = self.to_sign_magnitude();
+        let (negative_b, magnitude_b) = other.to_sign_magnitude();
+
+        return I::from_sign_magnitude(negative_a != negative_b, magnitude_mul(&magnitude_a, &magnitude_b));
     }
-}
\ No newline at end of file
+}
+
+impl Div for &I
+{
+    type Output = I;
+
+    fn div(self, other: &I) -> I
+    {
+        return self.checked_div(other).expect("Attempt to divide an I by zero!");
+    }
+}
+
+impl Rem for &I
+{
+    type Output = I;
+
+    fn rem(self, other: &I) -> I
+    {
+        return self.checked_rem(other).expect("Attempt to divide an I by zero!");
+    }
+}
+
+impl fmt::Display for I
+{
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result
+    {
+        let (negative, mut magnitude) = self.to_sign_magnitude();
+
+        // Peel off 19 digits at a time, the most that fit in a u64
+        let mut groups = Vec::<u64>::new();
+
+        while magnitude.len() > 0
+        {
+            groups.push(magnitude_div_small(&mut magnitude, I_DECIMAL_GROUP));
+        }
+
+        let mut string = String::new();
+
+        if negative
+        {
+            string.push('-');
+        }
+
+        match groups.pop()
+        {
+            Some(group) => string += &group.to_string(),
+            None => string.push('0'),
+        }
+
+        for group in groups.iter().rev()
+        {
+            string += &format!("{:019}", group);
+        }
+
+        return write!(f, "{}", string);
+    }
+}
+
+impl FromStr for I
+{
+    type Err = Box<dyn Error>;
+
+    fn from_str(string: &str) -> Result<I, Box<dyn Error>>
+    {
+        let (negative, digits) = match string.strip_prefix('-')
+        {
+            Some(digits) => (true, digits),
+            None => (false, string.strip_prefix('+').unwrap_or(string)),
+        };
+
+        if digits.len() == 0 || !digits.bytes().all(|digit| digit.is_ascii_digit())
+        {
+            bail!("\"{}\" is not a decimal integer!", string);
+        }
+
+        let mut magnitude = Vec::<u64>::new();
+
+        // Feed the digits in 19 at a time, starting with whatever is left over at the front
+        let mut start = 0;
+        let mut end = digits.len() % 19;
+
+        if end == 0
+        {
+            end = 19;
+        }
+
+        while start < digits.len()
+        {
+            let group: u64 = digits[start..end].parse()?;
+            magnitude_mul_small_add(&mut magnitude, 10u64.pow((end - start) as u32), group);
+
+            start = end;
+            end += 19;
+        }
+
+        return Ok(I::from_sign_magnitude(negative, magnitude));
+    }
+}
+
+// Functions!
+//
+
+
+
+// apetypes::parse_digits() - Parse a fixed number of decimal digits out of a string
+//
+// ARGUMENTS:
+//  string: &str - The string to parse from
+//  start: usize - Where the digits start
+//  count: usize - How many digits there are
+fn parse_digits(string: &str, start: usize, count: usize) -> Result<u32, Box<dyn Error>>
+{
+    let digits = match string.as_bytes().get(start..start + count)
+    {
+        Some(digits) => digits,
+        None => bail!("\"{}\" is too short!", string),
+    };
+
+    let mut value: u32 = 0;
+
+    for digit in digits
+    {
+        if !digit.is_ascii_digit()
+        {
+            bail!("\"{}\" has a non digit where a digit should be!", string);
+        }
+
+        value = value * 10 + (digit - b'0') as u32;
+    }
+
+    return Ok(value);
+}
+
+// apetypes::days_in_month() - Get the number of days in a month
+//
+// ARGUMENTS:
+//  year: i32 - The year, for leap years
+//  month: u32 - The month, from 1 to 12
+fn days_in_month(year: i32, month: u32) -> u32
+{
+    match month
+    {
+        2 if (year % 4 == 0 && year % 100 != 0) || year % 400 == 0 => return 29,
+        2 => return 28,
+        4 | 6 | 9 | 11 => return 30,
+        _ => return 31,
+    }
+}
+
+// apetypes::days_from_civil() - Get the days since 1970-01-01 of a date, see https://howardhinnant.github.io/date_algorithms.html
+//
+// ARGUMENTS:
+//  year: i32 - The year
+//  month: u32 - The month, from 1 to 12
+//  day: u32 - The day of the month
+fn days_from_civil(year: i32, month: u32, day: u32) -> i64
+{
+    // Count years from March so the leap day is at the end of the year
+    let year = if month <= 2 { year as i64 - 1 } else { year as i64 };
+    let era = year.div_euclid(400);
+    let year_of_era = year - era * 400;
+    let day_of_year = (153 * ((month as i64 + 9) % 12) + 2) / 5 + day as i64 - 1;
+    let day_of_era = year_of_era * 365 + year_of_era / 4 - year_of_era / 100 + day_of_year;
+
+    return era * 146097 + day_of_era - 719468;
+}
+
+// apetypes::civil_from_days() - Get the date of a number of days since 1970-01-01, the inverse of days_from_civil()
+//
+// ARGUMENTS:
+//  days: i64 - Days since 1970-01-01
+fn civil_from_days(days: i64) -> (i32, u32, u32)
+{
+    let days = days + 719468;
+    let era = days.div_euclid(146097);
+    let day_of_era = days - era * 146097;
+    let year_of_era = (day_of_era - day_of_era / 1460 + day_of_era / 36524 - day_of_era / 146096) / 365;
+    let day_of_year = day_of_era - (365 * year_of_era + year_of_era / 4 - year_of_era / 100);
+    let month_from_march = (5 * day_of_year + 2) / 153;
+    let day = (day_of_year - (153 * month_from_march + 2) / 5 + 1) as u32;
+    let month = if month_from_march < 10 { month_from_march + 3 } else { month_from_march - 9 } as u32;
+    let year = year_of_era + era * 400 + if month <= 2 { 1 } else { 0 };
+
+    return (year as i32, month, day);
+}
+
+// apetypes::power_of_ten() - Get 10^exponent as an I
+//
+fn power_of_ten(exponent: u32) -> I
+{
+    let mut magnitude = vec![1u64];
+
+    for _ in 0..exponent
+    {
+        magnitude_mul_small_add(&mut magnitude, 10, 0);
+    }
+
+    return I::from_sign_magnitude(false, magnitude);
+}
+
+// apetypes::twos_complement() - Negate limbs in place, least significant limb first
+//
+// ARGUMENTS:
+//  limbs: &mut [u64] - The limbs to negate
+fn twos_complement(limbs: &mut [u64])
+{
+    let mut carry = true;
+
+    for limb in limbs.iter_mut()
+    {
+        let (sum, overflow) = (!*limb).overflowing_add(carry as u64);
+        *limb = sum;
+        carry = overflow;
+    }
+}
+
+// apetypes::trim_magnitude() - Remove the leading zero limbs of a magnitude
+//
+// ARGUMENTS:
+//  magnitude: &mut Vec<u64> - The magnitude to trim, least significant limb first
+fn trim_magnitude(magnitude: &mut Vec<u64>)
+{
+    while magnitude.last() == Some(&0)
+    {
+        magnitude.pop();
+    }
+}
+
+// apetypes::magnitude_cmp() - Compare two trimmed magnitudes
+//
+fn magnitude_cmp(a: &[u64], b: &[u64]) -> Ordering
+{
+    if a.len() != b.len()
+    {
+        return a.len().cmp(&b.len());
+    }
+
+    for i in (0..a.len()).rev()
+    {
+        if a[i] != b[i]
+        {
+            return a[i].cmp(&b[i]);
+        }
+    }
+
+    return Ordering::Equal;
+}
+
+// apetypes::magnitude_add() - Add two magnitudes
+//
+fn magnitude_add(a: &[u64], b: &[u64]) -> Vec<u64>
+{
+    let mut sum = Vec::<u64>::with_capacity(std::cmp::max(a.len(), b.len()) + 1);
+    let mut carry: u128 = 0;
+
+    for i in 0..std::cmp::max(a.len(), b.len())
+    {
+        let limb = (*a.get(i).unwrap_or(&0) as u128) + (*b.get(i).unwrap_or(&0) as u128) + carry;
+        sum.push(limb as u64);
+        carry = limb >> 64;
+    }
+
+    sum.push(carry as u64);
+    trim_magnitude(&mut sum);
+
+    return sum;
+}
+
+// apetypes::magnitude_sub() - Subtract magnitude b from magnitude a, a must not be smaller than b
+//
+fn magnitude_sub(a: &[u64], b: &[u64]) -> Vec<u64>
+{
+    let mut difference = Vec::<u64>::with_capacity(a.len());
+    let mut borrow = false;
+
+    for (i, limb_a) in a.iter().enumerate()
+    {
+        let (limb, overflow_a) = limb_a.overflowing_sub(*b.get(i).unwrap_or(&0));
+        let (limb, overflow_b) = limb.overflowing_sub(borrow as u64);
+        difference.push(limb);
+        borrow = overflow_a || overflow_b;
+    }
+
+    trim_magnitude(&mut difference);
+
+    return difference;
+}
+
+// apetypes::magnitude_mul() - Multiply two magnitudes
+//
+fn magnitude_mul(a: &[u64], b: &[u64]) -> Vec<u64>
+{
+    let mut product = vec![0u64; a.len() + b.len()];
+
+    for i in 0..a.len()
+    {
+        let mut carry: u128 = 0;
+
+        for j in 0..b.len()
+        {
+            let limb = (a[i] as u128) * (b[j] as u128) + (product[i + j] as u128) + carry;
+            product[i + j] = limb as u64;
+            carry = limb >> 64;
+        }
+
+        product[i + b.len()] = carry as u64;
+    }
+
+    trim_magnitude(&mut product);
+
+    return product;
+}
+
+// apetypes::magnitude_div_rem() - Divide magnitude a by magnitude b one bit at a time, b must not be zero
+//
+fn magnitude_div_rem(a: &[u64], b: &[u64]) -> (Vec<u64>, Vec<u64>)
+{
+    let mut quotient = vec![0u64; a.len()];
+    let mut remainder = Vec::<u64>::new();
+
+    for bit in (0..a.len() * 64).rev()
+    {
+        // remainder = remainder * 2 + the next bit of a
+        remainder = magnitude_add(&remainder, &remainder);
+
+        if (a[bit / 64] >> (bit % 64)) & 1 == 1
+        {
+            remainder = magnitude_add(&remainder, &[1]);
+        }
+
+        if magnitude_cmp(&remainder, b) != Ordering::Less
+        {
+            remainder = magnitude_sub(&remainder, b);
+            quotient[bit / 64] |= 1 << (bit % 64);
+        }
+    }
+
+    trim_magnitude(&mut quotient);
+
+    return (quotient, remainder);
+}
+
+// apetypes::magnitude_div_small() - Divide a magnitude by a u64 in place, returning the remainder
+//
+fn magnitude_div_small(magnitude: &mut Vec<u64>, divisor: u64) -> u64
+{
+    let mut remainder: u128 = 0;
+
+    for limb in magnitude.iter_mut().rev()
+    {
+        let dividend = (remainder << 64) | (*limb as u128);
+        *limb = (dividend / divisor as u128) as u64;
+        remainder = dividend % divisor as u128;
+    }
+
+    trim_magnitude(magnitude);
+
+    return remainder as u64;
+}
+
+// apetypes::magnitude_mul_small_add() - Multiply a magnitude by a u64 and add another u64 to it in place
+//
+fn magnitude_mul_small_add(magnitude: &mut Vec<u64>, multiplier: u64, addend: u64)
+{
+    let mut carry: u128 = addend as u128;
+
+    for limb in magnitude.iter_mut()
+    {
+        let product = (*limb as u128) * (multiplier as u128) + carry;
+        *limb = product as u64;
+        carry = product >> 64;
+    }
+
+    magnitude.push(carry as u64);
+    trim_magnitude(magnitude);
+}
+
+// apetypes::F - Database floating point type, an IEEE-754 double
+//
+// Floats are ordered and compared with f64::total_cmp, so -0.0 sorts below 0.0 and NaN sorts above infinity. This is
+// the same order their bytes sort in on disk.
+#[derive(Debug, Clone)]
+pub struct F
+{
+    float: f64, // The float
+}
+
+impl F
+{
+    // apetypes::F::new() - Create a new F from an f64
+    //
+    // ARGUMENTS:
+    //  float: f64 - The float to store
+    pub fn new(float: f64) -> F
+    {
+        return F
+        {
+            float: float,
+        };
+    }
+
+    // apetypes::F::to_f64() - Get the float as an f64
+    //
+    pub fn to_f64(&self) -> f64
+    {
+        return self.float;
+    }
+
+    // apetypes::F::to_bytes() - Convert an F to a byte array that sorts the same way as the float when compared byte by byte
+    //
+    pub fn to_bytes(&self) -> Vec<u8>
+    {
+        let bits = self.float.to_bits();
+
+        // Flip every bit of negative floats so bigger magnitudes sort lower, and just the sign bit of positive floats
+        let sortable = if (bits >> 63) == 1 { !bits } else { bits | (1 << 63) };
+
+        return sortable.to_be_bytes().to_vec();
+    }
+
+    // apetypes::F::from_bytes() - Convert a byte array to an F
+    //
+    // ARGUMENTS:
+    //  bytes: &[u8] - The byte array to convert
+    pub fn from_bytes(bytes: &[u8]) -> Result<F, Box<dyn Error>>
+    {
+        if bytes.len() != 8
+        {
+            bail!("Float is {} bytes long, expected 8!", bytes.len());
+        }
+
+        let sortable = u64::from_be_bytes(bytes.try_into().expect("Slice of incorrect size when reading a float, you shouldn't see this!"));
+        let bits = if (sortable >> 63) == 1 { sortable & !(1 << 63) } else { !sortable };
+
+        return Ok(F::new(f64::from_bits(bits)));
+    }
+}
+
+impl PartialEq for F
+{
+    fn eq(&self, other: &F) -> bool
+    {
+        return self.float.total_cmp(&other.float) == Ordering::Equal;
+    }
+}
+
+impl PartialOrd for F
+{
+    fn partial_cmp(&self, other: &F) -> Option<Ordering>
+    {
+        return Some(self.float.total_cmp(&other.float));
+    }
+}
+
+// apetypes::D - Database fixed point decimal type
+//
+// The value is unscaled / 10^scale, and unscaled may have at most precision digits. Decimals compare by value alone, so
+// 1.5 and 1.50 are equal, like they would be in SQL.
+#[derive(Debug, Clone)]
+pub struct D
+{
+    unscaled: I, // The value without its decimal point
+    precision: u32, // The most digits the value may have
+    scale: u32, // How many of those digits come after the decimal point
+}
+
+impl D
+{
+    // apetypes::D::new() - Create a new D from an unscaled integer
+    //
+    // ARGUMENTS:
+    //  unscaled: I - The value without its decimal point, 12345 with a scale of 2 is 123.45
+    //  precision: u32 - The most digits the value may have
+    //  scale: u32 - How many of those digits come after the decimal point
+    pub fn new(unscaled: I, precision: u32, scale: u32) -> Result<D, Box<dyn Error>>
+    {
+        if precision == 0 || precision > D_MAX_PRECISION || scale > precision
+        {
+            bail!("Invalid decimal precision {} and scale {}!", precision, scale);
+        }
+
+        let digits = unscaled.to_string().trim_start_matches('-').len() as u32;
+
+        if unscaled != I::new(0) && digits > precision
+        {
+            bail!("{} has more than {} digits!", unscaled, precision);
+        }
+
+        return Ok
+        (
+            D
+            {
+                unscaled: unscaled,
+                precision: precision,
+                scale: scale,
+            }
+        );
+    }
+
+    // apetypes::D::parse() - Create a new D from a decimal string such as "-123.45"
+    //
+    // ARGUMENTS:
+    //  string: &str - The decimal string, it may not have more than scale digits after the decimal point
+    //  precision: u32 - The most digits the value may have
+    //  scale: u32 - How many of those digits come after the decimal point
+    pub fn parse(string: &str, precision: u32, scale: u32) -> Result<D, Box<dyn Error>>
+    {
+        if precision > D_MAX_PRECISION || scale > precision
+        {
+            bail!("Invalid decimal precision {} and scale {}!", precision, scale);
+        }
+
+        let (whole, fraction) = match string.split_once('.')
+        {
+            Some((whole, fraction)) => (whole, fraction),
+            None => (string, ""),
+        };
+
+        if fraction.len() > scale as usize || !fraction.bytes().all(|digit| digit.is_ascii_digit())
+        {
+            bail!("\"{}\" doesn't fit a scale of {}!", string, scale);
+        }
+
+        // Pad the fraction out to the scale and parse the digits as one integer
+        let mut unscaled = whole.to_string() + fraction;
+        unscaled += &"0".repeat(scale as usize - fraction.len());
+
+        if whole.len() == 0 || whole == "-" || whole == "+"
+        {
+            bail!("\"{}\" is not a decimal!", string);
+        }
+
+        return D::new(unscaled.parse()?, precision, scale);
+    }
+
+    // apetypes::D::precision() - Get the most digits the value may have
+    //
+    pub fn precision(&self) -> u32
+    {
+        return self.precision;
+    }
+
+    // apetypes::D::scale() - Get how many digits come after the decimal point
+    //
+    pub fn scale(&self) -> u32
+    {
+        return self.scale;
+    }
+
+    // apetypes::D::unscaled() - Get the value without its decimal point
+    //
+    pub fn unscaled(&self) -> &I
+    {
+        return &self.unscaled;
+    }
+
+    // apetypes::D::cmp_value() - Compare the values of two decimals, ignoring their precision and scale
+    //
+    // ARGUMENTS:
+    //  other: &D - The decimal to compare with
+    pub fn cmp_value(&self, other: &D) -> Ordering
+    {
+        // Bring both decimals to the same scale before comparing
+        let scale = std::cmp::max(self.scale, other.scale);
+        let a = &self.unscaled * &power_of_ten(scale - self.scale);
+        let b = &other.unscaled * &power_of_ten(scale - other.scale);
+
+        return a.cmp(&b);
+    }
+
+    // apetypes::D::to_bytes() - Convert a D to a byte array that sorts by value when compared byte by byte
+    //
+    // Layout: a sign byte (0x01 negative, 0x02 zero, 0x03 positive), then for non zero values the decimal exponent e as
+    // an I and the significant digits d in pairs, offset by one and terminated by 0x00, such that the value is
+    // 0.d * 10^e. Negative values have the exponent, digits and terminator inverted. Scale and precision follow as u32s,
+    // so equal values only sort as equal without them, see sortable_bytes().
+    pub fn to_bytes(&self) -> Vec<u8>
+    {
+        let mut data = Vec::<u8>::new();
+        let string = self.unscaled.to_string();
+        let negative = self.unscaled.is_negative();
+        let digits = string.trim_start_matches('-');
+
+        if self.unscaled == I::new(0)
+        {
+            data.push(0x02);
+        }
+        else
+        {
+            let exponent = I::new(digits.len() as i64 - self.scale as i64);
+            let significant = digits.trim_end_matches('0').as_bytes();
+
+            let mut sortable = exponent.to_bytes();
+
+            for pair in significant.chunks(2)
+            {
+                let high = pair[0] - b'0';
+                let low = if pair.len() > 1 { pair[1] - b'0' } else { 0 };
+
+                sortable.push(high * 10 + low + 1);
+            }
+
+            sortable.push(0x00);
+
+            if negative
+            {
+                data.push(0x01);
+
+                for byte in sortable
+                {
+                    data.push(!byte);
+                }
+            }
+            else
+            {
+                data.push(0x03);
+                data.extend_from_slice(&sortable);
+            }
+        }
+
+        data.extend_from_slice(&self.scale.to_be_bytes());
+        data.extend_from_slice(&self.precision.to_be_bytes());
+
+        return data;
+    }
+
+    // apetypes::D::sortable_bytes() - Get the part of an encoded D that sorts by value, without its scale and precision
+    //
+    // ARGUMENTS:
+    //  bytes: &[u8] - The encoded D, see to_bytes()
+    pub fn sortable_bytes(bytes: &[u8]) -> &[u8]
+    {
+        return &bytes[..bytes.len().saturating_sub(8)];
+    }
+
+    // apetypes::D::from_bytes() - Convert a byte array to a D
+    //
+    // ARGUMENTS:
+    //  bytes: &[u8] - The byte array to convert
+    pub fn from_bytes(bytes: &[u8]) -> Result<D, Box<dyn Error>>
+    {
+        if bytes.len() < 9
+        {
+            bail!("Decimal is too short!");
+        }
+
+        let (sortable, tail) = bytes.split_at(bytes.len() - 8);
+        let scale = u32::from_be_bytes(tail[0..4].try_into().expect("Slice of incorrect size when reading a decimal scale, you shouldn't see this!"));
+        let precision = u32::from_be_bytes(tail[4..8].try_into().expect("Slice of incorrect size when reading a decimal precision, you shouldn't see this!"));
+
+        if precision > D_MAX_PRECISION || scale > precision
+        {
+            bail!("Invalid decimal precision {} and scale {}!", precision, scale);
+        }
+
+        let negative = match sortable[0]
+        {
+            0x01 => true,
+            0x02 => return D::new(I::new(0), precision, scale),
+            0x03 => false,
+            sign => bail!("Invalid decimal sign byte 0x{:02X}!", sign),
+        };
+
+        // Undo the inversion of negative values so both signs decode the same way
+        let mut data = sortable[1..].to_vec();
+
+        if negative
+        {
+            for byte in data.iter_mut()
+            {
+                *byte = !*byte;
+            }
+        }
+
+        if data.last() != Some(&0x00)
+        {
+            bail!("Decimal digits aren't terminated!");
+        }
+
+        data.pop();
+
+        // The exponent is a self delimiting I, find where it ends by its header
+        let exponent_length = match data.first()
+        {
+            Some(0x00) | Some(0xFF) => bail!("Decimal exponent out of range!"),
+            Some(header) if *header >= 0x80 => 1 + (*header - 0x80) as usize,
+            Some(header) => 1 + (0x7F - *header) as usize,
+            None => bail!("Decimal is missing its exponent!"),
+        };
+
+        if exponent_length > data.len()
+        {
+            bail!("Decimal exponent is truncated!");
+        }
+
+        let exponent = match I::from_bytes(&data[..exponent_length])?.to_i64()
+        {
+            Some(exponent) => exponent,
+            None => bail!("Decimal exponent out of range!"),
+        };
+
+        let mut digits = String::new();
+
+        if negative
+        {
+            digits.push('-');
+        }
+
+        for pair in &data[exponent_length..]
+        {
+            if *pair == 0 || *pair > 100
+            {
+                bail!("Invalid decimal digit pair 0x{:02X}!", pair);
+            }
+
+            digits += &format!("{:02}", pair - 1);
+        }
+
+        // The value is 0.digits * 10^exponent, work out how many zeros the unscaled integer needs
+        let significant = digits.trim_start_matches('-').len() as i64;
+        let zeros = match exponent.checked_sub(significant).and_then(|zeros| zeros.checked_add(scale as i64))
+        {
+            Some(zeros) => zeros,
+            None => bail!("Decimal exponent out of range!"),
+        };
+
+        // Check the digits fit before making them, a bad exponent could ask for any number of zeros
+        if significant + zeros > precision as i64
+        {
+            bail!("Decimal has more digits than its precision allows!");
+        }
+
+        if zeros < 0
+        {
+            // Only the padding zero of an odd number of digits may be dropped
+            if zeros != -1 || !digits.ends_with('0')
+            {
+                bail!("Decimal has more digits than its scale allows!");
+            }
+
+            digits.pop();
+        }
+        else
+        {
+            digits += &"0".repeat(zeros as usize);
+        }
+
+        return D::new(digits.parse()?, precision, scale);
+    }
+}
+
+impl PartialEq for D
+{
+    fn eq(&self, other: &D) -> bool
+    {
+        return self.cmp_value(other) == Ordering::Equal;
+    }
+}
+
+impl PartialOrd for D
+{
+    fn partial_cmp(&self, other: &D) -> Option<Ordering>
+    {
+        return Some(self.cmp_value(other));
+    }
+}
+
+impl fmt::Display for D
+{
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result
+    {
+        let string = self.unscaled.to_string();
+        let (sign, digits) = match string.strip_prefix('-')
+        {
+            Some(digits) => ("-", digits),
+            None => ("", &string[..]),
+        };
+
+        if self.scale == 0
+        {
+            return write!(f, "{}{}", sign, digits);
+        }
+
+        // Make sure there is at least one digit in front of the decimal point
+        let digits = format!("{:0>width$}", digits, width = self.scale as usize + 1);
+        let (whole, fraction) = digits.split_at(digits.len() - self.scale as usize);
+
+        return write!(f, "{}{}.{}", sign, whole, fraction);
+    }
+}
+
+// apetypes::T - Database time type, a UTC instant with nanosecond precision
+//
+// Instants are limited to the years 0000 to 9999 so every one of them can be written as RFC 3339.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord)]
+pub struct T
+{
+    seconds: i64, // Seconds since 1970-01-01T00:00:00Z
+    nanos: u32, // Nanoseconds into the second
+}
+
+impl T
+{
+    // apetypes::T::new() - Create a new T from the time since the unix epoch
+    //
+    // ARGUMENTS:
+    //  seconds: i64 - Seconds since 1970-01-01T00:00:00Z, negative for earlier instants
+    //  nanos: u32 - Nanoseconds into the second
+    pub fn new(seconds: i64, nanos: u32) -> Result<T, Box<dyn Error>>
+    {
+        if nanos >= NANOS_PER_SECOND
+        {
+            bail!("{} nanoseconds is more than a second!", nanos);
+        }
+
+        if !(DATE_MIN_DAYS * SECONDS_PER_DAY..(DATE_MAX_DAYS + 1) * SECONDS_PER_DAY).contains(&seconds)
+        {
+            bail!("{} seconds is outside of the years 0000 to 9999!", seconds);
+        }
+
+        return Ok
+        (
+            T
+            {
+                seconds: seconds,
+                nanos: nanos,
+            }
+        );
+    }
+
+    // apetypes::T::now() - Get the current time
+    //
+    pub fn now() -> T
+    {
+        let since_epoch = SystemTime::now().duration_since(UNIX_EPOCH).expect("System clock is set before 1970!");
+
+        return T::new(since_epoch.as_secs() as i64, since_epoch.subsec_nanos()).expect("System clock is set after 9999!");
+    }
+
+    // apetypes::T::seconds() - Get the seconds since the unix epoch
+    //
+    pub fn seconds(&self) -> i64
+    {
+        return self.seconds;
+    }
+
+    // apetypes::T::nanos() - Get the nanoseconds into the second
+    //
+    pub fn nanos(&self) -> u32
+    {
+        return self.nanos;
+    }
+
+    // apetypes::T::date() - Get the UTC date of the instant
+    //
+    pub fn date(&self) -> Date
+    {
+        return Date
+        {
+            days: self.seconds.div_euclid(SECONDS_PER_DAY) as i32,
+        };
+    }
+
+    // apetypes::T::parse_rfc3339() - Parse an RFC 3339 timestamp such as "1985-04-12T23:20:50.52Z" or "1996-12-19T16:39:57-08:00"
+    //
+    // Offsets are applied and thrown away, the T is always UTC. Leap seconds are refused.
+    //
+    // ARGUMENTS:
+    //  string: &str - The timestamp to parse
+    pub fn parse_rfc3339(string: &str) -> Result<T, Box<dyn Error>>
+    {
+        let bytes = string.as_bytes();
+
+        if !string.is_ascii() || bytes.len() < 20 || !matches!(bytes[10], b'T' | b't' | b' ') || bytes[13] != b':' || bytes[16] != b':'
+        {
+            bail!("\"{}\" is not an RFC 3339 timestamp!", string);
+        }
+
+        let date = Date::parse_rfc3339(&string[..10])?;
+        let hour = parse_digits(string, 11, 2)?;
+        let minute = parse_digits(string, 14, 2)?;
+        let second = parse_digits(string, 17, 2)?;
+
+        if hour > 23 || minute > 59 || second > 59
+        {
+            bail!("\"{}\" has an invalid time of day!", string);
+        }
+
+        let mut i = 19;
+        let mut nanos: u32 = 0;
+
+        // Fractions of a second, any digits past nanoseconds are refused rather than rounded
+        if bytes[i] == b'.'
+        {
+            let digits = bytes[i + 1..].iter().take_while(|byte| byte.is_ascii_digit()).count();
+
+            if digits == 0 || digits > 9
+            {
+                bail!("\"{}\" has an invalid fraction of a second!", string);
+            }
+
+            nanos = parse_digits(string, i + 1, digits)? * 10u32.pow(9 - digits as u32);
+            i += 1 + digits;
+        }
+
+        let offset: i64 = match &bytes[i..]
+        {
+            [b'Z'] | [b'z'] => 0,
+            [sign @ (b'+' | b'-'), _, _, b':', _, _] =>
+            {
+                let offset_hour = parse_digits(string, i + 1, 2)?;
+                let offset_minute = parse_digits(string, i + 4, 2)?;
+
+                if offset_hour > 23 || offset_minute > 59
+                {
+                    bail!("\"{}\" has an invalid offset!", string);
+                }
+
+                let offset = (offset_hour * 3600 + offset_minute * 60) as i64;
+
+                if *sign == b'-' { -offset } else { offset }
+            }
+            _ => bail!("\"{}\" is missing its offset!", string),
+        };
+
+        let seconds = date.days() * SECONDS_PER_DAY + (hour * 3600 + minute * 60 + second) as i64 - offset;
+
+        return T::new(seconds, nanos);
+    }
+
+    // apetypes::T::to_rfc3339() - Format the instant as an RFC 3339 timestamp in UTC, such as "1985-04-12T23:20:50.52Z"
+    //
+    pub fn to_rfc3339(&self) -> String
+    {
+        let second_of_day = self.seconds.rem_euclid(SECONDS_PER_DAY);
+        let mut string = format!("{}T{:02}:{:02}:{:02}", self.date(), second_of_day / 3600, (second_of_day / 60) % 60, second_of_day % 60);
+
+        if self.nanos != 0
+        {
+            string += format!(".{:09}", self.nanos).trim_end_matches('0');
+        }
+
+        return string + "Z";
+    }
+
+    // apetypes::T::to_bytes() - Convert a T to a byte array that sorts in time order when compared byte by byte
+    //
+    pub fn to_bytes(&self) -> Vec<u8>
+    {
+        // Flipping the sign bit makes negative seconds sort before positive ones
+        let mut data = ((self.seconds as u64) ^ (1 << 63)).to_be_bytes().to_vec();
+        data.extend_from_slice(&self.nanos.to_be_bytes());
+
+        return data;
+    }
+
+    // apetypes::T::from_bytes() - Convert a byte array to a T
+    //
+    // ARGUMENTS:
+    //  bytes: &[u8] - The byte array to convert
+    pub fn from_bytes(bytes: &[u8]) -> Result<T, Box<dyn Error>>
+    {
+        if bytes.len() != 12
+        {
+            bail!("Time is {} bytes long, expected 12!", bytes.len());
+        }
+
+        let seconds = u64::from_be_bytes(bytes[0..8].try_into().expect("Slice of incorrect size when reading a time, you shouldn't see this!")) ^ (1 << 63);
+        let nanos = u32::from_be_bytes(bytes[8..12].try_into().expect("Slice of incorrect size when reading a time, you shouldn't see this!"));
+
+        return T::new(seconds as i64, nanos);
+    }
+}
+
+impl fmt::Display for T
+{
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result
+    {
+        return write!(f, "{}", self.to_rfc3339());
+    }
+}
+
+impl FromStr for T
+{
+    type Err = Box<dyn Error>;
+
+    fn from_str(string: &str) -> Result<T, Box<dyn Error>>
+    {
+        return T::parse_rfc3339(string);
+    }
+}
+
+// apetypes::Date - Database date type, a day in the proleptic gregorian calendar
+//
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord)]
+pub struct Date
+{
+    days: i32, // Days since 1970-01-01
+}
+
+impl Date
+{
+    // apetypes::Date::new() - Create a new Date, throw an error if the day doesn't exist
+    //
+    // ARGUMENTS:
+    //  year: i32 - The year, from 0 to 9999
+    //  month: u32 - The month, from 1 to 12
+    //  day: u32 - The day of the month, starting at 1
+    pub fn new(year: i32, month: u32, day: u32) -> Result<Date, Box<dyn Error>>
+    {
+        if !(0..=9999).contains(&year) || !(1..=12).contains(&month) || day < 1 || day > days_in_month(year, month)
+        {
+            bail!("{:04}-{:02}-{:02} is not a valid date!", year, month, day);
+        }
+
+        return Date::from_days(days_from_civil(year, month, day));
+    }
+
+    // apetypes::Date::from_days() - Create a new Date from the days since 1970-01-01
+    //
+    // ARGUMENTS:
+    //  days: i64 - Days since 1970-01-01, negative for earlier dates
+    pub fn from_days(days: i64) -> Result<Date, Box<dyn Error>>
+    {
+        if !(DATE_MIN_DAYS..=DATE_MAX_DAYS).contains(&days)
+        {
+            bail!("Day {} is outside of the years 0000 to 9999!", days);
+        }
+
+        return Ok
+        (
+            Date
+            {
+                days: days as i32,
+            }
+        );
+    }
+
+    // apetypes::Date::days() - Get the days since 1970-01-01
+    //
+    pub fn days(&self) -> i64
+    {
+        return self.days as i64;
+    }
+
+    // apetypes::Date::ymd() - Get the year, month and day of the date
+    //
+    pub fn ymd(&self) -> (i32, u32, u32)
+    {
+        return civil_from_days(self.days as i64);
+    }
+
+    // apetypes::Date::parse_rfc3339() - Parse an RFC 3339 full date such as "1985-04-12"
+    //
+    // ARGUMENTS:
+    //  string: &str - The date to parse
+    pub fn parse_rfc3339(string: &str) -> Result<Date, Box<dyn Error>>
+    {
+        let bytes = string.as_bytes();
+
+        if !string.is_ascii() || bytes.len() != 10 || bytes[4] != b'-' || bytes[7] != b'-'
+        {
+            bail!("\"{}\" is not an RFC 3339 date!", string);
+        }
+
+        return Date::new(parse_digits(string, 0, 4)? as i32, parse_digits(string, 5, 2)?, parse_digits(string, 8, 2)?);
+    }
+
+    // apetypes::Date::to_rfc3339() - Format the date as an RFC 3339 full date, such as "1985-04-12"
+    //
+    pub fn to_rfc3339(&self) -> String
+    {
+        let (year, month, day) = self.ymd();
+
+        return format!("{:04}-{:02}-{:02}", year, month, day);
+    }
+
+    // apetypes::Date::to_bytes() - Convert a Date to a byte array that sorts in date order when compared byte by byte
+    //
+    pub fn to_bytes(&self) -> Vec<u8>
+    {
+        return ((self.days as u32) ^ (1 << 31)).to_be_bytes().to_vec();
+    }
+
+    // apetypes::Date::from_bytes() - Convert a byte array to a Date
+    //
+    // ARGUMENTS:
+    //  bytes: &[u8] - The byte array to convert
+    pub fn from_bytes(bytes: &[u8]) -> Result<Date, Box<dyn Error>>
+    {
+        if bytes.len() != 4
+        {
+            bail!("Date is {} bytes long, expected 4!", bytes.len());
+        }
+
+        let days = u32::from_be_bytes(bytes.try_into().expect("Slice of incorrect size when reading a date, you shouldn't see this!")) ^ (1 << 31);
+
+        return Date::from_days(days as i32 as i64);
+    }
+}
+
+impl fmt::Display for Date
+{
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result
+    {
+        return write!(f, "{}", self.to_rfc3339());
+    }
+}
+
+impl FromStr for Date
+{
+    type Err = Box<dyn Error>;
+
+    fn from_str(string: &str) -> Result<Date, Box<dyn Error>>
+    {
+        return Date::parse_rfc3339(string);
+    }
+}
+
+// apetypes::B - Database boolean type
+//
+#[derive(Debug, Clone, PartialEq, PartialOrd)]
+pub struct B
+{
+    boolean: bool, // The boolean value
+}
+
+impl B
+{
+    // apetypes::B::new(bool) - Create a new boolean
+    //
+    // ARGUMENTS:
+    //  boolean: bool - The boolean boolean to store
+    pub fn new(boolean: bool) -> B
+    {
+        return B
+        {
+            boolean: boolean,
+        };
+    }
+
+    // apetypes::B::is_true() - Check if the boolean is true
+    //
+    pub fn is_true(&self) -> bool
+    {
+        return self.boolean;
+    }
+}
+
+
+
+// Tests!
+//
+
+#[cfg(test)]
+mod tests
+{
+    use super::*;
+
+    // apetypes::tests::int() - Parse an I from a decimal string
+    //
+    fn int(string: &str) -> I
+    {
+        return string.parse().unwrap();
+    }
+
+    // apetypes::tests::test_i_decimal_round_trip() - Tests converting I to and from decimal strings
+    //
+    #[test]
+    fn test_i_decimal_round_trip()
+    {
+        for string in ["0", "1", "-1", "9223372036854775807", "-9223372036854775808", "18446744073709551616", "-340282366920938463463374607431768211457", "1000000000000000000000000000000000000000000000000000000000001"]
+        {
+            assert_eq!(int(string).to_string(), string);
+        }
+
+        assert_eq!(int("+42"), I::new(42));
+        assert_eq!(int("-0"), I::new(0));
+        assert!("".parse::<I>().is_err());
+        assert!("12a".parse::<I>().is_err());
+        assert!("-".parse::<I>().is_err());
+    }
+
+    // apetypes::tests::test_i_arithmetic() - Tests arithmetic past the range of an i64
+    //
+    #[test]
+    fn test_i_arithmetic()
+    {
+        let max = I::new(i64::MAX);
+        let min = I::new(i64::MIN);
+
+        assert_eq!(&max + &I::new(1), int("9223372036854775808"));
+        assert_eq!(&min - &I::new(1), int("-9223372036854775809"));
+        assert_eq!(&(&max + &I::new(1)) - &I::new(1), max);
+        assert_eq!((&max + &I::new(1)).to_i64(), None);
+        assert_eq!(&max * &max, int("85070591730234615847396907784232501249"));
+        assert_eq!(&min * &I::new(-1), int("9223372036854775808"));
+        assert_eq!(&int("85070591730234615847396907784232501249") / &max, max);
+        assert_eq!(&int("-100000000000000000000000000007") / &int("10000000000000"), int("-10000000000000000"));
+        assert_eq!(&int("-100000000000000000000000000007") % &int("10000000000000"), I::new(-7));
+        assert_eq!(&I::new(7) / &I::new(-2), I::new(-3));
+        assert_eq!(&I::new(7) % &I::new(-2), I::new(1));
+        assert_eq!(-&min, int("9223372036854775808"));
+        assert_eq!(I::new(5).checked_div(&I::new(0)), None);
+    }
+
+    // apetypes::tests::test_i_ordering() - Tests comparing integers in memory and as bytes
+    //
+    #[test]
+    fn test_i_ordering()
+    {
+        let mut integers: Vec<I> = vec!
+        [
+            "-1000000000000000000000000000000000000000", "-18446744073709551616", "-9223372036854775809", "-256", "-255", "-1",
+            "0", "1", "127", "128", "255", "256", "9223372036854775807", "18446744073709551616", "1000000000000000000000000000000000000000",
+        ].iter().map(|string| int(string)).collect();
+
+        // A magnitude long enough to need the long length header
+        integers.insert(0, -&int(&"9".repeat(400)));
+        integers.push(int(&"9".repeat(400)));
+
+        for i in 1..integers.len()
+        {
+            assert!(integers[i - 1] < integers[i], "{} < {}", integers[i - 1], integers[i]);
+            assert!(integers[i - 1].to_bytes() < integers[i].to_bytes(), "{} < {} as bytes", integers[i - 1], integers[i]);
+        }
+    }
+
+    // apetypes::tests::test_i_bytes_round_trip() - Tests converting integers to bytes and back
+    //
+    #[test]
+    fn test_i_bytes_round_trip()
+    {
+        for string in ["0", "1", "-1", "255", "-256", "9223372036854775807", "-9223372036854775808", "-340282366920938463463374607431768211457"]
+        {
+            assert_eq!(I::from_bytes(&int(string).to_bytes()).unwrap(), int(string));
+        }
+
+        let long = -&int(&"7".repeat(400));
+        assert_eq!(I::from_bytes(&long.to_bytes()).unwrap(), long);
+
+        assert_eq!(I::new(0).to_bytes(), vec![0x80]);
+        assert_eq!(I::new(1).to_bytes(), vec![0x81, 0x01]);
+        assert_eq!(I::new(-1).to_bytes(), vec![0x7E, 0xFE]);
+        assert!(I::from_bytes(&[0x82, 0x01]).is_err());
+        assert!(I::from_bytes(&[]).is_err());
+    }
+
+    // apetypes::tests::test_f_ordering() - Tests that floats sort the same way in memory and as bytes
+    //
+    #[test]
+    fn test_f_ordering()
+    {
+        let floats: Vec<F> = [f64::NEG_INFINITY, -1e300, -2.5, -1.0, -1e-300, -0.0, 0.0, 1e-300, 1.0, 2.5, 1e300, f64::INFINITY, f64::NAN]
+            .iter().map(|float| F::new(*float)).collect();
+
+        for i in 1..floats.len()
+        {
+            assert!(floats[i - 1] < floats[i], "{:?} < {:?}", floats[i - 1], floats[i]);
+            assert!(floats[i - 1].to_bytes() < floats[i].to_bytes(), "{:?} < {:?} as bytes", floats[i - 1], floats[i]);
+        }
+
+        for float in floats
+        {
+            assert_eq!(F::from_bytes(&float.to_bytes()).unwrap(), float);
+        }
+
+        assert!(F::from_bytes(&[0; 4]).is_err());
+    }
+
+    // apetypes::tests::test_d_parse_display() - Tests converting decimals to and from strings
+    //
+    #[test]
+    fn test_d_parse_display()
+    {
+        assert_eq!(D::parse("123.45", 5, 2).unwrap().to_string(), "123.45");
+        assert_eq!(D::parse("-0.5", 5, 2).unwrap().to_string(), "-0.50");
+        assert_eq!(D::parse("7", 5, 2).unwrap().to_string(), "7.00");
+        assert_eq!(D::parse("42", 5, 0).unwrap().to_string(), "42");
+        assert_eq!(D::parse("-0.01", 3, 2).unwrap().unscaled(), &I::new(-1));
+
+        assert!(D::parse("1.234", 5, 2).is_err()); // Too many digits after the point
+        assert!(D::parse("1234.5", 5, 2).is_err()); // Too many digits overall
+        assert!(D::parse(".5", 5, 2).is_err());
+        assert!(D::parse("1.-5", 5, 2).is_err());
+        assert!(D::new(I::new(1), 2, 3).is_err());
+        assert!(D::new(I::new(1), D_MAX_PRECISION + 1, 0).is_err());
+    }
+
+    // apetypes::tests::test_d_ordering() - Tests that decimals sort by value in memory and as bytes, whatever their scale
+    //
+    #[test]
+    fn test_d_ordering()
+    {
+        let decimals = vec!
+        [
+            D::parse("-1000.5", 10, 1).unwrap(),
+            D::parse("-999.99", 10, 2).unwrap(),
+            D::parse("-1", 10, 0).unwrap(),
+            D::parse("-0.001", 10, 3).unwrap(),
+            D::parse("0", 10, 2).unwrap(),
+            D::parse("0.001", 10, 3).unwrap(),
+            D::parse("0.01", 10, 2).unwrap(),
+            D::parse("0.1", 10, 1).unwrap(),
+            D::parse("0.105", 10, 3).unwrap(),
+            D::parse("1.5", 10, 1).unwrap(),
+            D::parse("1.50", 10, 2).unwrap(),
+            D::parse("1.55", 10, 2).unwrap(),
+            D::parse("10", 10, 0).unwrap(),
+            D::parse("99999999.99", 10, 2).unwrap(),
+        ];
+
+        let sortable = |decimal: &D| D::sortable_bytes(&decimal.to_bytes()).to_vec();
+
+        for i in 1..decimals.len()
+        {
+            if i == 10
+            {
+                continue; // 1.5 and 1.50
+            }
+
+            assert!(decimals[i - 1] < decimals[i], "{} < {}", decimals[i - 1], decimals[i]);
+            assert!(sortable(&decimals[i - 1]) < sortable(&decimals[i]), "{} < {} as bytes", decimals[i - 1], decimals[i]);
+        }
+
+        // Equal values are equal whatever their scale and precision, in memory and as bytes
+        assert_eq!(decimals[9], decimals[10]);
+        assert_eq!(decimals[9].partial_cmp(&decimals[10]), Some(Ordering::Equal));
+        assert_eq!(sortable(&decimals[9]), sortable(&decimals[10]));
+        assert_eq!(D::parse("0.5", 2, 1).unwrap(), D::parse("0.50", 3, 2).unwrap());
+        assert_ne!(D::parse("0.5", 2, 1).unwrap(), D::parse("0.51", 3, 2).unwrap());
+
+        // An exponent far bigger than the precision allows is refused before any digits are made
+        let mut huge = D::parse("1", 1, 0).unwrap().to_bytes();
+        huge.splice(1..3, I::new(i64::MAX).to_bytes());
+        assert!(D::from_bytes(&huge).is_err());
+
+        // Scale and precision still come back
+        for decimal in decimals
+        {
+            let decoded = D::from_bytes(&decimal.to_bytes()).unwrap();
+            assert_eq!((decoded.unscaled(), decoded.scale(), decoded.precision()), (decimal.unscaled(), decimal.scale(), decimal.precision()));
+        }
+    }
+
+    // apetypes::tests::test_type_ordering() - Tests that values of different types sort by type
+    //
+    #[test]
+    fn test_type_ordering()
+    {
+        assert!(Type::B(Some(B::new(false))) < Type::B(Some(B::new(true))));
+        assert!(Type::B(Some(B::new(true))) < Type::D(Some(D::parse("1", 1, 0).unwrap())));
+        assert!(Type::D(Some(D::parse("1", 1, 0).unwrap())) < Type::F(Some(F::new(1.0))));
+        assert!(Type::F(Some(F::new(1.0))) < Type::I(Some(I::new(1))));
+        assert!(Type::I(Some(I::new(1))) < Type::S(Some(S::new("1"))));
+    }
+
+    // apetypes::tests::test_bin_bytes_round_trip() - Tests that binary values survive being converted to bytes and sort the same way
+    //
+    #[test]
+    fn test_bin_bytes_round_trip()
+    {
+        let bins = vec!
+        [
+            Bin::new(b""),
+            Bin::new(b"\x00\x01"),
+            Bin::new(b"\xFF"),
+            Bin::Overflow { chunk: 512, length: 100000 },
+            Bin::Overflow { chunk: 1024, length: 300 },
+        ];
+
+        for i in 1..bins.len()
+        {
+            assert!(bins[i - 1] < bins[i]);
+            assert!(bins[i - 1].to_bytes() < bins[i].to_bytes());
+        }
+
+        for bin in bins
+        {
+            assert_eq!(Bin::from_bytes(&bin.to_bytes()).unwrap(), bin);
+        }
+
+        assert!(Bin::from_bytes(&[]).is_err());
+        assert!(Bin::from_bytes(&[0x01, 0x00]).is_err());
+        assert!(Bin::from_bytes(&[0x02]).is_err());
+
+        // Anything longer than BIN_INLINE_MAX belongs in overflow chunks
+        assert!(Bin::from_bytes(&Bin::new(&[7; BIN_INLINE_MAX]).to_bytes()).is_ok());
+        assert!(Bin::from_bytes(&Bin::new(&[7; BIN_INLINE_MAX + 1]).to_bytes()).is_err());
+    }
+
+    // apetypes::tests::test_t_rfc3339() - Tests parsing and formatting RFC 3339 timestamps
+    //
+    #[test]
+    fn test_t_rfc3339()
+    {
+        assert_eq!(T::parse_rfc3339("1970-01-01T00:00:00Z").unwrap(), T::new(0, 0).unwrap());
+        assert_eq!(T::parse_rfc3339("1985-04-12T23:20:50.52Z").unwrap().to_string(), "1985-04-12T23:20:50.52Z");
+        assert_eq!(T::parse_rfc3339("1996-12-19T16:39:57-08:00").unwrap().to_string(), "1996-12-20T00:39:57Z");
+        assert_eq!(T::parse_rfc3339("1937-01-01t12:00:27.87+00:20").unwrap().to_string(), "1937-01-01T11:40:27.87Z");
+        assert_eq!(T::parse_rfc3339("2000-02-29 23:59:59.123456789z").unwrap().nanos(), 123456789);
+        assert_eq!(T::parse_rfc3339("1969-12-31T23:59:59.5Z").unwrap().seconds(), -1);
+        assert_eq!(T::parse_rfc3339("0000-01-01T00:00:00Z").unwrap().to_string(), "0000-01-01T00:00:00Z");
+        assert_eq!(T::parse_rfc3339("9999-12-31T23:59:59.999999999Z").unwrap().to_string(), "9999-12-31T23:59:59.999999999Z");
+
+        assert!(T::parse_rfc3339("1990-12-31T23:59:60Z").is_err()); // Leap second
+        assert!(T::parse_rfc3339("2001-02-29T00:00:00Z").is_err()); // Not a leap year
+        assert!(T::parse_rfc3339("2001-01-01T00:00:00").is_err()); // No offset
+        assert!(T::parse_rfc3339("2001-01-01T00:00:00.Z").is_err());
+        assert!(T::parse_rfc3339("2001-01-01T00:00:00.1234567891Z").is_err());
+        assert!(T::parse_rfc3339("2001-01-01T24:00:00Z").is_err());
+        assert!(T::parse_rfc3339("2001-01-01T00:00:00+0100").is_err());
+        assert!(T::parse_rfc3339("0000-01-01T00:00:00+00:01").is_err()); // Before the year 0000 in UTC
+        assert!(T::parse_rfc3339("2001-01-01T00:00:0é").is_err());
+    }
+
+    // apetypes::tests::test_date_rfc3339() - Tests parsing and formatting RFC 3339 dates, and converting them to days
+    //
+    #[test]
+    fn test_date_rfc3339()
+    {
+        assert_eq!(Date::parse_rfc3339("1970-01-01").unwrap().days(), 0);
+        assert_eq!(Date::parse_rfc3339("2000-03-01").unwrap().days(), 11017);
+        assert_eq!(Date::parse_rfc3339("1969-12-31").unwrap().days(), -1);
+        assert_eq!(Date::parse_rfc3339("2024-02-29").unwrap().ymd(), (2024, 2, 29));
+        assert_eq!(Date::new(0, 1, 1).unwrap().days(), DATE_MIN_DAYS);
+        assert_eq!(Date::new(9999, 12, 31).unwrap().days(), DATE_MAX_DAYS);
+
+        // Every day should survive being formatted and parsed again
+        for days in (DATE_MIN_DAYS..=DATE_MAX_DAYS).step_by(97)
+        {
+            let date = Date::from_days(days).unwrap();
+            assert_eq!(date.to_string().parse::<Date>().unwrap(), date);
+        }
+
+        assert!(Date::parse_rfc3339("1900-02-29").is_err());
+        assert!(Date::parse_rfc3339("2023-13-01").is_err());
+        assert!(Date::parse_rfc3339("2023-1-01").is_err());
+        assert!(Date::from_days(DATE_MAX_DAYS + 1).is_err());
+    }
+
+    // apetypes::tests::test_time_ordering() - Tests that times and dates sort the same way as bytes as they do in memory
+    //
+    #[test]
+    fn test_time_ordering()
+    {
+        let times: Vec<T> = ["0000-01-01T00:00:00Z", "1969-12-31T23:59:59.999Z", "1970-01-01T00:00:00Z", "1970-01-01T00:00:00.000000001Z", "2024-06-01T12:00:00Z", "9999-12-31T23:59:59Z"]
+            .iter().map(|time| time.parse().unwrap()).collect();
+
+        for i in 1..times.len()
+        {
+            assert!(times[i - 1] < times[i]);
+            assert!(times[i - 1].to_bytes() < times[i].to_bytes());
+            assert!(times[i - 1].date() <= times[i].date());
+            assert!(times[i - 1].date().to_bytes() <= times[i].date().to_bytes());
+        }
+
+        for time in times
+        {
+            assert_eq!(T::from_bytes(&time.to_bytes()).unwrap(), time);
+            assert_eq!(Date::from_bytes(&time.date().to_bytes()).unwrap(), time.date());
+        }
+
+        assert!(T::from_bytes(&[0xFF; 12]).is_err());
+        assert!(Date::from_bytes(&[0xFF; 4]).is_err());
+    }
+
+    // apetypes::tests::test_null_ordering() - Tests that nulls sort before every value
+    //
+    #[test]
+    fn test_null_ordering()
+    {
+        assert!(Type::I(None) < Type::I(Some(I::new(-1000))));
+        assert!(Type::S(None) < Type::B(Some(B::new(false))));
+        assert!(Type::B(None) < Type::S(None));
+        assert_eq!(Type::S(None).partial_cmp(&Type::S(None)), Some(Ordering::Equal));
+
+        for class in [b'I', b'S', b'B', b'F', b'D', b'X', b'T', b'A']
+        {
+            let null = Type::null(class).unwrap();
+
+            assert!(null.is_null());
+            assert_eq!(null.sort_class(), class);
+        }
+
+        assert!(Type::null(0).is_none());
+        assert!(!Type::I(Some(I::new(0))).is_null());
+    }
+}
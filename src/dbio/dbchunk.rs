@@ -12,6 +12,8 @@ use crate::dbio::dbfield::*;
 use crate::dbio::dbcrc24::*;
 use crate::dbio::dbstruct::*;
 use crate::dbio::dblist::*;
+use crate::dbio::dbuuid::UuidV4;
+use crate::dbio::dbtree::LAZY_AVL_CONST;
 use crate::apetypes::*;
 use apebdlm::*;
 
@@ -25,33 +27,92 @@ const CHUNKSZ: usize = 256; // Total size of a chunk
 const CHUNKCRCSZ: usize = 3; // Size of the chunk CRC
 const CHUNKFIELDALLOC: usize = 256; // The default amount of memory to allocate with working with individual fields
 
-const CHUNK_ENTRY_CONT_HEADSZ: usize = 9; // 1 u8 + 1 u64 = 9 bytes
-const CHUNK_ENTRY_CONT_DATASZ: usize = CHUNKSZ - (CHUNK_ENTRY_CONT_HEADSZ + CHUNKCRCSZ);
+// Every chain of chunks (entries, the header, etc.) uses the same two layouts
+const CHUNK_CONT_HEADSZ: usize = 9; // 1 u8 + 1 u64 = 9 bytes
+const CHUNK_CONT_DATASZ: usize = CHUNKSZ - (CHUNK_CONT_HEADSZ + CHUNKCRCSZ);
 
-const CHUNK_ENTRY_STUB_HEADSZ: usize = 2; // 2 u8s = 2 bytes
-const CHUNK_ENTRY_STUB_DATASZ: usize = CHUNKSZ - (CHUNK_ENTRY_STUB_HEADSZ + CHUNKCRCSZ);
+const CHUNK_STUB_HEADSZ: usize = 2; // 2 u8s = 2 bytes
+const CHUNK_STUB_DATASZ: usize = CHUNKSZ - (CHUNK_STUB_HEADSZ + CHUNKCRCSZ);
 
+const JOURNAL_COUNTSZ: usize = 4; // Journals start with the number of writes as a u32
+const JOURNAL_PATCH_HEADSZ: usize = 9; // Each write starts with its position as a u64 and its length as a u8
+
+pub const DB_FORMAT_VERSION: i64 = 9; // Version of the database file format, stored in the header
+                                     // 1: Value lengths are variable length integers
+                                     // 2: Integers are arbitrary precision and sort byte by byte
+                                     // 3: The header records its creation time, entries start with a preamble
+                                     // 4: Entry preambles start with the entry's UUID
+                                     // 5: Lists store their structure in schema chunks
+                                     // 6: Structures are versioned, entry preambles record the version they were written under
+                                     // 7: Tree changes that take more than one write go through journal chunks
+                                     // 8: Lists keep their tree heads and entry count in a list chunk
+                                     // 9: Lists index each field in its own tree, kept in an index chunk
 const DB_DEFAULT_UUID_CACHE_SIZE: i64 = 1024; // Default size of the UUID cache, 1024*16 bytes
 const DB_DEFAULT_UNIX_PERMISSIONS: i64 = 0o777; // Default unix octal permissions
 
+// Inline binary values are never written to overflow chunks, so they have to fit in a single stub chunk
+const _: () = assert!(BIN_INLINE_MAX <= CHUNK_STUB_DATASZ);
+
 // dbchunks::CHUNK_TYPE - Chunk type constants
 pub mod CHUNK_TYPE
 {
     pub const DBHEAD: u8 = 0x01; // DB header
     pub const ENTRY: u8 = 0x02; // Entry
+    pub const BIN: u8 = 0x03; // Binary data too big to keep inline in its field
+    pub const SCHEMA: u8 = 0x04; // The structure of a list
+    pub const JOURNAL: u8 = 0x05; // Writes that have to happen all together, armed by UNDER_CONSTRUCTION on the first chunk
+    pub const LIST: u8 = 0x06; // The UUID tree head and entry count of a list, always a single stub chunk
+    pub const INDEX: u8 = 0x07; // The tree head and state of an index on one field of a list, always a single stub chunk
+}
+
+// dbchunk::LIST_CHUNK_OFFSET - Where each part of a list chunk sits, from the start of the chunk
+//
+// Everything but the ID has a fixed size and place, so it can be changed in place with a single write, see ListChunk.
+pub mod LIST_CHUNK_OFFSET
+{
+    pub const UUID_TREE_HEAD: u64 = 2; // u64, right after the stub header
+    pub const ENTRY_COUNT: u64 = 10; // u64
+    pub const JOURNAL: u64 = 18; // u64
+    pub const UUID_TREE_LAZE: u64 = 26; // u8
+    pub const LIST_ID: u64 = 27; // u8 length followed by the ID
+}
+
+// dbchunk::INDEX_CHUNK_OFFSET - Where each part of an index chunk sits, from the start of the chunk
+//
+// Like list chunks everything but the IDs can be changed in place with a single write, see IndexChunk.
+pub mod INDEX_CHUNK_OFFSET
+{
+    pub const HEAD: u64 = 2; // u64, right after the stub header
+    pub const LAZE: u64 = 10; // u8
+    pub const STATE: u64 = 11; // u8, see INDEX_STATE
+    pub const LIST_ID: u64 = 12; // u8 length followed by the ID, then the field ID the same way
+}
+
+// dbchunk::INDEX_STATE - Where an index is in its life, stored in its index chunk
+//
+pub mod INDEX_STATE
+{
+    pub const BUILDING: u8 = 0x00; // Still being filled from the entries already in the list, ignored when the list is opened
+    pub const READY: u8 = 0x01; // Holds every entry of the list
+    pub const DROPPED: u8 = 0x02; // No longer kept up to date
 }
 
 pub mod CHUNK_FLAG
 {
-    pub const UNDER_CONSTRUCTION: u8 = 0b1000000;
+    pub const UNDER_CONSTRUCTION: u8 = 0b10000000;
     pub const CONTINUED: u8 = 0b01000000;
+    pub const TYPE_MASK: u8 = 0b00111111; // Mask used to strip the flags from a chunk header, leaving the type
 }
 
+// A write recorded in a journal, the position in the file and the bytes to write there, see ChunkyFile::write_journal()
+pub type JournalPatch = (u64, Vec<u8>);
+
 // Enums!
 //
 
 
-// 
+// dbchunk::ChunkTypes - Every kind of chunk that can be added to a chunky file
+//
 pub enum ChunkTypes
 {
     DbHead(DbHeadChunk),
@@ -69,11 +130,12 @@ pub struct ChunkyFile
 
 impl ChunkyFile
 {
-    // dbchunk::ChunkyFile::create() - Create a new chunky file, throw an error if it already exists
+    // dbchunk::ChunkyFile::create() - Create a new chunky file starting with the given header, throw an error if it already exists
     //
     // ARGUMENTS:
-    //  file_name: other: &[T]
-    pub fn create(file_name: &str) -> Result<ChunkyFile, Box<dyn Error>>
+    //  file_name: &str - The path of the file to create
+    //  head: DbHeadChunk - The database header, written as the first chunk
+    pub fn create(file_name: &str, head: DbHeadChunk) -> Result<ChunkyFile, Box<dyn Error>>
     {
         let path = Path::new(file_name);
 
@@ -84,14 +146,131 @@ impl ChunkyFile
         }
 
         // Open a file with reading and writing enabled, also create it since it shouldn't exist
-        let file = File::options().read(true).write(true).create(true).open(&path)?;
+        let file = File::options().read(true).write(true).create_new(true).open(path)?;
+
+        let mut chunky = ChunkyFile
+        {
+            file: file,
+            size: 0, // Set the size to zero since we haven't written anything yet
+        };
+
+        chunky.add_chunk(&ChunkTypes::DbHead(head))?;
+
+        return Ok(chunky);
+    }
+
+    // dbchunk::ChunkyFile::open() - Open an existing chunky file, throw an error if it doesn't exist or isn't a valid database
+    //
+    // ARGUMENTS:
+    //  file_name: &str - The path of the file to open
+    pub fn open(file_name: &str) -> Result<ChunkyFile, Box<dyn Error>>
+    {
+        let mut chunky = ChunkyFile::open_unchecked(file_name)?;
+
+        // Every chunk is the same size, so anything else means the file is torn or isn't a database
+        if chunky.size == 0 || !chunky.size.is_multiple_of(CHUNKSZ)
+        {
+            bail!("File size is not a multiple of the chunk size!");
+        }
+
+        // The first chunk must always be the database header, and it has to be a version we understand
+        let head = chunky.read_head()?;
+
+        if head.ver != DB_FORMAT_VERSION
+        {
+            bail!("Unsupported database version {}!", head.ver);
+        }
+
+        // Finish any tree changes that were cut short, before anything reads the trees
+        chunky.replay_journals()?;
+
+        return Ok(chunky);
+    }
+
+    // dbchunk::ChunkyFile::open_unchecked() - Open an existing chunky file without validating it, for inspection and recovery tools
+    //
+    // ARGUMENTS:
+    //  file_name: &str - The path of the file to open
+    pub fn open_unchecked(file_name: &str) -> Result<ChunkyFile, Box<dyn Error>>
+    {
+        let path = Path::new(file_name);
+
+        if !path.exists() // If the file doesn't exist...
+        {
+            // There is nothing to open, tell the user and return!
+            bail!("File Does Not Exist!");
+        }
+
+        // Open the file with reading and writing enabled, but don't create it
+        let file = File::options().read(true).write(true).open(path)?;
+        let size = file.metadata()?.len() as usize;
 
         return Ok
         (
             ChunkyFile
             {
                 file: file,
-                size: 0, // Set the size to zero since we haven't written anything yet
+                size: size,
+            }
+        );
+    }
+
+    // dbchunk::ChunkyFile::chunks() - Iterate over every chunk in the file in order, starting from the header
+    //
+    pub fn chunks(&mut self) -> ChunkIter<'_>
+    {
+        return ChunkIter
+        {
+            chunky: self,
+            pos: 0,
+        };
+    }
+
+    // dbchunk::ChunkyFile::read_chunk() - Read a whole chunk from the file and verify its CRC
+    //
+    // ARGUMENTS:
+    //  chunk_pos: u64 - The position of the chunk in the file, must be a multiple of the chunk size
+    pub fn read_chunk(&mut self, chunk_pos: u64) -> Result<[u8; CHUNKSZ], Box<dyn Error>>
+    {
+        if !chunk_pos.is_multiple_of(CHUNKSZ as u64)
+        {
+            bail!("Chunk position {} is not aligned to the chunk size!", chunk_pos);
+        }
+
+        // Another handle to the same file may have appended to it, so refresh the size before giving up
+        if (chunk_pos + (CHUNKSZ as u64)) > (self.size as u64)
+        {
+            self.size = self.file.metadata()?.len() as usize;
+        }
+
+        if (chunk_pos + (CHUNKSZ as u64)) > (self.size as u64)
+        {
+            bail!("Chunk position {} is past the end of the file!", chunk_pos);
+        }
+
+        let mut chunk_data: [u8; CHUNKSZ] = [0; CHUNKSZ];
+
+        self.file.seek(SeekFrom::Start(chunk_pos))?;
+        self.file.read_exact(&mut chunk_data)?;
+
+        if !ApeCrc24::verify(&chunk_data)
+        {
+            bail!("Chunk at {} failed its CRC check!", chunk_pos);
+        }
+
+        return Ok(chunk_data);
+    }
+
+    // dbchunk::ChunkyFile::try_clone() - Get a second handle to the same chunky file
+    //
+    pub fn try_clone(&self) -> Result<ChunkyFile, Box<dyn Error>>
+    {
+        return Ok
+        (
+            ChunkyFile
+            {
+                file: self.file.try_clone()?,
+                size: self.size,
             }
         );
     }
@@ -102,181 +281,2278 @@ impl ChunkyFile
     //  chunk: ChunkTypes - The chunk to add wrapped in a ChunkTypes enum
     pub fn add_chunk(&mut self, chunk: &ChunkTypes) -> Result<Option<Vec<u64>>, Box<dyn Error>>
     {
-        return Ok(None); // To be removed...   
+        match chunk
+        {
+            ChunkTypes::DbHead(head) =>
+            {
+                // Everything else expects the header to sit at the very start of the file
+                if self.size != 0
+                {
+                    bail!("The database header must be the first chunk in the file!");
+                }
+
+                let (_, insertion_points) = self.add_field_chunks(CHUNK_TYPE::DBHEAD, &[], &head.fields)?;
+
+                return Ok(Some(insertion_points));
+            }
+            ChunkTypes::Entry(entry) =>
+            {
+                let (_, insertion_points) = self.add_field_chunks(CHUNK_TYPE::ENTRY, &entry.preamble_to_bytes()?, &entry.fields)?;
+
+                return Ok(Some(insertion_points));
+            }
+        }
     }
 
+    // dbchunk::ChunkyFile::add_entry_chunk() - Add an entry chunk to the file, returning the insertion points of the entry fields
+    //
+    // ARGUMENTS:
+    //  chunk: EntryChunk - The entry chunk to add
     pub fn add_entry_chunk(&mut self, chunk: EntryChunk) -> Result<Vec<u64>, Box<dyn Error>>
     {
-        let mut i = 0;
-        let g = chunk.fields.len(); // Allocate enough memory for the insertion points equal to the number of fields +1 for the insertion point of the chunk itself
-        let mut insertion_points = Vec::<u64>::with_capacity(g);
+        let (_, insertion_points) = self.add_entry_chunk_points(chunk)?;
+
+        return Ok(insertion_points);
+    }
+
+    // dbchunk::ChunkyFile::add_entry_chunk_points() - Add an entry chunk to the file, returning the insertion point of its UUID and of its fields
+    //
+    // ARGUMENTS:
+    //  chunk: EntryChunk - The entry chunk to add
+    pub fn add_entry_chunk_points(&mut self, chunk: EntryChunk) -> Result<(u64, Vec<u64>), Box<dyn Error>>
+    {
+        let preamble = chunk.preamble_to_bytes()?;
+        let (_, preamble_start) = length_from_bytes(&preamble)?;
+
+        let (chain, insertion_points) = self.add_field_chunks(CHUNK_TYPE::ENTRY, &preamble, &chunk.fields)?;
+
+        // The UUID is always the first field of the preamble
+        let uuid_point = chain.file_position(preamble_start).expect("Entry preamble outside of its own chain! You shouldn't see this!");
+
+        return Ok((uuid_point, insertion_points));
+    }
+
+    // dbchunk::ChunkyFile::add_field_chunks() - Write fields back to back as a chain of chunks, returning the chain and the insertion point of every field
+    //
+    // ARGUMENTS:
+    //  chunk_type: u8 - The CHUNK_TYPE of the chain
+    //  preamble: &[u8] - Data to write before the fields, such as an entry preamble
+    //  fields: &[Field] - The fields to write
+    fn add_field_chunks(&mut self, chunk_type: u8, preamble: &[u8], fields: &[Field]) -> Result<(ChunkChain, Vec<u64>), Box<dyn Error>>
+    {
+        let mut data = preamble.to_vec();
+        let mut field_offsets = Vec::<usize>::with_capacity(fields.len());
+
+        // Get the total data of all of the fields, remembering where each one starts
+        for field in fields
+        {
+            field_offsets.push(data.len());
+            data.extend_from_slice(&field.to_bytes()?);
+        }
+
+        let chain = self.add_chunk_chain(chunk_type, data)?;
 
-        // Get the total data of all of the fields + the total length
-        let mut fields_total_data = Vec::<Vec<u8>>::with_capacity(g);
-        let mut fields_total_length: usize = 0;
-        for field in chunk.fields
+        let mut insertion_points = Vec::<u64>::with_capacity(fields.len());
+
+        for offset in field_offsets
         {
-            let field_data = field.to_bytes()?;
-            fields_total_length += field_data.len();
-            fields_total_data.push(field_data);
+            insertion_points.push(chain.file_position(offset).expect("Field offset outside of its own chain! You shouldn't see this!"));
         }
 
-        let mut carry_data = Vec::<u8>::new();
-        let mut written_len: usize = 0;
+        return Ok((chain, insertion_points));
+    }
+
+    // dbchunk::ChunkyFile::add_chunk_chain() - Write a stream of data to the end of the file as a chain of chunks
+    //
+    // ARGUMENTS:
+    //  chunk_type: u8 - The CHUNK_TYPE of the chain
+    //  data: Vec<u8> - The data to write
+    pub fn add_chunk_chain(&mut self, chunk_type: u8, data: Vec<u8>) -> Result<ChunkChain, Box<dyn Error>>
+    {
+        let mut chunks = Vec::<u64>::new();
+        let mut remaining = &data[..];
 
-        while (i < g) || (carry_data.len() > 0)
+        // There are two types of chunks in a chain...
+        while remaining.len() > CHUNK_STUB_DATASZ // continued chunks...
         {
-            // There are two types of entry chunks...
-            if (fields_total_length - written_len) > CHUNK_ENTRY_STUB_DATASZ // continued entry chunks...
+            let (chunk_part, rest) = remaining.split_at(CHUNK_CONT_DATASZ);
+
+            chunks.push(self.append_continued_chunk(chunk_type, chunk_part)?);
+            remaining = rest;
+        }
+
+        // and stub chunks, which always end the chain
+        chunks.push(self.append_stub_chunk(chunk_type, remaining)?);
+
+        return Ok
+        (
+            ChunkChain
             {
-                let mut file_position = self.file.seek(SeekFrom::End(0))?;
+                chunk_type: chunk_type,
+                chunks: chunks,
+                data: data,
+                fields_start: 0,
+            }
+        );
+    }
+
+    // dbchunk::ChunkyFile::append_continued_chunk() - Append a continued chunk that points to the chunk right after it, returning its position
+    //
+    // ARGUMENTS:
+    //  chunk_type: u8 - The CHUNK_TYPE of the chain
+    //  chunk_part: &[u8] - The data of the chunk, exactly CHUNK_CONT_DATASZ bytes
+    fn append_continued_chunk(&mut self, chunk_type: u8, chunk_part: &[u8]) -> Result<u64, Box<dyn Error>>
+    {
+        let chunk_pos = self.file.seek(SeekFrom::End(0))?;
 
-                let header = CHUNK_FLAG::CONTINUED | CHUNK_TYPE::ENTRY;
-                let next_chunk: u64 = file_position + (CHUNKSZ as u64);
-                let mut data = Vec::<u8>::with_capacity(CHUNK_ENTRY_CONT_DATASZ);
+        let header = CHUNK_FLAG::CONTINUED | chunk_type;
+        let next_chunk: u64 = chunk_pos + (CHUNKSZ as u64);
 
-                if carry_data.len() > 0
-                {
-                    data.extend_from_slice(&carry_data);
-                    carry_data.clear();
-                }
-                
-                file_position += (CHUNK_ENTRY_CONT_HEADSZ as u64) + (data.len() as u64);
-                while (i < g) && (data.len() < CHUNK_ENTRY_CONT_DATASZ)
-                {
-                    insertion_points.push(file_position);
-                    data.extend_from_slice(&fields_total_data[i]);
-                    file_position += fields_total_data[i].len() as u64;
-                    i += 1;
-                }
+        // Layout of the continued chunk!
+        //
+        let chunk_data = binary_data!
+        (
+            byte!(header), // Chunk header
+            u64_be!(next_chunk), // Next chunk position in file
+            bytes_from_vec!(chunk_part) // Chunk data
+            // CRC to be appended...
+        );
+
+        return self.append_chunk(chunk_data);
+    }
+
+    // dbchunk::ChunkyFile::append_stub_chunk() - Append a stub chunk, which ends a chain, returning its position
+    //
+    // ARGUMENTS:
+    //  chunk_type: u8 - The CHUNK_TYPE of the chain
+    //  chunk_part: &[u8] - The data of the chunk, no more than CHUNK_STUB_DATASZ bytes
+    fn append_stub_chunk(&mut self, chunk_type: u8, chunk_part: &[u8]) -> Result<u64, Box<dyn Error>>
+    {
+        let header = chunk_type;
+        let data_length: u8 = chunk_part.len().try_into().expect("Stub chunk data length over 255! You shouldn't see this!");
+        let padding = vec![0; CHUNK_STUB_DATASZ - (data_length as usize)];
+
+        // Layout of the stub chunk!
+        //
+        let chunk_data = binary_data!
+        (
+            byte!(header), // Header
+            byte!(data_length), // length of the following data...
+            bytes_from_vec!(chunk_part), // Data...
+            bytes_from_vec!(padding) // Padding...
+            // CRC to be appended later...
+        );
+
+        return self.append_chunk(chunk_data);
+    }
+
+    // dbchunk::ChunkyFile::append_chunk() - Append the CRC to a chunk and write it to the end of the file, returning its position
+    //
+    // ARGUMENTS:
+    //  chunk_data: Vec<u8> - The chunk without its CRC
+    fn append_chunk(&mut self, mut chunk_data: Vec<u8>) -> Result<u64, Box<dyn Error>>
+    {
+        let crc = ApeCrc24::new(&chunk_data);
+
+        chunk_data.extend_from_slice(&crc.to_be_bytes());
+
+        let chunk_pos = self.file.seek(SeekFrom::End(0))?;
+        self.file.write_all(&chunk_data)?;
+        self.size = (chunk_pos as usize) + CHUNKSZ;
+
+        return Ok(chunk_pos);
+    }
+
+    // dbchunk::ChunkyFile::add_bin() - Store binary data, moving it to overflow chunks if it is too big to keep inline
+    //
+    // ARGUMENTS:
+    //  data: &[u8] - The data to store
+    pub fn add_bin(&mut self, data: &[u8]) -> Result<Bin, Box<dyn Error>>
+    {
+        let mut writer = self.bin_writer();
 
-                carry_data = data.split_off(CHUNK_ENTRY_CONT_DATASZ);
-                written_len += data.len();
+        writer.write_all(data)?;
 
-                // Layout of the continued entry chunk!
-                //
-                let mut chunk_data = binary_data!
-                (
-                    byte!(header), // Chunk header
-                    u64_be!(next_chunk), // Next chunk position in file
-                    bytes_from_vec!(data) // Chunk field data
-                    // CRC to be appended...
-                );
+        return writer.finish();
+    }
+
+    // dbchunk::ChunkyFile::bin_writer() - Start streaming binary data to the end of the file, see BinWriter
+    //
+    pub fn bin_writer(&mut self) -> BinWriter<'_>
+    {
+        return BinWriter
+        {
+            chunky: self,
+            buffer: Vec::<u8>::new(),
+            first_chunk: None,
+            next_chunk: None,
+            length: 0,
+        };
+    }
+
+    // dbchunk::ChunkyFile::bin_reader() - Stream binary data back out, whether it is inline or in overflow chunks
+    //
+    // ARGUMENTS:
+    //  bin: &Bin - The binary value to read
+    pub fn bin_reader(&mut self, bin: &Bin) -> BinReader<'_>
+    {
+        let (buffer, next_chunk) = match bin
+        {
+            Bin::Inline(data) => (data.clone(), None),
+            Bin::Overflow { chunk, .. } => (Vec::<u8>::new(), Some(*chunk)),
+        };
+
+        return BinReader
+        {
+            chunky: self,
+            buffer: buffer,
+            offset: 0,
+            next_chunk: next_chunk,
+            remaining: bin.len(),
+        };
+    }
+
+    // dbchunk::ChunkyFile::read_chunk_chain() - Read a chain of chunks, verifying every CRC and stripping the headers and padding
+    //
+    // ARGUMENTS:
+    //  chunk_pos: u64 - The position of the first chunk in the chain
+    pub fn read_chunk_chain(&mut self, chunk_pos: u64) -> Result<ChunkChain, Box<dyn Error>>
+    {
+        let mut chunks = Vec::<u64>::new();
+        let mut data = Vec::<u8>::new();
+        let mut current_pos = chunk_pos;
+        let mut chunk_type: Option<u8> = None;
 
-                let crc = ApeCrc24::new(&chunk_data);
+        loop
+        {
+            let chunk_data = self.read_chunk(current_pos)?;
+            chunks.push(current_pos);
 
-                chunk_data.extend_from_slice(&crc.to_be_bytes());
+            // The first chunk decides the type of the whole chain
+            let expected_type = *chunk_type.get_or_insert(chunk_data[0] & CHUNK_FLAG::TYPE_MASK);
 
-                self.file.write_all(&chunk_data)?;
+            if (chunk_data[0] & CHUNK_FLAG::TYPE_MASK) != expected_type
+            {
+                bail!("Chunk at {} does not match the type of its chain!", current_pos);
             }
-            else // and stub entry chunks
+
+            if (chunk_data[0] & CHUNK_FLAG::CONTINUED) != 0 // Continued chunks point to the next chunk...
             {
-                let mut file_position = self.file.seek(SeekFrom::End(0))?; // Seek to the end of the file and get the position
+                let next_chunk = u64::from_be_bytes(chunk_data[1..CHUNK_CONT_HEADSZ].try_into().expect("Slice of incorrect size when reading the next chunk, you shouldn't see this!"));
 
-                let header = CHUNK_TYPE::ENTRY; // Set the header to represent a bare entry chunk
-                let mut data = Vec::<u8>::with_capacity(CHUNKSZ);
-                
-                if carry_data.len() > 0
-                {
-                    data.extend_from_slice(&carry_data);
-                    carry_data.clear();
-                }
+                data.extend_from_slice(&chunk_data[CHUNK_CONT_HEADSZ..CHUNK_CONT_HEADSZ + CHUNK_CONT_DATASZ]);
 
-                file_position += CHUNK_ENTRY_STUB_HEADSZ as u64;
-                while i < g
+                // Chains are always written front to back, anything else would loop forever
+                if next_chunk <= current_pos
                 {
-                    insertion_points.push(file_position);
-                    data.extend_from_slice(&fields_total_data[i]);
-                    file_position += fields_total_data[i].len() as u64;
-                    i += 1;
+                    bail!("Chunk at {} points backwards to {}!", current_pos, next_chunk);
                 }
 
-                let data_length: u8 = data.len().try_into().expect("Stub chunk data length over 255! You shouldn't see this!");
-                let padding = vec![0; CHUNK_ENTRY_STUB_DATASZ - (data_length as usize)];
+                current_pos = next_chunk;
+            }
+            else // and stub chunks end the chain
+            {
+                let data_length = chunk_data[1] as usize;
 
-                // Layout of the stub entry chunk!
-                //
-                let mut chunk_data = binary_data!
-                (
-                    byte!(header), // Header
-                    byte!(data_length), // length of the following data...
-                    bytes_from_vec!(data), // Padding...
-                    bytes_from_vec!(padding) // Data...
-                    // CRC to be appended later...
-                );
+                if data_length > CHUNK_STUB_DATASZ
+                {
+                    bail!("Chunk at {} claims more data than it can hold!", current_pos);
+                }
 
-                let crc = ApeCrc24::new(&chunk_data);
+                data.extend_from_slice(&chunk_data[CHUNK_STUB_HEADSZ..CHUNK_STUB_HEADSZ + data_length]);
 
-                chunk_data.extend_from_slice(&crc.to_be_bytes());
+                break;
+            }
+        }
 
-                self.file.write_all(&chunk_data)?;
+        return Ok
+        (
+            ChunkChain
+            {
+                chunk_type: chunk_type.expect("Chain without a first chunk! You shouldn't see this!"),
+                chunks: chunks,
+                data: data,
+                fields_start: 0,
             }
+        );
+    }
+
+    // dbchunk::ChunkyFile::read_entry_chunk() - Read an entry chunk and every chunk it continues into, decoding its preamble and fields
+    //
+    // ARGUMENTS:
+    //  chunk_pos: u64 - The position of the first chunk of the entry
+    pub fn read_entry_chunk(&mut self, chunk_pos: u64) -> Result<(ChunkChain, EntryChunk), Box<dyn Error>>
+    {
+        let mut chain = self.read_chunk_chain(chunk_pos)?;
 
-            
+        if chain.chunk_type != CHUNK_TYPE::ENTRY
+        {
+            bail!("Chunk at {} is not an entry chunk!", chunk_pos);
         }
 
-        return Ok(insertion_points);
+        let (entry_chunk, fields_start) = EntryChunk::from_bytes(&chain.data)?;
+        chain.fields_start = fields_start;
+
+        return Ok((chain, entry_chunk));
     }
-}
 
-// dbchunk::DbHeadChunk - Struct for creating and modifying the DB header chunk
-pub struct DbHeadChunk
-{
-    //pub chunk_numbers: Vec<u64>,
-    pub fields: Vec<Field>,
-}
+    // dbchunk::ChunkyFile::add_schema_chunk() - Store the structure of a list, returning the position of the schema chunk
+    //
+    // ARGUMENTS:
+    //  structure: &Structure - The structure to store
+    pub fn add_schema_chunk(&mut self, structure: &Structure) -> Result<u64, Box<dyn Error>>
+    {
+        let (chain, _) = self.add_field_chunks(CHUNK_TYPE::SCHEMA, &[], &structure.to_fields())?;
 
-impl DbHeadChunk
-{
-    // dbchunk::DbHeadChunk::new() - Create a new DB header chunk
+        return Ok(chain.chunks[0]);
+    }
+
+    // dbchunk::ChunkyFile::read_schema_chunk() - Read a schema chunk and every chunk it continues into, rebuilding the structure
     //
     // ARGUMENTS:
-    //  name: &str - The name of the database
-    //  owner: &str - The owner of the database
-    pub fn new(name: &str, owner: &str) -> DbHeadChunk
+    //  chunk_pos: u64 - The position of the first chunk of the schema
+    pub fn read_schema_chunk(&mut self, chunk_pos: u64) -> Result<Structure, Box<dyn Error>>
     {
-        let mut dbfields = Vec::<Field>::new();
+        let chain = self.read_chunk_chain(chunk_pos)?;
+
+        if chain.chunk_type != CHUNK_TYPE::SCHEMA
+        {
+            bail!("Chunk at {} is not a schema chunk!", chunk_pos);
+        }
 
-        let mut requirements = Vec::<Requirement>::new();
+        return Structure::from_fields(&Field::from_stream(&chain.data)?);
+    }
 
-        //requirements.push(Requirement::new("name", std::mem::discriminant(&Type::S(S::new("")))));
+    // dbchunk::ChunkyFile::schemas() - Read every structure stored in the file, in the order they were added
+    //
+    pub fn schemas(&mut self) -> Result<Vec<Structure>, Box<dyn Error>>
+    {
+        let mut schema_chunks = Vec::<u64>::new();
+        let mut continuations = Vec::<u64>::new();
 
-        let dbstructure = Structure::new("db", requirements);
+        for chunk in self.chunks()
+        {
+            let chunk = chunk?;
 
-        dbfields.push(Field::new("name", Type::S(Some(S::new(name))))); // Name field, database name
-        dbfields.push(Field::new("ver", Type::I(Some(I::new(0))))); // Version field, database file version
-        dbfields.push(Field::new("uuid_cache_size", Type::I(Some(I::new(DB_DEFAULT_UUID_CACHE_SIZE))))); // Uuid cache size field
-        dbfields.push(Field::new("perm", Type::I(Some(I::new(DB_DEFAULT_UNIX_PERMISSIONS))))); // Unix permissions field
-        dbfields.push(Field::new("owner", Type::S(Some(S::new(owner))))); // Owner field
-        dbfields.push(Field::new("sane", Type::B(Some(B::new(true))))); // Sane field
-        dbfields.push(Field::new("insane", Type::B(Some(B::new(false))))); // Insane field
+            match chunk.kind
+            {
+                ChunkKind::SchemaStub { .. } => schema_chunks.push(chunk.pos),
+                ChunkKind::SchemaContinued { next_chunk } =>
+                {
+                    schema_chunks.push(chunk.pos);
+                    continuations.push(next_chunk);
+                }
+                _ => {}
+            }
+        }
 
-        println!("{}", dbstructure.meets(&dbfields));
+        let mut structures = Vec::<Structure>::new();
 
-        return DbHeadChunk
+        // Only the first chunk of each chain starts a schema, the rest are pointed to by the chunk before them
+        for chunk_pos in schema_chunks
         {
-            //chunk_numbers: Vec::<u64>::new(),
-            fields: dbfields,
-        };
+            if !continuations.contains(&chunk_pos)
+            {
+                structures.push(self.read_schema_chunk(chunk_pos)?);
+            }
+        }
+
+        return Ok(structures);
     }
-}
 
+    // dbchunk::ChunkyFile::add_journal() - Add an empty journal with room for at least the given amount of data, returning its position
+    //
+    // A journal holds writes that have to happen all together, such as a tree rotation. It can be reused for as long as
+    // the writes fit, see write_journal().
+    //
+    // ARGUMENTS:
+    //  capacity: usize - How many bytes of encoded writes the journal has to hold, see journal_size()
+    pub fn add_journal(&mut self, capacity: usize) -> Result<u64, Box<dyn Error>>
+    {
+        // Round up to whole chunks, the room would be wasted otherwise
+        let chunk_count = std::cmp::max(1, capacity.saturating_sub(CHUNK_STUB_DATASZ).div_ceil(CHUNK_CONT_DATASZ) + 1);
+        let capacity = (chunk_count - 1) * CHUNK_CONT_DATASZ + CHUNK_STUB_DATASZ;
 
+        let chain = self.add_chunk_chain(CHUNK_TYPE::JOURNAL, vec![0; capacity])?;
 
-pub struct EntryChunk
-{
+        return Ok(chain.chunks[0]);
+    }
+
+    // dbchunk::ChunkyFile::journal_capacity() - Get how many bytes of encoded writes a journal can hold
+    //
+    // ARGUMENTS:
+    //  journal_pos: u64 - The position of the first chunk of the journal
+    pub fn journal_capacity(&mut self, journal_pos: u64) -> Result<usize, Box<dyn Error>>
+    {
+        return Ok(self.read_journal_chain(journal_pos)?.data.len());
+    }
+
+    // dbchunk::ChunkyFile::journal_size() - Get how many bytes a set of writes takes up in a journal
+    //
+    // ARGUMENTS:
+    //  patches: &[JournalPatch] - The writes
+    pub fn journal_size(patches: &[JournalPatch]) -> usize
+    {
+        return JOURNAL_COUNTSZ + patches.iter().map(|(_, data)| JOURNAL_PATCH_HEADSZ + data.len()).sum::<usize>();
+    }
+
+    // dbchunk::ChunkyFile::write_journal() - Record a set of writes in a journal and arm it, without making the writes yet
+    //
+    // The writes are recorded before the journal is armed, so a journal cut short is never replayed. Once armed the writes
+    // will be made by replay_journal(), now or when the file is next opened.
+    //
+    // ARGUMENTS:
+    //  journal_pos: u64 - The position of the first chunk of the journal
+    //  patches: &[JournalPatch] - The writes, each inside the data of a chain
+    pub fn write_journal(&mut self, journal_pos: u64, patches: &[JournalPatch]) -> Result<(), Box<dyn Error>>
+    {
+        let chain = self.read_journal_chain(journal_pos)?;
+
+        if ChunkyFile::journal_size(patches) > chain.data.len()
+        {
+            bail!("Journal at {} is too small for {} writes!", journal_pos, patches.len());
+        }
+
+        let patch_count: u32 = patches.len().try_into()?;
+        let mut data = patch_count.to_be_bytes().to_vec();
+
+        for (pos, patch_data) in patches
+        {
+            let patch_length: u8 = patch_data.len().try_into()?;
+
+            data.extend_from_slice(&pos.to_be_bytes());
+            data.push(patch_length);
+            data.extend_from_slice(patch_data);
+        }
+
+        // The patches have to be on disk before the journal is armed, and armed before anything they cover is touched
+        self.write_at(chain.file_position(0).expect("Journal without any data! You shouldn't see this!"), &data)?;
+        self.file.sync_data()?;
+        self.change_chunk_flag(journal_pos, CHUNK_FLAG::UNDER_CONSTRUCTION, true)?;
+        self.file.sync_data()?;
+
+        return Ok(());
+    }
+
+    // dbchunk::ChunkyFile::replay_journal() - Make every write recorded in an armed journal, then disarm it
+    //
+    // Writes are recorded as the bytes to end up with, so replaying a journal that was partly made already is harmless.
+    //
+    // ARGUMENTS:
+    //  journal_pos: u64 - The position of the first chunk of the journal
+    pub fn replay_journal(&mut self, journal_pos: u64) -> Result<(), Box<dyn Error>>
+    {
+        if (self.read_chunk(journal_pos)?[0] & CHUNK_FLAG::UNDER_CONSTRUCTION) == 0
+        {
+            return Ok(());
+        }
+
+        let chain = self.read_journal_chain(journal_pos)?;
+        let data = &chain.data;
+
+        let patch_count = u32::from_be_bytes(data[..JOURNAL_COUNTSZ].try_into().expect("Slice of incorrect size when reading a journal, you shouldn't see this!"));
+        let mut i: usize = JOURNAL_COUNTSZ;
+
+        for _ in 0..patch_count
+        {
+            if i + JOURNAL_PATCH_HEADSZ > data.len()
+            {
+                bail!("Journal at {} is truncated!", journal_pos);
+            }
+
+            let pos = u64::from_be_bytes(data[i..i + 8].try_into().expect("Slice of incorrect size when reading a journal, you shouldn't see this!"));
+            let patch_length = data[i + 8] as usize;
+            i += JOURNAL_PATCH_HEADSZ;
+
+            if i + patch_length > data.len()
+            {
+                bail!("Journal at {} is truncated!", journal_pos);
+            }
+
+            self.write_at(pos, &data[i..i + patch_length])?;
+            i += patch_length;
+        }
+
+        // Every write has to be on disk before the journal stops covering it
+        self.file.sync_data()?;
+        self.change_chunk_flag(journal_pos, CHUNK_FLAG::UNDER_CONSTRUCTION, false)?;
+
+        return Ok(());
+    }
+
+    // dbchunk::ChunkyFile::replay_journals() - Replay every journal left armed by an interrupted write, returning how many there were
+    //
+    pub fn replay_journals(&mut self) -> Result<u64, Box<dyn Error>>
+    {
+        let mut armed = Vec::<u64>::new();
+
+        // Only the first chunk of a journal is ever armed, and one that failed its CRC never got that far
+        for chunk in self.chunks()
+        {
+            let chunk = chunk?;
+
+            if matches!(chunk.kind, ChunkKind::JournalStub { .. } | ChunkKind::JournalContinued { .. }) && chunk.crc_ok && (chunk.flags & CHUNK_FLAG::UNDER_CONSTRUCTION) != 0
+            {
+                armed.push(chunk.pos);
+            }
+        }
+
+        for journal_pos in &armed
+        {
+            self.replay_journal(*journal_pos)?;
+        }
+
+        return Ok(armed.len() as u64);
+    }
+
+    // dbchunk::ChunkyFile::read_journal_chain() - Read the chain of a journal, making sure it is one
+    //
+    // ARGUMENTS:
+    //  journal_pos: u64 - The position of the first chunk of the journal
+    fn read_journal_chain(&mut self, journal_pos: u64) -> Result<ChunkChain, Box<dyn Error>>
+    {
+        let chain = self.read_chunk_chain(journal_pos)?;
+
+        if chain.chunk_type != CHUNK_TYPE::JOURNAL || chain.data.len() < JOURNAL_COUNTSZ
+        {
+            bail!("Chunk at {} is not a journal chunk!", journal_pos);
+        }
+
+        return Ok(chain);
+    }
+
+    // dbchunk::ChunkyFile::change_chunk_flag() - Set or clear a flag in the header of a chunk
+    //
+    // ARGUMENTS:
+    //  chunk_pos: u64 - The position of the chunk
+    //  flag: u8 - The CHUNK_FLAG to change
+    //  set: bool - Whether to set the flag, otherwise it is cleared
+    fn change_chunk_flag(&mut self, chunk_pos: u64, flag: u8, set: bool) -> Result<(), Box<dyn Error>>
+    {
+        let mut chunk_data = self.read_chunk(chunk_pos)?;
+
+        if set
+        {
+            chunk_data[0] |= flag;
+        }
+        else
+        {
+            chunk_data[0] &= !flag;
+        }
+
+        let crc = ApeCrc24::new(&chunk_data[..CHUNKSZ - CHUNKCRCSZ]);
+        chunk_data[CHUNKSZ - CHUNKCRCSZ..].copy_from_slice(&crc.to_be_bytes());
+
+        self.file.seek(SeekFrom::Start(chunk_pos))?;
+        self.file.write_all(&chunk_data)?;
+
+        return Ok(());
+    }
+
+    // dbchunk::ChunkyFile::within_chunk() - Check if a write lands inside a single chunk, so it is made in one go
+    //
+    // ARGUMENTS:
+    //  pos: u64 - The position of the write
+    //  length: usize - The length of the write
+    pub fn within_chunk(pos: u64, length: usize) -> bool
+    {
+        return (pos % CHUNKSZ as u64) as usize + length <= CHUNKSZ - CHUNKCRCSZ;
+    }
+
+    // dbchunk::ChunkyFile::add_list_chunk() - Store the UUID tree head and entry count of a list, returning the position of the list chunk
+    //
+    // ARGUMENTS:
+    //  list_chunk: &ListChunk - The list chunk to store
+    pub fn add_list_chunk(&mut self, list_chunk: &ListChunk) -> Result<u64, Box<dyn Error>>
+    {
+        return self.append_stub_chunk(CHUNK_TYPE::LIST, &list_chunk.to_bytes()?);
+    }
+
+    // dbchunk::ChunkyFile::read_list_chunk() - Read a list chunk
+    //
+    // ARGUMENTS:
+    //  chunk_pos: u64 - The position of the list chunk
+    pub fn read_list_chunk(&mut self, chunk_pos: u64) -> Result<ListChunk, Box<dyn Error>>
+    {
+        let chain = self.read_chunk_chain(chunk_pos)?;
+
+        if chain.chunk_type != CHUNK_TYPE::LIST || chain.chunks.len() != 1
+        {
+            bail!("Chunk at {} is not a list chunk!", chunk_pos);
+        }
+
+        return ListChunk::from_bytes(&chain.data);
+    }
+
+    // dbchunk::ChunkyFile::list_chunks() - Read every list chunk in the file, along with their positions, in the order they were added
+    //
+    pub fn list_chunks(&mut self) -> Result<Vec<(u64, ListChunk)>, Box<dyn Error>>
+    {
+        let mut chunk_positions = Vec::<u64>::new();
+
+        for chunk in self.chunks()
+        {
+            let chunk = chunk?;
+
+            if chunk.kind == ChunkKind::List
+            {
+                chunk_positions.push(chunk.pos);
+            }
+        }
+
+        let mut list_chunks = Vec::<(u64, ListChunk)>::new();
+
+        for chunk_pos in chunk_positions
+        {
+            list_chunks.push((chunk_pos, self.read_list_chunk(chunk_pos)?));
+        }
+
+        return Ok(list_chunks);
+    }
+
+    // dbchunk::ChunkyFile::add_index_chunk() - Store the tree head and state of an index, returning the position of the index chunk
+    //
+    // ARGUMENTS:
+    //  index_chunk: &IndexChunk - The index chunk to store
+    pub fn add_index_chunk(&mut self, index_chunk: &IndexChunk) -> Result<u64, Box<dyn Error>>
+    {
+        return self.append_stub_chunk(CHUNK_TYPE::INDEX, &index_chunk.to_bytes()?);
+    }
+
+    // dbchunk::ChunkyFile::read_index_chunk() - Read an index chunk
+    //
+    // ARGUMENTS:
+    //  chunk_pos: u64 - The position of the index chunk
+    pub fn read_index_chunk(&mut self, chunk_pos: u64) -> Result<IndexChunk, Box<dyn Error>>
+    {
+        let chain = self.read_chunk_chain(chunk_pos)?;
+
+        if chain.chunk_type != CHUNK_TYPE::INDEX || chain.chunks.len() != 1
+        {
+            bail!("Chunk at {} is not an index chunk!", chunk_pos);
+        }
+
+        return IndexChunk::from_bytes(&chain.data);
+    }
+
+    // dbchunk::ChunkyFile::index_chunks() - Read every index chunk in the file, along with their positions, in the order they were added
+    //
+    // Dropped indexes and indexes that were never finished are read too, see IndexChunk::state.
+    pub fn index_chunks(&mut self) -> Result<Vec<(u64, IndexChunk)>, Box<dyn Error>>
+    {
+        let mut chunk_positions = Vec::<u64>::new();
+
+        for chunk in self.chunks()
+        {
+            let chunk = chunk?;
+
+            if chunk.kind == ChunkKind::Index
+            {
+                chunk_positions.push(chunk.pos);
+            }
+        }
+
+        let mut index_chunks = Vec::<(u64, IndexChunk)>::new();
+
+        for chunk_pos in chunk_positions
+        {
+            index_chunks.push((chunk_pos, self.read_index_chunk(chunk_pos)?));
+        }
+
+        return Ok(index_chunks);
+    }
+
+    // dbchunk::ChunkyFile::chain_start() - Get the position of the first chunk of the chain a position in the file is part of
+    //
+    // Chains are written front to back with every chunk right after the one before it, so this walks back one chunk at a
+    // time for as long as the chunk before continues into the current one.
+    //
+    // ARGUMENTS:
+    //  pos: u64 - The position in the file, such as a field's insertion point
+    pub fn chain_start(&mut self, pos: u64) -> Result<u64, Box<dyn Error>>
+    {
+        let mut chunk_pos = ChunkyFile::chunk_pos(pos);
+        let chunk_type = self.read_chunk(chunk_pos)?[0] & CHUNK_FLAG::TYPE_MASK;
+
+        while chunk_pos > 0
+        {
+            let previous_pos = chunk_pos - CHUNKSZ as u64;
+            let previous = self.read_chunk(previous_pos)?;
+
+            let continues_here = (previous[0] & CHUNK_FLAG::CONTINUED) != 0 &&
+                (previous[0] & CHUNK_FLAG::TYPE_MASK) == chunk_type &&
+                u64::from_be_bytes(previous[1..CHUNK_CONT_HEADSZ].try_into().expect("Slice of incorrect size when reading the next chunk, you shouldn't see this!")) == chunk_pos;
+
+            if !continues_here
+            {
+                break;
+            }
+
+            chunk_pos = previous_pos;
+        }
+
+        return Ok(chunk_pos);
+    }
+
+    // dbchunk::ChunkyFile::chunk_pos() - Get the position of the chunk a position in the file is part of
+    //
+    // ARGUMENTS:
+    //  pos: u64 - The position in the file
+    pub fn chunk_pos(pos: u64) -> u64
+    {
+        return pos - (pos % CHUNKSZ as u64);
+    }
+
+    // dbchunk::ChunkyFile::chunk_data_bounds() - Get where the data of a chunk starts and ends in the file, and the chunk it continues into
+    //
+    // ARGUMENTS:
+    //  chunk_pos: u64 - The position of the chunk
+    //  chunk_data: &[u8; CHUNKSZ] - The contents of the chunk
+    fn chunk_data_bounds(chunk_pos: u64, chunk_data: &[u8; CHUNKSZ]) -> Result<(u64, u64, Option<u64>), Box<dyn Error>>
+    {
+        if (chunk_data[0] & CHUNK_FLAG::CONTINUED) != 0
+        {
+            let next_chunk = u64::from_be_bytes(chunk_data[1..CHUNK_CONT_HEADSZ].try_into().expect("Slice of incorrect size when reading the next chunk, you shouldn't see this!"));
+
+            if next_chunk <= chunk_pos
+            {
+                bail!("Chunk at {} points backwards to {}!", chunk_pos, next_chunk);
+            }
+
+            let data_start = chunk_pos + CHUNK_CONT_HEADSZ as u64;
+
+            return Ok((data_start, data_start + CHUNK_CONT_DATASZ as u64, Some(next_chunk)));
+        }
+
+        let data_length = chunk_data[1] as usize;
+
+        if data_length > CHUNK_STUB_DATASZ
+        {
+            bail!("Chunk at {} claims more data than it can hold!", chunk_pos);
+        }
+
+        let data_start = chunk_pos + CHUNK_STUB_HEADSZ as u64;
+
+        return Ok((data_start, data_start + data_length as u64, None));
+    }
+
+    // dbchunk::ChunkyFile::read_from() - Read the data of a chain from a position in the file to the end of the chain
+    //
+    // ARGUMENTS:
+    //  pos: u64 - The position to start reading from, such as a field insertion point
+    pub fn read_from(&mut self, pos: u64) -> Result<Vec<u8>, Box<dyn Error>>
+    {
+        return self.read_at(pos, usize::MAX);
+    }
+
+    // dbchunk::ChunkyFile::read_at() - Read up to length bytes of the data of a chain from a position in the file, stopping early if the chain ends
+    //
+    // ARGUMENTS:
+    //  pos: u64 - The position to start reading from, such as a field insertion point
+    //  length: usize - The most bytes to read
+    pub fn read_at(&mut self, pos: u64, length: usize) -> Result<Vec<u8>, Box<dyn Error>>
+    {
+        let mut data = Vec::<u8>::new();
+        let mut current_pos = pos;
+
+        while data.len() < length
+        {
+            let chunk_pos = current_pos - (current_pos % CHUNKSZ as u64);
+            let chunk_data = self.read_chunk(chunk_pos)?;
+            let (data_start, data_end, next_chunk) = ChunkyFile::chunk_data_bounds(chunk_pos, &chunk_data)?;
+
+            // Following a chain lands on the start of the next chunk, skip its header
+            if current_pos == chunk_pos
+            {
+                current_pos = data_start;
+            }
+
+            if current_pos < data_start || current_pos > data_end
+            {
+                bail!("Position {} is not inside the data of its chunk!", current_pos);
+            }
+
+            let read_len = std::cmp::min(length - data.len(), (data_end - current_pos) as usize);
+            let read_start = (current_pos - chunk_pos) as usize;
+
+            data.extend_from_slice(&chunk_data[read_start..read_start + read_len]);
+
+            match next_chunk
+            {
+                Some(next_chunk) => current_pos = next_chunk,
+                None => break,
+            }
+        }
+
+        return Ok(data);
+    }
+
+    // dbchunk::ChunkyFile::read_field_bytes() - Read the bytes of the field at an insertion point, reading no more than the field needs
+    //
+    // ARGUMENTS:
+    //  field_pos: u64 - The insertion point of the field
+    pub fn read_field_bytes(&mut self, field_pos: u64) -> Result<Vec<u8>, Box<dyn Error>>
+    {
+        // Most fields fit in a chunk's worth of data, only go back for more when the field says it needs it
+        let mut data = self.read_at(field_pos, CHUNKFIELDALLOC)?;
+
+        loop
+        {
+            match Field::from_bytes_with_len(&data)
+            {
+                Ok((_, field_len)) =>
+                {
+                    data.truncate(field_len);
+
+                    return Ok(data);
+                }
+                Err(DecodeError::Truncated { needed, have }) if needed > have =>
+                {
+                    let more = self.read_at(field_pos, needed)?;
+
+                    // The chain ended before the field did
+                    if more.len() <= data.len()
+                    {
+                        return Err(Box::new(DecodeError::Truncated { needed: needed, have: more.len() }));
+                    }
+
+                    data = more;
+                }
+                Err(e) =>
+                {
+                    return Err(Box::new(e));
+                }
+            }
+        }
+    }
+
+    // dbchunk::ChunkyFile::read_field() - Read the field at an insertion point, even if it is split across chunks
+    //
+    // ARGUMENTS:
+    //  field_pos: u64 - The insertion point of the field
+    pub fn read_field(&mut self, field_pos: u64) -> Result<Field, Box<dyn Error>>
+    {
+        return Ok(Field::from_bytes(&self.read_field_bytes(field_pos)?)?);
+    }
+
+    // dbchunk::ChunkyFile::write_at() - Overwrite data inside a chain, following it across chunks and updating every CRC touched
+    //
+    // ARGUMENTS:
+    //  pos: u64 - The position to start writing at, must be inside the data of a chunk
+    //  data: &[u8] - The data to write
+    pub fn write_at(&mut self, pos: u64, data: &[u8]) -> Result<(), Box<dyn Error>>
+    {
+        let mut remaining = data;
+        let mut current_pos = pos;
+
+        while remaining.len() > 0
+        {
+            let chunk_pos = current_pos - (current_pos % CHUNKSZ as u64);
+            let mut chunk_data = self.read_chunk(chunk_pos)?;
+            let (data_start, data_end, next_chunk) = ChunkyFile::chunk_data_bounds(chunk_pos, &chunk_data)?;
+
+            // Following a chain lands on the start of the next chunk, skip its header
+            if current_pos == chunk_pos
+            {
+                current_pos = data_start;
+            }
+
+            if current_pos < data_start || current_pos >= data_end
+            {
+                bail!("Position {} is not inside the data of its chunk!", current_pos);
+            }
+
+            // Patch as much as fits in this chunk, then recompute its CRC
+            let patch_len = std::cmp::min(remaining.len(), (data_end - current_pos) as usize);
+            let patch_start = (current_pos - chunk_pos) as usize;
+
+            chunk_data[patch_start..patch_start + patch_len].copy_from_slice(&remaining[..patch_len]);
+
+            let crc = ApeCrc24::new(&chunk_data[..CHUNKSZ - CHUNKCRCSZ]);
+            chunk_data[CHUNKSZ - CHUNKCRCSZ..].copy_from_slice(&crc.to_be_bytes());
+
+            self.file.seek(SeekFrom::Start(chunk_pos))?;
+            self.file.write_all(&chunk_data)?;
+
+            remaining = &remaining[patch_len..];
+
+            if remaining.len() > 0
+            {
+                match next_chunk
+                {
+                    Some(next_chunk) => current_pos = next_chunk,
+                    None => bail!("Write at {} runs past the end of its chain!", pos),
+                }
+            }
+        }
+
+        return Ok(());
+    }
+
+    // dbchunk::ChunkyFile::read_head() - Read the database header from the start of the file
+    //
+    pub fn read_head(&mut self) -> Result<DbHeader, Box<dyn Error>>
+    {
+        let chain = self.read_chunk_chain(0)?;
+
+        if chain.chunk_type != CHUNK_TYPE::DBHEAD
+        {
+            bail!("First chunk is not a database header!");
+        }
+
+        return DbHeader::from_fields(&Field::from_stream(&chain.data)?);
+    }
+}
+
+// dbchunk::ChunkKind - What a chunk found while iterating over a file turned out to be
+//
+#[derive(Debug, Clone, PartialEq)]
+pub enum ChunkKind
+{
+    DbHead { next_chunk: Option<u64> }, // A header chunk, pointing to the next chunk if it is continued
+    EntryStub { data_length: u8 }, // A bare entry chunk, or the last chunk of a continued entry
+    EntryContinued { next_chunk: u64 }, // An entry chunk that continues into another chunk
+    BinStub { data_length: u8 }, // The last chunk of some overflowed binary data
+    BinContinued { next_chunk: u64 }, // A chunk of overflowed binary data that continues into another chunk
+    SchemaStub { data_length: u8 }, // A bare schema chunk, or the last chunk of a continued schema
+    SchemaContinued { next_chunk: u64 }, // A schema chunk that continues into another chunk
+    JournalStub { data_length: u8 }, // A bare journal chunk, or the last chunk of a continued journal
+    JournalContinued { next_chunk: u64 }, // A journal chunk that continues into another chunk
+    List, // A list chunk, which never continues
+    Index, // An index chunk, which never continues
+    Unknown { chunk_type: u8 }, // A chunk with a type we don't know about
+    TornTail { length: usize }, // Trailing bytes too short to be a chunk, left by an interrupted write
+}
+
+// dbchunk::ChunkInfo - A single chunk found while iterating over a file
+//
+#[derive(Debug, Clone, PartialEq)]
+pub struct ChunkInfo
+{
+    pub pos: u64, // The position of the chunk in the file
+    pub kind: ChunkKind, // What the chunk is
+    pub flags: u8, // The flags from the chunk header
+    pub crc_ok: bool, // Whether the chunk passed its CRC check, always false for a torn tail
+}
+
+// dbchunk::ChunkIter - Iterator over every chunk in a chunky file, see ChunkyFile::chunks()
+//
+pub struct ChunkIter<'a>
+{
+    chunky: &'a mut ChunkyFile,
+    pos: u64, // The position of the next chunk to read
+}
+
+impl ChunkIter<'_>
+{
+    // dbchunk::ChunkIter::read_next() - Read and classify the chunk at the current position
+    //
+    // ARGUMENTS:
+    //  file_size: u64 - The current size of the file
+    fn read_next(&mut self, file_size: u64) -> Result<ChunkInfo, Box<dyn Error>>
+    {
+        let chunk_pos = self.pos;
+        let remaining = (file_size - chunk_pos) as usize;
+
+        // Anything shorter than a whole chunk can only be the remains of a write that never finished
+        if remaining < CHUNKSZ
+        {
+            self.pos = file_size;
+
+            return Ok
+            (
+                ChunkInfo
+                {
+                    pos: chunk_pos,
+                    kind: ChunkKind::TornTail { length: remaining },
+                    flags: 0,
+                    crc_ok: false,
+                }
+            );
+        }
+
+        let mut chunk_data: [u8; CHUNKSZ] = [0; CHUNKSZ];
+
+        self.chunky.file.seek(SeekFrom::Start(chunk_pos))?;
+        self.chunky.file.read_exact(&mut chunk_data)?;
+        self.pos += CHUNKSZ as u64;
+
+        let header = chunk_data[0];
+        let flags = header & !CHUNK_FLAG::TYPE_MASK;
+        let next_chunk = u64::from_be_bytes(chunk_data[1..CHUNK_CONT_HEADSZ].try_into().expect("Slice of incorrect size when reading the next chunk, you shouldn't see this!"));
+        let continued = (flags & CHUNK_FLAG::CONTINUED) != 0;
+
+        let kind = match header & CHUNK_FLAG::TYPE_MASK
+        {
+            CHUNK_TYPE::DBHEAD if continued => ChunkKind::DbHead { next_chunk: Some(next_chunk) },
+            CHUNK_TYPE::DBHEAD => ChunkKind::DbHead { next_chunk: None },
+            CHUNK_TYPE::ENTRY if continued => ChunkKind::EntryContinued { next_chunk: next_chunk },
+            CHUNK_TYPE::ENTRY => ChunkKind::EntryStub { data_length: chunk_data[1] },
+            CHUNK_TYPE::BIN if continued => ChunkKind::BinContinued { next_chunk: next_chunk },
+            CHUNK_TYPE::BIN => ChunkKind::BinStub { data_length: chunk_data[1] },
+            CHUNK_TYPE::SCHEMA if continued => ChunkKind::SchemaContinued { next_chunk: next_chunk },
+            CHUNK_TYPE::SCHEMA => ChunkKind::SchemaStub { data_length: chunk_data[1] },
+            CHUNK_TYPE::JOURNAL if continued => ChunkKind::JournalContinued { next_chunk: next_chunk },
+            CHUNK_TYPE::JOURNAL => ChunkKind::JournalStub { data_length: chunk_data[1] },
+            CHUNK_TYPE::LIST if !continued => ChunkKind::List,
+            CHUNK_TYPE::INDEX if !continued => ChunkKind::Index,
+            chunk_type => ChunkKind::Unknown { chunk_type: chunk_type },
+        };
+
+        return Ok
+        (
+            ChunkInfo
+            {
+                pos: chunk_pos,
+                kind: kind,
+                flags: flags,
+                crc_ok: ApeCrc24::verify(&chunk_data),
+            }
+        );
+    }
+}
+
+impl Iterator for ChunkIter<'_>
+{
+    type Item = Result<ChunkInfo, Box<dyn Error>>;
+
+    fn next(&mut self) -> Option<Self::Item>
+    {
+        // Check the real size of the file every time, so chunks appended through other handles are seen too
+        let file_size = match self.chunky.file.metadata()
+        {
+            Ok(metadata) => metadata.len(),
+            Err(e) => return Some(Err(Box::new(e))),
+        };
+
+        if self.pos >= file_size
+        {
+            return None;
+        }
+
+        let chunk = self.read_next(file_size);
+
+        // Don't keep going after an I/O error, the next read would most likely fail the same way
+        if chunk.is_err()
+        {
+            self.pos = file_size;
+        }
+
+        return Some(chunk);
+    }
+}
+
+// dbchunk::BinWriter - Streams binary data into a chain of overflow chunks, see ChunkyFile::bin_writer()
+//
+// Chunks are written as soon as they are full, so only a chunk's worth of data is ever held in memory. Nothing else may
+// append to the file until finish() is called, since the chain has to be contiguous. If the writer is dropped without
+// finishing, the chunks already written are left behind with no stub to end them.
+pub struct BinWriter<'a>
+{
+    chunky: &'a mut ChunkyFile,
+    buffer: Vec<u8>, // Data not yet written to a chunk
+    first_chunk: Option<u64>, // The position of the first chunk, once one has been written
+    next_chunk: Option<u64>, // Where the last chunk written says the next one is
+    length: u64, // The length of all the data written so far
+}
+
+impl BinWriter<'_>
+{
+    // dbchunk::BinWriter::write_chunk() - Write a chunk of the chain, making sure it lands where the previous one said it would
+    //
+    // ARGUMENTS:
+    //  chunk_part: &[u8] - The data of the chunk
+    //  last: bool - Whether this is the stub chunk that ends the chain
+    fn write_chunk(&mut self, chunk_part: &[u8], last: bool) -> Result<(), Box<dyn Error>>
+    {
+        let chunk_pos = match last
+        {
+            true => self.chunky.append_stub_chunk(CHUNK_TYPE::BIN, chunk_part)?,
+            false => self.chunky.append_continued_chunk(CHUNK_TYPE::BIN, chunk_part)?,
+        };
+
+        if self.next_chunk.is_some_and(|next_chunk| next_chunk != chunk_pos)
+        {
+            bail!("Something else appended to the file while binary data was being written!");
+        }
+
+        self.first_chunk.get_or_insert(chunk_pos);
+        self.next_chunk = Some(chunk_pos + CHUNKSZ as u64);
+
+        return Ok(());
+    }
+
+    // dbchunk::BinWriter::finish() - Write whatever is left and get the Bin to store in a field
+    //
+    pub fn finish(mut self) -> Result<Bin, Box<dyn Error>>
+    {
+        // Small values never touch the file
+        if self.first_chunk.is_none() && self.buffer.len() <= BIN_INLINE_MAX
+        {
+            return Ok(Bin::Inline(self.buffer));
+        }
+
+        let buffer = std::mem::take(&mut self.buffer);
+        self.write_chunk(&buffer, true)?;
+
+        return Ok
+        (
+            Bin::Overflow
+            {
+                chunk: self.first_chunk.expect("Overflowed binary data without a first chunk! You shouldn't see this!"),
+                length: self.length,
+            }
+        );
+    }
+}
+
+impl Write for BinWriter<'_>
+{
+    fn write(&mut self, buf: &[u8]) -> std::io::Result<usize>
+    {
+        let mut input = buf;
+
+        // Only write a continued chunk once there is more data than a stub chunk could hold, same as add_chunk_chain()
+        while self.buffer.len() + input.len() > CHUNK_STUB_DATASZ
+        {
+            let take = std::cmp::min(CHUNK_CONT_DATASZ.saturating_sub(self.buffer.len()), input.len());
+
+            self.buffer.extend_from_slice(&input[..take]);
+            input = &input[take..];
+
+            let rest = self.buffer.split_off(CHUNK_CONT_DATASZ);
+            let chunk_part = std::mem::replace(&mut self.buffer, rest);
+
+            self.write_chunk(&chunk_part, false).map_err(|e| std::io::Error::other(e.to_string()))?;
+        }
+
+        self.buffer.extend_from_slice(input);
+        self.length += buf.len() as u64;
+
+        return Ok(buf.len());
+    }
+
+    fn flush(&mut self) -> std::io::Result<()>
+    {
+        return self.chunky.file.flush();
+    }
+}
+
+// dbchunk::BinReader - Streams binary data out of a field or its overflow chunks, see ChunkyFile::bin_reader()
+//
+pub struct BinReader<'a>
+{
+    chunky: &'a mut ChunkyFile,
+    buffer: Vec<u8>, // The data of the current chunk
+    offset: usize, // How much of the buffer has been read
+    next_chunk: Option<u64>, // The next chunk to read, if there is one
+    remaining: u64, // How much data is left to read
+}
+
+impl BinReader<'_>
+{
+    // dbchunk::BinReader::load_chunk() - Read the next overflow chunk into the buffer, verifying its CRC
+    //
+    // ARGUMENTS:
+    //  chunk_pos: u64 - The position of the chunk
+    fn load_chunk(&mut self, chunk_pos: u64) -> Result<(), Box<dyn Error>>
+    {
+        let chunk_data = self.chunky.read_chunk(chunk_pos)?;
+
+        if (chunk_data[0] & CHUNK_FLAG::TYPE_MASK) != CHUNK_TYPE::BIN
+        {
+            bail!("Chunk at {} is not a binary overflow chunk!", chunk_pos);
+        }
+
+        let (data_start, data_end, next_chunk) = ChunkyFile::chunk_data_bounds(chunk_pos, &chunk_data)?;
+
+        self.buffer = chunk_data[(data_start - chunk_pos) as usize..(data_end - chunk_pos) as usize].to_vec();
+        self.offset = 0;
+        self.next_chunk = next_chunk;
+
+        return Ok(());
+    }
+}
+
+impl Read for BinReader<'_>
+{
+    fn read(&mut self, buf: &mut [u8]) -> std::io::Result<usize>
+    {
+        if self.remaining == 0 || buf.len() == 0
+        {
+            return Ok(0);
+        }
+
+        while self.offset >= self.buffer.len()
+        {
+            match self.next_chunk
+            {
+                Some(chunk_pos) => self.load_chunk(chunk_pos).map_err(|e| std::io::Error::other(e.to_string()))?,
+                None => return Err(std::io::Error::new(std::io::ErrorKind::UnexpectedEof, "Binary data ended before its length!")),
+            }
+        }
+
+        let read_len = std::cmp::min(std::cmp::min(buf.len(), self.buffer.len() - self.offset), usize::try_from(self.remaining).unwrap_or(usize::MAX));
+
+        buf[..read_len].copy_from_slice(&self.buffer[self.offset..self.offset + read_len]);
+        self.offset += read_len;
+        self.remaining -= read_len as u64;
+
+        return Ok(read_len);
+    }
+}
+
+// dbchunk::ChunkChain - The data of a chain of chunks, along with where each chunk sits in the file
+//
+#[derive(Debug)]
+pub struct ChunkChain
+{
+    pub chunk_type: u8, // The type of the chunks, without flags
+    pub chunks: Vec<u64>, // The position of every chunk in the chain
+    pub data: Vec<u8>, // The data of the chain with the chunk headers, padding and CRCs stripped
+    pub fields_start: usize, // Where the fields start in the data, after any preamble
+}
+
+impl ChunkChain
+{
+    // dbchunk::ChunkChain::file_position() - Map an offset in the chain data to a position in the file
+    //
+    // ARGUMENTS:
+    //  offset: usize - The offset into the chain data
+    pub fn file_position(&self, offset: usize) -> Option<u64>
+    {
+        if offset >= self.data.len()
+        {
+            return None;
+        }
+
+        // Every chunk but the last is a full continued chunk
+        let continued_chunks = self.chunks.len() - 1;
+        let chunk_index = offset / CHUNK_CONT_DATASZ;
+
+        if chunk_index < continued_chunks
+        {
+            return Some(self.chunks[chunk_index] + (CHUNK_CONT_HEADSZ + (offset % CHUNK_CONT_DATASZ)) as u64);
+        }
+
+        return Some(self.chunks[continued_chunks] + (CHUNK_STUB_HEADSZ + offset - (continued_chunks * CHUNK_CONT_DATASZ)) as u64);
+    }
+
+    // dbchunk::ChunkChain::insertion_points() - Get the position in the file of every field in the chain data
+    //
+    pub fn insertion_points(&self) -> Result<Vec<u64>, Box<dyn Error>>
+    {
+        let mut insertion_points = Vec::<u64>::new();
+        let mut i: usize = self.fields_start;
+
+        while i < self.data.len()
+        {
+            insertion_points.push(self.file_position(i).expect("Field offset outside of its own chain! You shouldn't see this!"));
+
+            let (_, field_len) = Field::from_bytes_with_len(&self.data[i..])?;
+            i += field_len;
+        }
+
+        return Ok(insertion_points);
+    }
+}
+
+// dbchunk::DbHeadChunk - Struct for creating and modifying the DB header chunk
+pub struct DbHeadChunk
+{
+    //pub chunk_numbers: Vec<u64>,
+    pub fields: Vec<Field>,
+}
+
+impl DbHeadChunk
+{
+    // dbchunk::DbHeadChunk::new() - Create a new DB header chunk
+    //
+    // ARGUMENTS:
+    //  name: &str - The name of the database
+    //  owner: &str - The owner of the database
+    pub fn new(name: &str, owner: &str) -> DbHeadChunk
+    {
+        let mut dbfields = Vec::<Field>::new();
+
+        dbfields.push(Field::new("name", Type::S(Some(S::new(name))))); // Name field, database name
+        dbfields.push(Field::new("ver", Type::I(Some(I::new(DB_FORMAT_VERSION))))); // Version field, database file version
+        dbfields.push(Field::new("uuid_cache_size", Type::I(Some(I::new(DB_DEFAULT_UUID_CACHE_SIZE))))); // Uuid cache size field
+        dbfields.push(Field::new("perm", Type::I(Some(I::new(DB_DEFAULT_UNIX_PERMISSIONS))))); // Unix permissions field
+        dbfields.push(Field::new("owner", Type::S(Some(S::new(owner))))); // Owner field
+        dbfields.push(Field::new("sane", Type::B(Some(B::new(true))))); // Sane field
+        dbfields.push(Field::new("insane", Type::B(Some(B::new(false))))); // Insane field
+        dbfields.push(Field::new("created", Type::T(Some(T::now())))); // Creation time field
+
+        debug_assert!(DbHeadChunk::structure().meets(&dbfields));
+
+        return DbHeadChunk
+        {
+            //chunk_numbers: Vec::<u64>::new(),
+            fields: dbfields,
+        };
+    }
+
+    // dbchunk::DbHeadChunk::structure() - Get the structure every DB header chunk meets
+    //
+    pub fn structure() -> Structure
+    {
+        let required = |id: &str, field_type: Type| Requirement::new(id, std::mem::discriminant(&field_type)).not_null().required();
+
+        return Structure::new("db", vec!
+        [
+            required("name", Type::S(None)),
+            required("ver", Type::I(None)),
+            required("uuid_cache_size", Type::I(None)),
+            required("perm", Type::I(None)),
+            required("owner", Type::S(None)),
+            required("sane", Type::B(None)),
+            required("insane", Type::B(None)),
+            required("created", Type::T(None)),
+        ]).expect("The DB header structure is invalid! You shouldn't see this!");
+    }
+}
+
+
+// dbchunk::DbHeader - The database header read back from a file
+//
+#[derive(Debug, Clone, PartialEq)]
+pub struct DbHeader
+{
+    pub name: String, // Name of the database
+    pub ver: i64, // Database file version
+    pub uuid_cache_size: i64, // Size of the UUID cache
+    pub perm: i64, // Unix permissions
+    pub owner: String, // Owner of the database
+    pub sane: bool,
+    pub insane: bool,
+    pub created: T, // When the database was created
+}
+
+impl DbHeader
+{
+    // dbchunk::DbHeader::from_fields() - Build a header from the fields of a header chunk
+    //
+    // ARGUMENTS:
+    //  fields: &[Field] - The fields read from the header chunk
+    pub fn from_fields(fields: &[Field]) -> Result<DbHeader, Box<dyn Error>>
+    {
+        let find = |id: &str| -> Result<&Type, Box<dyn Error>>
+        {
+            for field in fields
+            {
+                if field.id == id
+                {
+                    return Ok(&field.value);
+                }
+            }
+
+            bail!("Database header is missing the {} field!", id);
+        };
+
+        let get_s = |id: &str| -> Result<String, Box<dyn Error>>
+        {
+            match find(id)?
+            {
+                Type::S(Some(string)) => return Ok(string.as_str().to_string()),
+                _ => bail!("Database header field {} is not a string!", id),
+            }
+        };
+
+        let get_i = |id: &str| -> Result<i64, Box<dyn Error>>
+        {
+            match find(id)?
+            {
+                Type::I(Some(integer)) => match integer.to_i64()
+                {
+                    Some(integer) => return Ok(integer),
+                    None => bail!("Database header field {} is out of range!", id),
+                },
+                _ => bail!("Database header field {} is not an integer!", id),
+            }
+        };
+
+        let get_b = |id: &str| -> Result<bool, Box<dyn Error>>
+        {
+            match find(id)?
+            {
+                Type::B(Some(boolean)) => return Ok(boolean.is_true()),
+                _ => bail!("Database header field {} is not a boolean!", id),
+            }
+        };
+
+        let get_t = |id: &str| -> Result<T, Box<dyn Error>>
+        {
+            match find(id)?
+            {
+                Type::T(Some(time)) => return Ok(*time),
+                _ => bail!("Database header field {} is not a time!", id),
+            }
+        };
+
+        return Ok
+        (
+            DbHeader
+            {
+                name: get_s("name")?,
+                ver: get_i("ver")?,
+                uuid_cache_size: get_i("uuid_cache_size")?,
+                perm: get_i("perm")?,
+                owner: get_s("owner")?,
+                sane: get_b("sane")?,
+                insane: get_b("insane")?,
+                created: get_t("created")?,
+            }
+        );
+    }
+}
+
+// dbchunk::EntryChunk - Struct for creating entry chunks
+//
+// On disk an entry chunk starts with a preamble, the length of the preamble as a variable length integer followed by
+// fields describing the entry itself, such as when it was last modified. The entry's own fields follow the preamble.
+// The first preamble field is always the entry's UUID, so it sits in the first chunk of the entry.
+#[derive(Debug, Clone, PartialEq)]
+pub struct EntryChunk
+{
     //pub chunk_numbers: Vec<u64>,
+    pub uuid: UuidV4, // The UUID of the entry
     pub fields: Vec<Field>,
+    pub modified: T, // When the entry was last modified
+    pub version: u64, // The version of the structure the entry was written under
+}
+
+impl EntryChunk
+{
+    // dbchunk::EntryChunk::new() - Create an entry chunk from an entry, with no structure version
+    //
+    // ARGUMENTS:
+    //  entry: Entry - The entry to store
+    pub fn new(entry: Entry) -> Self
+    {
+        return Self
+        {
+            //chunk_numbers: Vec::<u64>::new(),
+            uuid: entry.uuid,
+            fields: entry.fields,
+            modified: entry.modified,
+            version: 0, // Set by the list writing the entry
+        };
+    }
+
+    // dbchunk::EntryChunk::from_fields() - Create an entry chunk from bare fields, with a new UUID, modified now and no structure version
+    //
+    // ARGUMENTS:
+    //  fields: Vec<Field> - The fields to store
+    pub fn from_fields(fields: Vec<Field>) -> Self
+    {
+        return Self
+        {
+            uuid: UuidV4::new(),
+            fields: fields,
+            modified: T::now(),
+            version: 0,
+        };
+    }
+
+    // dbchunk::EntryChunk::preamble_to_bytes() - Encode the preamble that goes in front of the entry's fields
+    //
+    pub fn preamble_to_bytes(&self) -> Result<Vec<u8>, Box<dyn Error>>
+    {
+        let mut preamble = Vec::<u8>::new();
+
+        preamble.extend_from_slice(&Field::new("uuid", Type::Ref(Some(self.uuid.clone()))).to_bytes()?);
+        preamble.extend_from_slice(&Field::new("modified", Type::T(Some(self.modified))).to_bytes()?);
+        preamble.extend_from_slice(&Field::new("version", Type::I(Some(I::from(self.version as i64)))).to_bytes()?);
+
+        let mut data = length_to_bytes(preamble.len());
+        data.extend_from_slice(&preamble);
+
+        return Ok(data);
+    }
+
+    // dbchunk::EntryChunk::from_bytes() - Decode the data of an entry chain, also returning where the fields start
+    //
+    // ARGUMENTS:
+    //  data: &[u8] - The data of the chain
+    pub fn from_bytes(data: &[u8]) -> Result<(EntryChunk, usize), Box<dyn Error>>
+    {
+        let (preamble_length, length_length) = length_from_bytes(data)?;
+        let fields_start = length_length + preamble_length;
+
+        if fields_start > data.len()
+        {
+            return Err(Box::new(DecodeError::Truncated { needed: fields_start, have: data.len() }));
+        }
+
+        let mut uuid: Option<UuidV4> = None;
+        let mut modified: Option<T> = None;
+        let mut version: Option<u64> = None;
+
+        // Unknown preamble fields are skipped so newer files can add to the preamble
+        for field in Field::from_stream(&data[length_length..fields_start])?
+        {
+            match (field.id.as_str(), field.value)
+            {
+                ("uuid", Type::Ref(Some(entry_uuid))) => uuid = Some(entry_uuid),
+                ("modified", Type::T(Some(time))) => modified = Some(time),
+                ("version", Type::I(Some(structure_version))) => version = structure_version.to_i64().and_then(|structure_version| u64::try_from(structure_version).ok()),
+                _ => {}
+            }
+        }
+
+        let uuid = match uuid
+        {
+            Some(uuid) => uuid,
+            None => bail!("Entry preamble is missing its UUID!"),
+        };
+
+        let modified = match modified
+        {
+            Some(modified) => modified,
+            None => bail!("Entry preamble is missing its modification time!"),
+        };
+
+        let version = match version
+        {
+            Some(version) => version,
+            None => bail!("Entry preamble is missing its structure version!"),
+        };
+
+        return Ok
+        (
+            (
+                EntryChunk
+                {
+                    uuid: uuid,
+                    fields: Field::from_stream(&data[fields_start..])?,
+                    modified: modified,
+                    version: version,
+                },
+                fields_start
+            )
+        );
+    }
+}
+
+// dbchunk::ListChunk - The UUID tree head and entry count of a list, along with the journal its trees share
+//
+// A list chunk is a single stub chunk laid out as in LIST_CHUNK_OFFSET, so every part but the ID can be changed with a
+// single write inside one chunk. The list it belongs to is found by its ID, which is the ID of its structure. The
+// indexes on its fields each have their own index chunk, see IndexChunk.
+#[derive(Debug, Clone, PartialEq)]
+pub struct ListChunk
+{
+    pub list_id: String,
+    pub uuid_tree_head: u64,
+    pub uuid_tree_laze: u8,
+    pub entry_count: u64,
+    pub journal: u64, // The journal the trees of the list share, see ChunkyFile::add_journal()
+}
+
+impl ListChunk
+{
+    // dbchunk::ListChunk::new() - Create a list chunk for an empty list
+    //
+    // ARGUMENTS:
+    //  list_id: &str - The ID of the list
+    //  journal: u64 - The position of the journal for the trees of the list
+    //  uuid_tree_laze: u8 - The laze of the UUID tree, see LazyAVL::max_balance()
+    pub fn new(list_id: &str, journal: u64, uuid_tree_laze: u8) -> Self
+    {
+        return Self
+        {
+            list_id: list_id.to_string(),
+            uuid_tree_head: 0,
+            uuid_tree_laze: uuid_tree_laze,
+            entry_count: 0,
+            journal: journal,
+        };
+    }
+
+    // dbchunk::ListChunk::to_bytes() - Encode the list chunk as the data of a stub chunk
+    //
+    pub fn to_bytes(&self) -> Result<Vec<u8>, Box<dyn Error>>
+    {
+        let id_length: u8 = match self.list_id.len().try_into()
+        {
+            Ok(id_length) if LIST_CHUNK_OFFSET::LIST_ID as usize - CHUNK_STUB_HEADSZ + 1 + id_length as usize <= CHUNK_STUB_DATASZ => id_length,
+            _ => bail!("List ID {} is too long to fit in a list chunk!", self.list_id),
+        };
+
+        let data = binary_data!
+        (
+            u64_be!(self.uuid_tree_head),
+            u64_be!(self.entry_count),
+            u64_be!(self.journal),
+            byte!(self.uuid_tree_laze),
+            byte!(id_length),
+            bytes_from_vec!(self.list_id.as_bytes())
+        );
+
+        return Ok(data);
+    }
+
+    // dbchunk::ListChunk::from_bytes() - Decode the data of a list chunk
+    //
+    // ARGUMENTS:
+    //  data: &[u8] - The data of the chunk, without its header
+    pub fn from_bytes(data: &[u8]) -> Result<ListChunk, Box<dyn Error>>
+    {
+        let at = |offset: u64| offset as usize - CHUNK_STUB_HEADSZ;
+        let id_start = at(LIST_CHUNK_OFFSET::LIST_ID) + 1;
+
+        if data.len() < id_start
+        {
+            return Err(Box::new(DecodeError::Truncated { needed: id_start, have: data.len() }));
+        }
+
+        let id_end = id_start + data[id_start - 1] as usize;
+
+        if data.len() < id_end
+        {
+            return Err(Box::new(DecodeError::Truncated { needed: id_end, have: data.len() }));
+        }
+
+        let u64_at = |offset: u64| u64::from_be_bytes(data[at(offset)..at(offset) + 8].try_into().expect("Slice of incorrect size when reading a list chunk, you shouldn't see this!"));
+        let uuid_tree_laze = data[at(LIST_CHUNK_OFFSET::UUID_TREE_LAZE)];
+
+        if uuid_tree_laze > LAZY_AVL_CONST::LAZE_MAX
+        {
+            bail!("List chunk has a laze of {}, the most is {}!", uuid_tree_laze, LAZY_AVL_CONST::LAZE_MAX);
+        }
+
+        return Ok
+        (
+            ListChunk
+            {
+                list_id: String::from_utf8(data[id_start..id_end].to_vec())?,
+                uuid_tree_head: u64_at(LIST_CHUNK_OFFSET::UUID_TREE_HEAD),
+                uuid_tree_laze: uuid_tree_laze,
+                entry_count: u64_at(LIST_CHUNK_OFFSET::ENTRY_COUNT),
+                journal: u64_at(LIST_CHUNK_OFFSET::JOURNAL),
+            }
+        );
+    }
 }
 
-impl EntryChunk
+// dbchunk::IndexChunk - The tree head and state of an index on one field of a list
+//
+// An index chunk is a single stub chunk laid out as in INDEX_CHUNK_OFFSET. Chunks are never taken out of the file, so
+// a dropped index keeps its chunk with its state set to INDEX_STATE::DROPPED, and creating the index again adds a new one.
+#[derive(Debug, Clone, PartialEq)]
+pub struct IndexChunk
 {
-    pub fn new(entry: Entry) -> Self
+    pub list_id: String,
+    pub field_id: String,
+    pub head: u64,
+    pub laze: u8,
+    pub state: u8, // See INDEX_STATE
+}
+
+impl IndexChunk
+{
+    // dbchunk::IndexChunk::new() - Create an index chunk for an empty index that is still being built
+    //
+    // ARGUMENTS:
+    //  list_id: &str - The ID of the list
+    //  field_id: &str - The ID of the indexed field
+    //  laze: u8 - The laze of the index tree, see LazyAVL::max_balance()
+    pub fn new(list_id: &str, field_id: &str, laze: u8) -> Self
     {
         return Self
         {
-            //chunk_numbers: Vec::<u64>::new(),
-            fields: entry.fields,
+            list_id: list_id.to_string(),
+            field_id: field_id.to_string(),
+            head: 0,
+            laze: laze,
+            state: INDEX_STATE::BUILDING,
+        };
+    }
+
+    // dbchunk::IndexChunk::to_bytes() - Encode the index chunk as the data of a stub chunk
+    //
+    pub fn to_bytes(&self) -> Result<Vec<u8>, Box<dyn Error>>
+    {
+        let fits = INDEX_CHUNK_OFFSET::LIST_ID as usize - CHUNK_STUB_HEADSZ + 2 + self.list_id.len() + self.field_id.len() <= CHUNK_STUB_DATASZ;
+
+        if !fits
+        {
+            bail!("Index on {} of list {} is too long to fit in an index chunk!", self.field_id, self.list_id);
+        }
+
+        let data = binary_data!
+        (
+            u64_be!(self.head),
+            byte!(self.laze),
+            byte!(self.state),
+            byte!(self.list_id.len() as u8),
+            bytes_from_vec!(self.list_id.as_bytes()),
+            byte!(self.field_id.len() as u8),
+            bytes_from_vec!(self.field_id.as_bytes())
+        );
+
+        return Ok(data);
+    }
+
+    // dbchunk::IndexChunk::from_bytes() - Decode the data of an index chunk
+    //
+    // ARGUMENTS:
+    //  data: &[u8] - The data of the chunk, without its header
+    pub fn from_bytes(data: &[u8]) -> Result<IndexChunk, Box<dyn Error>>
+    {
+        let at = |offset: u64| offset as usize - CHUNK_STUB_HEADSZ;
+
+        // Both IDs are a length followed by the ID
+        let read_id = |start: usize| -> Result<(String, usize), Box<dyn Error>>
+        {
+            if data.len() <= start
+            {
+                return Err(Box::new(DecodeError::Truncated { needed: start + 1, have: data.len() }));
+            }
+
+            let end = start + 1 + data[start] as usize;
+
+            if data.len() < end
+            {
+                return Err(Box::new(DecodeError::Truncated { needed: end, have: data.len() }));
+            }
+
+            return Ok((String::from_utf8(data[start + 1..end].to_vec())?, end));
+        };
+
+        let (list_id, list_id_end) = read_id(at(INDEX_CHUNK_OFFSET::LIST_ID))?;
+        let (field_id, _) = read_id(list_id_end)?;
+        let laze = data[at(INDEX_CHUNK_OFFSET::LAZE)];
+
+        if laze > LAZY_AVL_CONST::LAZE_MAX
+        {
+            bail!("Index chunk has a laze of {}, the most is {}!", laze, LAZY_AVL_CONST::LAZE_MAX);
+        }
+
+        return Ok
+        (
+            IndexChunk
+            {
+                list_id: list_id,
+                field_id: field_id,
+                head: u64::from_be_bytes(data[at(INDEX_CHUNK_OFFSET::HEAD)..at(INDEX_CHUNK_OFFSET::HEAD) + 8].try_into().expect("Slice of incorrect size when reading an index chunk, you shouldn't see this!")),
+                laze: laze,
+                state: data[at(INDEX_CHUNK_OFFSET::STATE)],
+            }
+        );
+    }
+}
+
+// Tests!
+//
+
+#[cfg(test)]
+mod tests
+{
+    use std::fs::remove_file;
+    use super::*;
+
+    // dbio::dbchunk::tests::create_test_file() - Create a fresh database file for a test, removing any leftovers
+    //
+    fn create_test_file(file_name: &str) -> ChunkyFile
+    {
+        let _ = remove_file(file_name);
+
+        return ChunkyFile::create(file_name, DbHeadChunk::new("Test Database", "tester")).unwrap();
+    }
+
+    // dbio::dbchunk::tests::test_chunky_open() - Tests reopening a file and appending to it
+    //
+    #[test]
+    fn test_chunky_open()
+    {
+        let file_name = "test_chunky_open.apedb";
+        let chunky = create_test_file(file_name);
+        drop(chunky);
+
+        let mut chunky = ChunkyFile::open(file_name).unwrap();
+        assert_eq!(chunky.size, CHUNKSZ);
+
+        let fields = vec![Field::new("id", Type::S(Some(S::new("Hello"))))];
+        let entry = Entry::new(UuidV4::new(), fields).unwrap();
+        chunky.add_entry_chunk(EntryChunk::new(entry)).unwrap();
+        assert_eq!(chunky.size, CHUNKSZ * 2);
+        drop(chunky);
+
+        let mut chunky = ChunkyFile::open(file_name).unwrap();
+        assert_eq!(chunky.size, CHUNKSZ * 2);
+        assert_eq!(chunky.read_chunk(CHUNKSZ as u64).unwrap()[0], CHUNK_TYPE::ENTRY);
+        drop(chunky);
+
+        remove_file(file_name).unwrap();
+    }
+
+    // dbio::dbchunk::tests::test_chunky_open_missing() - Tests that opening a missing file fails
+    //
+    #[test]
+    fn test_chunky_open_missing()
+    {
+        assert!(ChunkyFile::open("test_chunky_open_missing.apedb").is_err());
+    }
+
+    // dbio::dbchunk::tests::test_chunky_open_torn() - Tests that a file which isn't a multiple of the chunk size is refused
+    //
+    #[test]
+    fn test_chunky_open_torn()
+    {
+        let file_name = "test_chunky_open_torn.apedb";
+        let mut chunky = create_test_file(file_name);
+        chunky.file.write_all(&[0; 10]).unwrap();
+        drop(chunky);
+
+        assert!(ChunkyFile::open(file_name).is_err());
+
+        remove_file(file_name).unwrap();
+    }
+
+    // dbio::dbchunk::tests::test_chunky_open_bad_crc() - Tests that a header with a bad CRC is refused
+    //
+    #[test]
+    fn test_chunky_open_bad_crc()
+    {
+        let file_name = "test_chunky_open_bad_crc.apedb";
+        let mut chunky = create_test_file(file_name);
+        chunky.file.seek(SeekFrom::Start(10)).unwrap();
+        chunky.file.write_all(&[0xFF]).unwrap();
+        drop(chunky);
+
+        assert!(ChunkyFile::open(file_name).is_err());
+
+        remove_file(file_name).unwrap();
+    }
+
+    // dbio::dbchunk::tests::test_chunky_read_head() - Tests that the header written by create() reads back
+    //
+    #[test]
+    fn test_chunky_read_head()
+    {
+        let file_name = "test_chunky_read_head.apedb";
+        let before = T::now();
+        let chunky = create_test_file(file_name);
+        let after = T::now();
+        drop(chunky);
+
+        let mut chunky = ChunkyFile::open(file_name).unwrap();
+        let head = chunky.read_head().unwrap();
+
+        assert_eq!(head.name, "Test Database");
+        assert_eq!(head.owner, "tester");
+        assert_eq!(head.ver, DB_FORMAT_VERSION);
+        assert_eq!(head.uuid_cache_size, DB_DEFAULT_UUID_CACHE_SIZE);
+        assert_eq!(head.perm, DB_DEFAULT_UNIX_PERMISSIONS);
+        assert!(head.sane);
+        assert!(!head.insane);
+        assert!(before <= head.created && head.created <= after);
+        drop(chunky);
+
+        remove_file(file_name).unwrap();
+    }
+
+    // dbio::dbchunk::tests::test_chunky_long_head() - Tests a header long enough to be continued over several chunks
+    //
+    #[test]
+    fn test_chunky_long_head()
+    {
+        let file_name = "test_chunky_long_head.apedb";
+        let _ = remove_file(file_name);
+        let name = "N".repeat(200);
+        let owner = "O".repeat(200);
+
+        let mut chunky = ChunkyFile::create(file_name, DbHeadChunk::new(&name, &owner)).unwrap();
+        assert!(chunky.size > CHUNKSZ);
+
+        let head = chunky.read_head().unwrap();
+        assert_eq!(head.name, name);
+        assert_eq!(head.owner, owner);
+        drop(chunky);
+
+        remove_file(file_name).unwrap();
+    }
+
+    // dbio::dbchunk::tests::test_chunky_head_first() - Tests that a second header is refused
+    //
+    #[test]
+    fn test_chunky_head_first()
+    {
+        let file_name = "test_chunky_head_first.apedb";
+        let mut chunky = create_test_file(file_name);
+
+        assert!(chunky.add_chunk(&ChunkTypes::DbHead(DbHeadChunk::new("Again", "tester"))).is_err());
+        drop(chunky);
+
+        remove_file(file_name).unwrap();
+    }
+
+    // dbio::dbchunk::tests::test_chunky_read_entry_chunk() - Tests reading back an entry continued over several chunks
+    //
+    #[test]
+    fn test_chunky_read_entry_chunk()
+    {
+        let file_name = "test_chunky_read_entry_chunk.apedb";
+        let mut chunky = create_test_file(file_name);
+
+        let mut fields = Vec::<Field>::new();
+
+        for i in 0..20
+        {
+            fields.push(Field::new(&format!("field{}", i), Type::S(Some(S::new(&"x".repeat(i * 10))))));
+        }
+
+        let entry_pos = chunky.size as u64;
+        let entry = Entry::new(UuidV4::new(), fields.clone()).unwrap();
+        let uuid = entry.uuid.clone();
+        let modified = entry.modified;
+        let mut entry_chunk = EntryChunk::new(entry);
+        entry_chunk.version = 7;
+        let (uuid_point, insertion_points) = chunky.add_entry_chunk_points(entry_chunk).unwrap();
+
+        let (chain, entry_chunk) = chunky.read_entry_chunk(entry_pos).unwrap();
+
+        assert!(chain.chunks.len() > 1);
+        assert_eq!(entry_chunk.fields, fields);
+        assert_eq!(entry_chunk.uuid, uuid);
+        assert_eq!(entry_chunk.modified, modified);
+        assert_eq!(entry_chunk.version, 7);
+
+        // The UUID leads back to the start of the entry
+        assert_eq!(ChunkyFile::chunk_pos(uuid_point), entry_pos);
+        assert_eq!(chunky.read_field(uuid_point).unwrap().value, Type::Ref(Some(uuid)));
+        assert_eq!(chain.insertion_points().unwrap(), insertion_points);
+
+        // Every insertion point should lead back to its field, even when the field crosses a chunk, and to the start of the entry
+        for (i, insertion_point) in insertion_points.iter().enumerate()
+        {
+            assert_eq!(chunky.read_field(*insertion_point).unwrap(), fields[i]);
+            assert_eq!(chunky.chain_start(*insertion_point).unwrap(), entry_pos);
+        }
+
+        drop(chunky);
+
+        remove_file(file_name).unwrap();
+    }
+
+    // dbio::dbchunk::tests::test_chunky_write_at() - Tests that patching a chain keeps every CRC valid
+    //
+    #[test]
+    fn test_chunky_write_at()
+    {
+        let file_name = "test_chunky_write_at.apedb";
+        let mut chunky = create_test_file(file_name);
+
+        let entry_pos = chunky.size as u64;
+        let fields = vec![Field::new("id", Type::S(Some(S::new(&"y".repeat(214))))), Field::new("next", Type::I(Some(I::new(5))))];
+        let entry = Entry::new(UuidV4::new(), fields).unwrap();
+        let insertion_points = chunky.add_entry_chunk(EntryChunk::new(entry)).unwrap();
+
+        // The second field starts in the first chunk and finishes in the second one
+        let child_pos = insertion_points[1] + 1;
+        chunky.write_at(child_pos, &0xDEADBEEF_u64.to_be_bytes()).unwrap();
+
+        let (_, entry_chunk) = chunky.read_entry_chunk(entry_pos).unwrap();
+        assert_eq!(entry_chunk.fields[1].left_child, 0xDEADBEEF);
+
+        drop(chunky);
+
+        remove_file(file_name).unwrap();
+    }
+
+    // dbio::dbchunk::tests::test_chunky_chunks() - Tests walking a file chunk by chunk, including damaged chunks
+    //
+    #[test]
+    fn test_chunky_chunks()
+    {
+        let file_name = "test_chunky_chunks.apedb";
+        let mut chunky = create_test_file(file_name);
+
+        let fields = vec![Field::new("id", Type::S(Some(S::new(&"z".repeat(250)))))];
+        chunky.add_entry_chunk(EntryChunk::new(Entry::new(UuidV4::new(), fields).unwrap())).unwrap();
+
+        // Damage the last chunk and leave half a chunk at the end of the file
+        chunky.file.seek(SeekFrom::Start((CHUNKSZ * 2 + 20) as u64)).unwrap();
+        chunky.file.write_all(&[0xFF]).unwrap();
+        chunky.file.seek(SeekFrom::End(0)).unwrap();
+        chunky.file.write_all(&[0; 100]).unwrap();
+        drop(chunky);
+
+        assert!(ChunkyFile::open(file_name).is_err());
+
+        let mut chunky = ChunkyFile::open_unchecked(file_name).unwrap();
+        let chunks: Vec<ChunkInfo> = chunky.chunks().map(|chunk| chunk.unwrap()).collect();
+
+        assert_eq!(chunks.len(), 4);
+
+        assert_eq!(chunks[0].kind, ChunkKind::DbHead { next_chunk: None });
+        assert!(chunks[0].crc_ok);
+
+        assert_eq!(chunks[1].pos, CHUNKSZ as u64);
+        assert_eq!(chunks[1].kind, ChunkKind::EntryContinued { next_chunk: (CHUNKSZ * 2) as u64 });
+        assert_eq!(chunks[1].flags, CHUNK_FLAG::CONTINUED);
+        assert!(chunks[1].crc_ok);
+
+        assert!(matches!(chunks[2].kind, ChunkKind::EntryStub { .. }));
+        assert!(!chunks[2].crc_ok);
+
+        assert_eq!(chunks[3].kind, ChunkKind::TornTail { length: 100 });
+        assert!(!chunks[3].crc_ok);
+
+        drop(chunky);
+
+        remove_file(file_name).unwrap();
+    }
+
+    // dbio::dbchunk::tests::test_chunky_schemas() - Tests storing structures, long enough to continue over several chunks, and finding them again
+    //
+    #[test]
+    fn test_chunky_schemas()
+    {
+        let file_name = "test_chunky_schemas.apedb";
+        let mut chunky = create_test_file(file_name);
+
+        let short = Structure::new("short", vec![Requirement::new("id", std::mem::discriminant(&Type::S(None))).required()]).unwrap();
+        let long = Structure::new("long", (0..20).map(|i| Requirement::new(&format!("field{}", i), std::mem::discriminant(&Type::I(None))).constrain(Constraint::Min(I::new(i)))).collect()).unwrap();
+
+        let short_pos = chunky.add_schema_chunk(&short).unwrap();
+        chunky.add_entry_chunk(EntryChunk::from_fields(vec![Field::new("id", Type::S(Some(S::new("Test"))))])).unwrap();
+        let long_pos = chunky.add_schema_chunk(&long).unwrap();
+
+        assert_eq!(chunky.read_schema_chunk(short_pos).unwrap(), short);
+        assert_eq!(chunky.read_schema_chunk(long_pos).unwrap(), long);
+        assert!(chunky.read_schema_chunk(CHUNKSZ as u64 * 2).is_err()); // The entry
+        drop(chunky);
+
+        let mut chunky = ChunkyFile::open(file_name).unwrap();
+        assert_eq!(chunky.schemas().unwrap(), vec![short, long]);
+
+        let kinds: Vec<ChunkKind> = chunky.chunks().map(|chunk| chunk.unwrap().kind).collect();
+        assert!(matches!(kinds[1], ChunkKind::SchemaStub { .. }));
+        assert!(matches!(kinds[3], ChunkKind::SchemaContinued { .. }));
+
+        // The header itself meets the header structure
+        let head = Field::from_stream(&chunky.read_chunk_chain(0).unwrap().data).unwrap();
+        assert_eq!(DbHeadChunk::structure().check(&head), Ok(()));
+        drop(chunky);
+
+        remove_file(file_name).unwrap();
+    }
+
+    // dbio::dbchunk::tests::test_chunky_journal() - Tests that journals only make their writes once armed, and are finished when the file is opened
+    //
+    #[test]
+    fn test_chunky_journal()
+    {
+        let file_name = "test_chunky_journal.apedb";
+        let mut chunky = create_test_file(file_name);
+
+        let entry_pos = chunky.size as u64;
+        let insertion_points = chunky.add_entry_chunk(EntryChunk::from_fields(vec![Field::new("a", Type::I(Some(I::new(1)))), Field::new("b", Type::I(Some(I::new(2))))])).unwrap();
+        let patches: Vec<JournalPatch> = insertion_points.iter().map(|insertion_point| (*insertion_point + 1, 7u64.to_be_bytes().to_vec())).collect();
+
+        // Journals take up whole chunks, and too small a journal is refused outright
+        let small_journal = chunky.add_journal(0).unwrap();
+        let journal = chunky.add_journal(CHUNK_STUB_DATASZ * 3).unwrap();
+        assert_eq!(chunky.journal_capacity(small_journal).unwrap(), CHUNK_STUB_DATASZ);
+        assert!(chunky.journal_capacity(journal).unwrap() >= CHUNK_STUB_DATASZ * 3);
+
+        chunky.write_journal(small_journal, &patches[..1]).unwrap();
+        chunky.replay_journal(small_journal).unwrap();
+        assert!(chunky.write_journal(small_journal, &vec![patches[0].clone(); 20]).is_err());
+
+        // An armed journal hasn't made its writes yet
+        chunky.write_journal(journal, &patches).unwrap();
+        assert_eq!(chunky.read_entry_chunk(entry_pos).unwrap().1.fields[1].left_child, 0);
+        drop(chunky);
+
+        let mut chunky = ChunkyFile::open(file_name).unwrap();
+        let (_, entry_chunk) = chunky.read_entry_chunk(entry_pos).unwrap();
+        assert_eq!(entry_chunk.fields[0].left_child, 7);
+        assert_eq!(entry_chunk.fields[1].left_child, 7);
+
+        // Replayed journals are disarmed, both of them
+        assert_eq!(chunky.replay_journals().unwrap(), 0);
+        assert!(chunky.chunks().all(|chunk| (chunk.unwrap().flags & CHUNK_FLAG::UNDER_CONSTRUCTION) == 0));
+        assert!(chunky.write_journal(entry_pos, &patches).is_err());
+        drop(chunky);
+
+        remove_file(file_name).unwrap();
+    }
+
+    // dbio::dbchunk::tests::test_chunky_list_chunks() - Tests storing list chunks, finding them again and changing them in place
+    //
+    #[test]
+    fn test_chunky_list_chunks()
+    {
+        let file_name = "test_chunky_list_chunks.apedb";
+        let mut chunky = create_test_file(file_name);
+
+        let mut users = ListChunk::new("users", 1234, 3);
+        let posts = ListChunk::new("posts", 5678, 0);
+
+        let users_pos = chunky.add_list_chunk(&users).unwrap();
+        chunky.add_entry_chunk(EntryChunk::from_fields(vec![Field::new("id", Type::S(Some(S::new("Test"))))])).unwrap();
+        let posts_pos = chunky.add_list_chunk(&posts).unwrap();
+
+        assert_eq!(chunky.read_list_chunk(users_pos).unwrap(), users);
+        assert!(chunky.read_list_chunk(CHUNKSZ as u64 * 2).is_err()); // The entry
+        assert!(chunky.add_list_chunk(&ListChunk::new(&"x".repeat(250), 0, 0)).is_err());
+
+        // Every part but the ID can be changed in place
+        chunky.write_at(users_pos + LIST_CHUNK_OFFSET::UUID_TREE_HEAD, &2u64.to_be_bytes()).unwrap();
+        chunky.write_at(users_pos + LIST_CHUNK_OFFSET::ENTRY_COUNT, &3u64.to_be_bytes()).unwrap();
+        users.uuid_tree_head = 2;
+        users.entry_count = 3;
+
+        // A laze the tree can't use is refused rather than passed on
+        chunky.write_at(users_pos + LIST_CHUNK_OFFSET::UUID_TREE_LAZE, &[LAZY_AVL_CONST::LAZE_MAX + 1]).unwrap();
+        assert!(chunky.read_list_chunk(users_pos).is_err());
+        chunky.write_at(users_pos + LIST_CHUNK_OFFSET::UUID_TREE_LAZE, &[users.uuid_tree_laze]).unwrap();
+        drop(chunky);
+
+        let mut chunky = ChunkyFile::open(file_name).unwrap();
+        assert_eq!(chunky.list_chunks().unwrap(), vec![(users_pos, users), (posts_pos, posts)]);
+        drop(chunky);
+
+        remove_file(file_name).unwrap();
+    }
+
+    // dbio::dbchunk::tests::test_chunky_index_chunks() - Tests storing index chunks, finding them again and changing them in place
+    //
+    #[test]
+    fn test_chunky_index_chunks()
+    {
+        let file_name = "test_chunky_index_chunks.apedb";
+        let mut chunky = create_test_file(file_name);
+
+        let mut email = IndexChunk::new("users", "email", 2);
+        let title = IndexChunk::new("posts", "title", 0);
+        assert_eq!(email.state, INDEX_STATE::BUILDING);
+
+        let email_pos = chunky.add_index_chunk(&email).unwrap();
+        chunky.add_list_chunk(&ListChunk::new("users", 0, 0)).unwrap();
+        let title_pos = chunky.add_index_chunk(&title).unwrap();
+
+        assert_eq!(chunky.read_index_chunk(email_pos).unwrap(), email);
+        assert!(chunky.read_index_chunk(email_pos + CHUNKSZ as u64).is_err()); // The list chunk
+        assert!(chunky.add_index_chunk(&IndexChunk::new(&"x".repeat(120), &"y".repeat(120), 0)).is_err());
+
+        // Every part but the IDs can be changed in place
+        chunky.write_at(email_pos + INDEX_CHUNK_OFFSET::HEAD, &7u64.to_be_bytes()).unwrap();
+        chunky.write_at(email_pos + INDEX_CHUNK_OFFSET::STATE, &[INDEX_STATE::READY]).unwrap();
+        email.head = 7;
+        email.state = INDEX_STATE::READY;
+
+        // A laze the tree can't use is refused rather than passed on
+        chunky.write_at(email_pos + INDEX_CHUNK_OFFSET::LAZE, &[LAZY_AVL_CONST::LAZE_MAX + 1]).unwrap();
+        assert!(chunky.read_index_chunk(email_pos).is_err());
+        chunky.write_at(email_pos + INDEX_CHUNK_OFFSET::LAZE, &[email.laze]).unwrap();
+        drop(chunky);
+
+        let mut chunky = ChunkyFile::open(file_name).unwrap();
+        assert_eq!(chunky.index_chunks().unwrap(), vec![(email_pos, email), (title_pos, title)]);
+        assert_eq!(chunky.list_chunks().unwrap().len(), 1);
+        drop(chunky);
+
+        remove_file(file_name).unwrap();
+    }
+
+    // dbio::dbchunk::tests::test_chunky_bin_inline() - Tests that small binary values stay inline and never touch the file
+    //
+    #[test]
+    fn test_chunky_bin_inline()
+    {
+        let file_name = "test_chunky_bin_inline.apedb";
+        let mut chunky = create_test_file(file_name);
+
+        let data: Vec<u8> = (0..BIN_INLINE_MAX).map(|i| i as u8).collect();
+        let bin = chunky.add_bin(&data).unwrap();
+
+        assert_eq!(bin, Bin::new(&data));
+        assert_eq!(chunky.size, CHUNKSZ);
+
+        let mut read_back = Vec::<u8>::new();
+        chunky.bin_reader(&bin).read_to_end(&mut read_back).unwrap();
+        assert_eq!(read_back, data);
+
+        drop(chunky);
+
+        remove_file(file_name).unwrap();
+    }
+
+    // dbio::dbchunk::tests::test_chunky_bin_overflow() - Tests streaming a big binary value through overflow chunks and back
+    //
+    #[test]
+    fn test_chunky_bin_overflow()
+    {
+        let file_name = "test_chunky_bin_overflow.apedb";
+        let mut chunky = create_test_file(file_name);
+
+        let data: Vec<u8> = (0..100_000u32).map(|i| (i * 7 + i / 256) as u8).collect();
+
+        // Write in awkward sizes so chunks fill up part way through writes
+        let mut writer = chunky.bin_writer();
+
+        for part in data.chunks(1000)
+        {
+            let (a, b) = part.split_at(3);
+            writer.write_all(a).unwrap();
+            writer.write_all(b).unwrap();
+        }
+
+        let bin = writer.finish().unwrap();
+
+        assert_eq!(bin, Bin::Overflow { chunk: CHUNKSZ as u64, length: data.len() as u64 });
+        assert_eq!(chunky.size, CHUNKSZ * (1 + data.len().div_ceil(CHUNK_CONT_DATASZ)));
+
+        // The chain is laid out exactly as add_chunk_chain() would have written it
+        let chain = chunky.read_chunk_chain(CHUNKSZ as u64).unwrap();
+        assert_eq!(chain.chunk_type, CHUNK_TYPE::BIN);
+        assert_eq!(chain.data, data);
+
+        // Store the value in a field and read it back in small pieces
+        let fields = vec![Field::new("photo", Type::Bin(Some(bin)))];
+        let insertion_points = chunky.add_entry_chunk(EntryChunk::new(Entry::new(UuidV4::new(), fields).unwrap())).unwrap();
+
+        let bin = match chunky.read_field(insertion_points[0]).unwrap().value
+        {
+            Type::Bin(Some(bin)) => bin,
+            value => panic!("Expected binary data, got {:?}", value),
         };
+
+        let mut reader = chunky.bin_reader(&bin);
+        let mut read_back = Vec::<u8>::new();
+        let mut buf = [0u8; 100];
+
+        loop
+        {
+            let read_len = reader.read(&mut buf).unwrap();
+
+            if read_len == 0
+            {
+                break;
+            }
+
+            read_back.extend_from_slice(&buf[..read_len]);
+        }
+
+        assert_eq!(read_back, data);
+
+        let chunks: Vec<ChunkInfo> = chunky.chunks().map(|chunk| chunk.unwrap()).collect();
+        assert_eq!(chunks[1].kind, ChunkKind::BinContinued { next_chunk: (CHUNKSZ * 2) as u64 });
+        assert!(matches!(chunks[chunks.len() - 2].kind, ChunkKind::BinStub { .. }));
+
+        // Damaged overflow chunks fail their CRC check
+        chunky.file.seek(SeekFrom::Start((CHUNKSZ * 5 + 50) as u64)).unwrap();
+        chunky.file.write_all(&[0xFF]).unwrap();
+
+        assert!(chunky.bin_reader(&bin).read_to_end(&mut Vec::<u8>::new()).is_err());
+
+        drop(chunky);
+
+        remove_file(file_name).unwrap();
     }
-}
\ No newline at end of file
+}
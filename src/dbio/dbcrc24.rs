@@ -75,7 +75,7 @@ mod tests
         data.extend_from_slice(hello);
         data.extend_from_slice(&crc24.to_be_bytes());
 
-        assert_eq!(ApeCrc24::verify(&data), true);
+        assert!(ApeCrc24::verify(&data));
     }
 
     #[test]
@@ -94,6 +94,6 @@ mod tests
 
         data.extend_from_slice(&crc24.to_be_bytes());
 
-        assert_eq!(ApeCrc24::verify(&data), false);
+        assert!(!ApeCrc24::verify(&data));
     }
 }
\ No newline at end of file
@@ -2,11 +2,12 @@
 
 
 
-use std::io::Read;
-use std::io::Seek;
-use std::fs::File;
+use std::cmp::Ordering;
 use crate::apetypes::*;
+use crate::dbio::dbchunk::ChunkyFile;
+use crate::dbio::dbuuid::UuidV4;
 use std::error::Error;
+use std::fmt;
 use simple_error::*;
 use apebdlm::*;
 
@@ -14,6 +15,8 @@ use apebdlm::*;
 //
 
 pub const FIELDHEADSZ: usize = 18; // 1 header byte + pointer to left child + pointer to right child + field type
+pub const NULL_TYPE: u8 = 0x00; // Type byte of null values, the value is the sort class of the type they would have had
+const MAX_NESTING: usize = 32; // The deepest lists and documents can be nested, so bad data can't blow the stack
 
 // Enums!
 //
@@ -26,11 +29,49 @@ pub enum FieldCmp
     LessThan
 }
 
+// dbio::dbfield::DecodeError - Everything that can go wrong when converting bytes to a field
+//
+#[derive(Debug, Clone, PartialEq)]
+pub enum DecodeError
+{
+    Truncated { needed: usize, have: usize }, // The data ended early, at least needed bytes are required
+    InvalidUtf8, // The ID or a string value isn't valid UTF-8
+    UnknownTypeByte(u8), // The type byte doesn't belong to any type
+    InvalidValue { value_type: u8 }, // The value doesn't make sense for its type
+    LengthOverflow, // A length is too large to ever fit in memory
+}
+
+impl fmt::Display for DecodeError
+{
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result
+    {
+        match self
+        {
+            DecodeError::Truncated { needed, have } => write!(f, "Field truncated, needed {} bytes but only have {}!", needed, have),
+            DecodeError::InvalidUtf8 => write!(f, "Field contains invalid UTF-8!"),
+            DecodeError::UnknownTypeByte(value_type) => write!(f, "Unknown value type byte 0x{:02X}!", value_type),
+            DecodeError::InvalidValue { value_type } => write!(f, "Invalid value for type byte 0x{:02X}!", value_type),
+            DecodeError::LengthOverflow => write!(f, "Field length overflows!"),
+        }
+    }
+}
+
+impl Error for DecodeError {}
+
 // Structs!
 //
 
 
 
+// dbio::dbfield::FieldBytes - The parts of an encoded field that matter when comparing it, see Field::split_bytes
+//
+pub struct FieldBytes<'a>
+{
+    pub id: &'a [u8], // The ID
+    pub value_type: u8, // The type byte
+    pub value: &'a [u8], // The value, without its length
+}
+
 // dbio::dbfield::Field - A data structure used to assign IDs to values.
 //
 #[derive(Debug, Clone, PartialEq)]
@@ -67,24 +108,32 @@ impl Field
     pub fn to_bytes(&self) -> Result<Vec<u8>, Box<dyn Error>>
     {
         let id_data = self.id.as_bytes();
-        let id_length:u8 = id_data.len().try_into().expect("ID length check fail!"); // The maximum length of an ID is 255 bytes, this should have been checked before...
-
-        let value_data:Vec<u8> = match &self.value
+        let id_length: u8 = match id_data.len().try_into()
         {
-            Type::S(string) =>
-            {
-                string.as_ref().unwrap().to_bytes()
-            }
-            Type::I(integer) =>
-            {
-                integer.as_ref().unwrap().to_bytes()
-            }
-            Type::B(_) =>
-            {
-                Vec::<u8>::new() // Boolean's values are stored in their type, there is no value to store
-            }
+            Ok(id_length) => id_length,
+            Err(_) => bail!("Field ID {} is longer than 255 bytes!", self.id),
         };
 
+        // Nulls of every type share a layout, and sort before everything else thanks to their type byte
+        if self.value.is_null()
+        {
+            let null_data = binary_data!
+            (
+                byte_signed!(self.avl_balace), // Header byte, used for binary tree metadata
+                u64_be!(self.left_child), // Pointer to left child
+                u64_be!(self.right_child), // Pointer to right child
+                byte!(NULL_TYPE), // The null type
+                byte!(id_length), // The length of the ID in bytes (max 255)
+                bytes_from_vec!(id_data), // The ID
+                bytes_from_vec!(length_to_bytes(1)), // The length of the value, always 1
+                byte!(self.value.sort_class()) // The type the value would have had
+            );
+
+            return Ok(null_data);
+        }
+
+        let value_data:Vec<u8> = value_to_bytes(&self.value, 0)?; // Boolean's values are stored in their type, they have no value data
+
         let value_type: u8 = match &self.value
         {
             Type::S(_) =>
@@ -95,6 +144,38 @@ impl Field
             {
                 b'I'
             }
+            Type::F(_) =>
+            {
+                b'F'
+            }
+            Type::D(_) =>
+            {
+                b'D'
+            }
+            Type::Bin(_) =>
+            {
+                b'X'
+            }
+            Type::T(_) =>
+            {
+                b'T'
+            }
+            Type::Date(_) =>
+            {
+                b'A'
+            }
+            Type::List(_) =>
+            {
+                b'L'
+            }
+            Type::Doc(_) =>
+            {
+                b'O'
+            }
+            Type::Ref(_) =>
+            {
+                b'R'
+            }
             Type::B(boolean) =>
             {
                 // If the boolean is true, the type is an uppercase 'B', otherwise the type is a lowercase 'b'
@@ -111,7 +192,7 @@ impl Field
 
         let data = match &self.value
         {
-            Type::S(_) | Type::I(_) => // Types S and I have the same binary layout...
+            Type::S(_) | Type::I(_) | Type::F(_) | Type::D(_) | Type::Bin(_) | Type::T(_) | Type::Date(_) | Type::List(_) | Type::Doc(_) | Type::Ref(_) => // Everything but booleans has the same binary layout...
             {
                 binary_data!
                 (
@@ -121,7 +202,7 @@ impl Field
                     byte!(value_type), // The type
                     byte!(id_length), // The length of the ID in bytes (max 255)
                     bytes_from_vec!(id_data), // The ID
-                    byte!(value_data.len()), // The length of the value in bytes (max 255)
+                    bytes_from_vec!(length_to_bytes(value_data.len())), // The length of the value in bytes, variable length
                     bytes_from_vec!(value_data) // The value
                 )
             }
@@ -146,56 +227,46 @@ impl Field
     //
     // ARGUMENTS:
     //  data: &[u8] - A slice of bytes to be converted to a field
-    pub fn from_bytes(data: &[u8]) -> Result<Field, Box<dyn Error>>
+    pub fn from_bytes(data: &[u8]) -> Result<Field, DecodeError>
     {
-        // Check to make sure the length of the data isn't too short(to be implemented)
+        let (field, _) = Field::from_bytes_with_len(data)?;
+
+        return Ok(field);
+    }
 
+    // dbio::dbfield::Field::from_bytes_with_len - Converts bytes to a field, also returning the number of bytes the field took up
+    //
+    // ARGUMENTS:
+    //  data: &[u8] - A slice of bytes starting with the field to be converted
+    pub fn from_bytes_with_len(data: &[u8]) -> Result<(Field, usize), DecodeError>
+    {
         // Use an iterator through the data to keep track of where we are...
         let mut i: usize = 0;
-        // Get all the fixed header data...
 
-        let avl_balance: i8 = data[i] as i8; // Get the avl balance...
-        i += 1;
-
-        let left_child: u64 = u64::from_be_bytes(data[i..i+8].try_into().expect("Slice of incorrect size when reading the left child of an entry, you shouldn't see this!")); // Get the left child pointer...
-        i += 8;
+        // Get all the fixed header data, making sure it is all there first...
+        let header = take_bytes(data, i, FIELDHEADSZ + 1)?;
+        i += FIELDHEADSZ + 1;
 
-        let right_child: u64 = u64::from_be_bytes(data[i..i+8].try_into().expect("Slice of incorrect size when reading the right child of an entry, you shouldn't see this!")); // Get the right child pointer...
-        i += 8;
-
-        let value_type_byte: u8 = data[i]; // Get the value type...
-        i += 1;
+        let avl_balance: i8 = header[0] as i8; // Get the avl balance...
+        let left_child: u64 = u64::from_be_bytes(header[1..9].try_into().expect("Slice of incorrect size when reading the left child of an entry, you shouldn't see this!")); // Get the left child pointer...
+        let right_child: u64 = u64::from_be_bytes(header[9..17].try_into().expect("Slice of incorrect size when reading the right child of an entry, you shouldn't see this!")); // Get the right child pointer...
+        let value_type_byte: u8 = header[17]; // Get the value type...
+        let id_length: u8 = header[18]; // Get the length of the ID...
 
         // Get the ID...
-        let id_length: u8 = data[i]; // Get the length of the ID...
-        // Check to see if the ID length doesn't make sense (to be implemented)...
-        i += 1;
-
-        let id_data: Vec<u8> = data[i..i+id_length as usize].to_vec(); // Get the ID data...
+        let id_data = take_bytes(data, i, id_length as usize)?; // Get the ID data...
         i += id_length as usize;
 
+        let id = match std::str::from_utf8(id_data)
+        {
+            Ok(id) => id.to_string(),
+            Err(_) => return Err(DecodeError::InvalidUtf8),
+        };
+
         // Get the value data...
-        // Note that the way we extrapolate the data depends on the value type...
+        // Note that booleans are stored in their type and have no value data
         let value = match value_type_byte
         {
-            b'S' =>
-            {
-                let value_length: u8 = data[i]; // Get the length of the value...
-                i += 1;
-                let value_data: Vec<u8> = data[i..i+value_length as usize].to_vec(); // Get the value data...
-
-                Type::S(Some(S::from_bytes(&value_data)?)) // Set value to a string...
-            }
-
-            b'I' =>
-            {
-                let value_length: u8 = data[i]; // Get the length of the value...
-                i += 1;
-                let value_data: Vec<u8> = data[i..i+8].to_vec(); // Get the value data, should work with different sized integers(to be implemented)...
-
-                Type::I(Some(I::from_bytes(&value_data)?)) // Set value to an integer...
-            }
-
             b'B' =>
             {
                 Type::B(Some(B::new(true))) // Set value to a boolean...
@@ -208,39 +279,71 @@ impl Field
 
             _ =>
             {
-                bail!("Invalid value type byte!");
+                let value_data = take_value_bytes(data, &mut i)?; // Get the value data...
+
+                value_from_bytes(value_type_byte, value_data, 0)?
             }
         };
 
         return Ok
         (
-            Field
-            {
-                avl_balace: avl_balance,
-                left_child: left_child,
-                right_child: right_child,
-                id: String::from_utf8(id_data.to_vec())?,
-                value: value,
-            }
-        )
+            (
+                Field
+                {
+                    avl_balace: avl_balance,
+                    left_child: left_child,
+                    right_child: right_child,
+                    id: id,
+                    value: value,
+                },
+                i
+            )
+        );
     }
 
+    // dbio::dbfield::Field::from_stream - Converts a stream of back-to-back fields, such as the data of a chunk chain, to fields.
+    //
+    // ARGUMENTS:
+    //  data: &[u8] - A slice of bytes containing nothing but fields
+    pub fn from_stream(data: &[u8]) -> Result<Vec<Field>, DecodeError>
+    {
+        let mut fields = Vec::<Field>::new();
+        let mut i: usize = 0;
+
+        while i < data.len()
+        {
+            let (field, field_len) = Field::from_bytes_with_len(&data[i..])?;
+            fields.push(field);
+            i += field_len;
+        }
+
+        return Ok(fields);
+    }
+
+    // dbio::dbfield::Field::cmp - Compares two fields by ID and then by value
+    //
+    // Nulls sort before every value, like NULLS FIRST in SQL. Two nulls of the same type compare equal so they can share
+    // an index, it's up to queries to decide that null never matches anything. cmp_in_file() follows the same rules.
+    //
+    // ARGUMENTS:
+    //  field_b: &Field - The field to compare with
+    #[allow(clippy::should_implement_trait)] // Returns a FieldCmp rather than an Ordering
     pub fn cmp(&self, field_b: &Field) -> Result<FieldCmp, Box<dyn Error>>
     {
-        if(self.id < field_b.id)
+        if self.id < field_b.id
         {
             return Ok(FieldCmp::LessThan);
         }
-        if(self.id > field_b.id)
+        if self.id > field_b.id
         {
             return Ok(FieldCmp::GreaterThan);
         }
 
-        if(self.value < field_b.value)
+        if self.value < field_b.value
         {
             return Ok(FieldCmp::LessThan);
         }
-        if(self.value > field_b.value)
+        if self.value > field_b.value
         {
             return Ok(FieldCmp::GreaterThan);
         }
@@ -248,101 +351,541 @@ impl Field
         return Ok(FieldCmp::Equal);
     }
 
-    pub fn cmp_in_file(file: &mut File, field_point_a: u64, field_point_b: u64) -> Result<FieldCmp, Box<dyn Error>>
+    // dbio::dbfield::Field::cmp_in_file - Compares two fields stored in a chunky file byte by byte, without decoding them
+    //
+    // ARGUMENTS:
+    //  file: &mut ChunkyFile - The file containing both fields
+    //  field_point_a: u64 - The insertion point of the first field
+    //  field_point_b: u64 - The insertion point of the second field
+    pub fn cmp_in_file(file: &mut ChunkyFile, field_point_a: u64, field_point_b: u64) -> Result<FieldCmp, Box<dyn Error>>
     {
-        // Does not work with continued chunks, to be implemented!
-        // Implement a buffer of 256 bytes in size for both the a and b fields
-        let mut buffer_a: [u8;256] = [0; 256];
-        let mut buffer_b: [u8;256] = [0; 256];
+        // Read both fields, following them across chunks if they are continued
+        let data_a = file.read_field_bytes(field_point_a)?;
+        let data_b = file.read_field_bytes(field_point_b)?;
 
-        // Skip past the header on the first field point since it is kinda useless for what we need to do...
-        file.seek(std::io::SeekFrom::Start(field_point_a + (FIELDHEADSZ as u64)))?;
-        file.read(&mut buffer_a)?; // Read the first field
+        let bytes_a = Field::split_bytes(&data_a)?;
+        let bytes_b = Field::split_bytes(&data_b)?;
 
-        // Skip past the header on the second field point since it is kinda useless for what we need to do...
-        file.seek(std::io::SeekFrom::Start(field_point_b + (FIELDHEADSZ as u64)))?;
-        file.read(&mut buffer_b)?; // Read the second field
+        // Compare the IDs, then the types, then the values, each as plain bytes. Nulls have the lowest type byte and their
+        // value is the type they would have had, so they sort the same way they do in Field::cmp()
+        let mut ordering = bytes_a.id.cmp(bytes_b.id).then(bytes_a.value_type.cmp(&bytes_b.value_type));
 
-        // Initialize the iterator...
-        let mut i: usize = 0;
+        if ordering == Ordering::Equal
+        {
+            ordering = Field::cmp_value_bytes(bytes_a.value_type, bytes_a.value, bytes_b.value)?;
+        }
 
-        // Get the ID length
-        let id_len_a = buffer_a[i];
-        let id_len_b = buffer_b[i];
+        match ordering
+        {
+            Ordering::Less => return Ok(FieldCmp::LessThan),
+            Ordering::Greater => return Ok(FieldCmp::GreaterThan),
+            Ordering::Equal => return Ok(FieldCmp::Equal),
+        }
+    }
 
-        // Compare the id lengths for a difference...
+    // dbio::dbfield::Field::cmp_value_bytes - Compares two encoded values of the same type, see cmp_in_file()
+    //
+    // Decimals keep their scale and precision after the bytes that sort, so equal values such as 0.5 and 0.50 differ in
+    // their bytes. Decimals are compared without them, and lists and documents that differ are decoded and compared in
+    // memory, since they may hold decimals.
+    //
+    // ARGUMENTS:
+    //  value_type: u8 - The type byte both values share
+    //  value_a: &[u8] - The first value, without its length
+    //  value_b: &[u8] - The second value, without its length
+    fn cmp_value_bytes(value_type: u8, value_a: &[u8], value_b: &[u8]) -> Result<Ordering, Box<dyn Error>>
+    {
+        if value_a == value_b
+        {
+            return Ok(Ordering::Equal);
+        }
 
-        if id_len_a > id_len_b
+        match value_type
         {
-            return Ok(FieldCmp::GreaterThan);
+            b'D' => return Ok(D::sortable_bytes(value_a).cmp(D::sortable_bytes(value_b))),
+            b'L' | b'O' =>
+            {
+                let a = value_from_bytes(value_type, value_a, 0)?;
+                let b = value_from_bytes(value_type, value_b, 0)?;
+
+                return Ok(a.partial_cmp(&b).unwrap_or(Ordering::Equal));
+            }
+            _ => return Ok(value_a.cmp(value_b)),
         }
+    }
+
+    // dbio::dbfield::Field::split_bytes - Splits an encoded field into its ID, type byte and value, skipping the tree header
+    //
+    // ARGUMENTS:
+    //  data: &[u8] - A slice of bytes starting with the field
+    pub fn split_bytes(data: &[u8]) -> Result<FieldBytes<'_>, DecodeError>
+    {
+        let mut i: usize = FIELDHEADSZ - 1; // Skip past the tree header
+
+        let header = take_bytes(data, i, 2)?;
+        i += 2;
+
+        let value_type_byte = header[0];
+        let id_length = header[1] as usize;
+
+        let id_data = take_bytes(data, i, id_length)?;
+        i += id_length;
 
-        if id_len_a < id_len_b
+        // Booleans are stored in their type, everything else has a length prefixed value. Booleans are reported as type 'B'
+        // with a value of 0 or 1 so false sorts before true and both sort in the same place as other types
+        let (value_type, value_data) = match value_type_byte
         {
-            return Ok(FieldCmp::LessThan);
+            b'B' =>
+            {
+                (b'B', &[1u8][..])
+            }
+            b'b' =>
+            {
+                (b'B', &[0u8][..])
+            }
+            _ =>
+            {
+                (value_type_byte, take_value_bytes(data, &mut i)?)
+            }
+        };
+
+        return Ok
+        (
+            FieldBytes
+            {
+                id: id_data,
+                value_type: value_type,
+                value: value_data,
+            }
+        );
+    }
+}
+
+// Functions!
+//
+
+
+
+// dbio::dbfield::value_to_bytes - Encodes a value without its type, for storing in a field or a list or document
+//
+// Every encoding sorts byte by byte in the same order as the values do in memory. Booleans have no value data since they
+// are stored in their type, and nulls are handled by the caller. Anything value_from_bytes() would refuse is refused
+// here too, so nothing is written that can't be read back.
+//
+// ARGUMENTS:
+//  value: &Type - The value to encode, must not be null
+//  depth: usize - How many lists and documents deep the value is
+fn value_to_bytes(value: &Type, depth: usize) -> Result<Vec<u8>, Box<dyn Error>>
+{
+    if matches!(value, Type::List(_) | Type::Doc(_)) && depth >= MAX_NESTING
+    {
+        bail!("Lists and documents can't be nested more than {} deep!", MAX_NESTING);
+    }
+
+    let value_data: Vec<u8> = match value
+    {
+        Type::S(string) =>
+        {
+            string.as_ref().unwrap().to_bytes()
         }
+        Type::I(integer) =>
+        {
+            integer.as_ref().unwrap().to_bytes()
+        }
+        Type::F(float) =>
+        {
+            float.as_ref().unwrap().to_bytes()
+        }
+        Type::D(decimal) =>
+        {
+            decimal.as_ref().unwrap().to_bytes()
+        }
+        Type::Bin(binary) =>
+        {
+            let binary = binary.as_ref().unwrap();
 
-        // Increment the iterator
-        i += 1;
-        // Set the start point...
-        let mut start_i = i;
+            if matches!(binary, Bin::Inline(_)) && binary.len() > BIN_INLINE_MAX as u64
+            {
+                bail!("Binary value of {} bytes is too big to store inline, see ChunkyFile::add_bin()!", binary.len());
+            }
 
-        // Compare the id data for a difference...
-        while (i - start_i) < (id_len_a as usize)
+            binary.to_bytes()
+        }
+        Type::T(time) =>
+        {
+            time.as_ref().unwrap().to_bytes()
+        }
+        Type::Date(date) =>
+        {
+            date.as_ref().unwrap().to_bytes()
+        }
+        Type::Ref(uuid) =>
+        {
+            uuid.as_ref().unwrap().to_bytes()
+        }
+        Type::List(elements) =>
         {
-            if buffer_a[i] > buffer_b[i]
+            // Elements back to back, each one knowing where it ends
+            let mut data = Vec::<u8>::new();
+
+            for element in elements.as_ref().unwrap()
             {
-                return Ok(FieldCmp::GreaterThan);
+                element_to_bytes(element, &mut data, depth)?;
             }
 
-            if buffer_a[i] < buffer_b[i]
+            data
+        }
+        Type::Doc(fields) =>
+        {
+            // Each field is its escaped ID followed by its value as an element, with no tree header
+            let mut data = Vec::<u8>::new();
+
+            for field in fields.as_ref().unwrap()
             {
-                return Ok(FieldCmp::LessThan);
+                if field.id.len() > 255
+                {
+                    bail!("Document field ID {} is longer than 255 bytes!", field.id);
+                }
+
+                escape_bytes(field.id.as_bytes(), &mut data);
+                element_to_bytes(&field.value, &mut data, depth)?;
             }
 
-            // Increment the iterator
-            i += 1;
+            data
         }
+        Type::B(_) =>
+        {
+            Vec::<u8>::new() // Boolean's values are stored in their type, there is no value to store
+        }
+    };
 
-        // Compare the value lengths for a difference...
-        let value_len_a = buffer_a[i];
-        let value_len_b = buffer_b[i];
+    return Ok(value_data);
+}
 
-        if value_len_a > value_len_b
+// dbio::dbfield::value_from_bytes - Decodes a value from its type byte and value data, the inverse of value_to_bytes
+//
+// ARGUMENTS:
+//  value_type_byte: u8 - The type byte of the value
+//  value_data: &[u8] - The value data
+//  depth: usize - How many lists and documents deep the value is
+fn value_from_bytes(value_type_byte: u8, value_data: &[u8], depth: usize) -> Result<Type, DecodeError>
+{
+    let invalid = DecodeError::InvalidValue { value_type: value_type_byte };
+
+    let value = match value_type_byte
+    {
+        b'S' =>
         {
-            return Ok(FieldCmp::GreaterThan);
+            match S::from_bytes(value_data)
+            {
+                Ok(string) => Type::S(Some(string)), // Set value to a string...
+                Err(_) => return Err(DecodeError::InvalidUtf8),
+            }
         }
 
-        if value_len_a < value_len_b
+        b'I' =>
         {
-            return Ok(FieldCmp::LessThan);
+            Type::I(Some(I::from_bytes(value_data).map_err(|_| invalid)?)) // Set value to an integer...
         }
 
-        // Increment the iterator
-        i += 1;
-        // Set the start point...
-        start_i = i;
+        b'F' =>
+        {
+            Type::F(Some(F::from_bytes(value_data).map_err(|_| invalid)?)) // Set value to a float...
+        }
+
+        b'D' =>
+        {
+            Type::D(Some(D::from_bytes(value_data).map_err(|_| invalid)?)) // Set value to a decimal...
+        }
+
+        b'X' =>
+        {
+            Type::Bin(Some(Bin::from_bytes(value_data).map_err(|_| invalid)?)) // Set value to binary data...
+        }
+
+        b'T' =>
+        {
+            Type::T(Some(T::from_bytes(value_data).map_err(|_| invalid)?)) // Set value to a time...
+        }
+
+        b'A' =>
+        {
+            Type::Date(Some(Date::from_bytes(value_data).map_err(|_| invalid)?)) // Set value to a date...
+        }
+
+        b'R' =>
+        {
+            Type::Ref(Some(UuidV4::from_bytes(value_data).map_err(|_| invalid)?)) // Set value to a reference...
+        }
+
+        b'L' | b'O' if depth >= MAX_NESTING =>
+        {
+            return Err(invalid);
+        }
 
-        // Compare the value data for a difference...
-        while (i - start_i) < (value_len_a as usize)
+        b'L' =>
         {
-            if buffer_a[i] > buffer_b[i]
+            let mut elements = Vec::<Type>::new();
+            let mut i: usize = 0;
+
+            while i < value_data.len()
             {
-                return Ok(FieldCmp::GreaterThan);
+                elements.push(element_from_bytes(value_type_byte, value_data, &mut i, depth)?);
             }
 
-            if buffer_a[i] < buffer_b[i]
+            Type::List(Some(elements)) // Set value to a list...
+        }
+
+        b'O' =>
+        {
+            let mut fields = Vec::<Field>::new();
+            let mut i: usize = 0;
+
+            while i < value_data.len()
             {
-                return Ok(FieldCmp::LessThan);
+                let id = match String::from_utf8(unescape_bytes(value_type_byte, value_data, &mut i)?)
+                {
+                    Ok(id) if id.len() <= 255 => id,
+                    Ok(_) => return Err(invalid),
+                    Err(_) => return Err(DecodeError::InvalidUtf8),
+                };
+
+                fields.push(Field::new(&id, element_from_bytes(value_type_byte, value_data, &mut i, depth)?));
             }
 
-            // Increment the iterator
-            i += 1;
+            Type::Doc(Some(fields)) // Set value to a document...
         }
 
+        // Booleans inside lists and documents carry their value, they only live in their type at the top of a field
+        b'B' =>
+        {
+            match value_data
+            {
+                [0] => Type::B(Some(B::new(false))),
+                [1] => Type::B(Some(B::new(true))),
+                _ => return Err(invalid),
+            }
+        }
 
-        return Ok(FieldCmp::Equal);
+        NULL_TYPE =>
+        {
+            match value_data
+            {
+                [sort_class] => match Type::null(*sort_class)
+                {
+                    Some(null) => null, // Set value to a null...
+                    None => return Err(invalid),
+                },
+                _ => return Err(invalid),
+            }
+        }
+
+        _ =>
+        {
+            return Err(DecodeError::UnknownTypeByte(value_type_byte));
+        }
+    };
+
+    return Ok(value);
+}
+
+// dbio::dbfield::element_to_bytes - Encodes a value inside a list or document, its type byte followed by its escaped value
+//
+// ARGUMENTS:
+//  value: &Type - The value to encode
+//  data: &mut Vec<u8> - Where to put the encoded value
+//  depth: usize - How many lists and documents deep the list or document is
+fn element_to_bytes(value: &Type, data: &mut Vec<u8>, depth: usize) -> Result<(), Box<dyn Error>>
+{
+    // Type bytes are the sort class so booleans sort together, which means booleans need a value
+    let value_data = match value
+    {
+        _ if value.is_null() => vec![value.sort_class()],
+        Type::B(Some(boolean)) => vec![boolean.is_true() as u8],
+        _ => value_to_bytes(value, depth + 1)?,
+    };
+
+    data.push(if value.is_null() { NULL_TYPE } else { value.sort_class() });
+    escape_bytes(&value_data, data);
+
+    return Ok(());
+}
+
+// dbio::dbfield::element_from_bytes - Decodes a value inside a list or document, the inverse of element_to_bytes
+//
+// ARGUMENTS:
+//  container_type: u8 - The type byte of the list or document, for errors
+//  data: &[u8] - The value data of the list or document
+//  i: &mut usize - Where the element starts, moved past it
+//  depth: usize - How many lists and documents deep the list or document is
+fn element_from_bytes(container_type: u8, data: &[u8], i: &mut usize, depth: usize) -> Result<Type, DecodeError>
+{
+    let value_type_byte = match data.get(*i)
+    {
+        Some(value_type_byte) => *value_type_byte,
+        None => return Err(DecodeError::InvalidValue { value_type: container_type }),
+    };
+
+    *i += 1;
+
+    let value_data = unescape_bytes(container_type, data, i)?;
+
+    return value_from_bytes(value_type_byte, &value_data, depth + 1);
+}
+
+// dbio::dbfield::escape_bytes - Writes bytes so they can be followed by more data without changing how they sort
+//
+// Every 0x00 becomes 0x00 0xFF and the bytes end with 0x00 0x01, so shorter byte strings still sort before longer ones
+// that start the same way.
+//
+// ARGUMENTS:
+//  bytes: &[u8] - The bytes to escape
+//  data: &mut Vec<u8> - Where to put the escaped bytes
+fn escape_bytes(bytes: &[u8], data: &mut Vec<u8>)
+{
+    for byte in bytes
+    {
+        data.push(*byte);
+
+        if *byte == 0x00
+        {
+            data.push(0xFF);
+        }
+    }
+
+    data.extend_from_slice(&[0x00, 0x01]);
+}
+
+// dbio::dbfield::unescape_bytes - Reads bytes written by escape_bytes
+//
+// ARGUMENTS:
+//  container_type: u8 - The type byte of the list or document, for errors
+//  data: &[u8] - The data to read from
+//  i: &mut usize - Where the escaped bytes start, moved past them
+fn unescape_bytes(container_type: u8, data: &[u8], i: &mut usize) -> Result<Vec<u8>, DecodeError>
+{
+    let mut bytes = Vec::<u8>::new();
+
+    loop
+    {
+        match (data.get(*i), data.get(*i + 1))
+        {
+            (Some(0x00), Some(0x01)) =>
+            {
+                *i += 2;
+
+                return Ok(bytes);
+            }
+            (Some(0x00), Some(0xFF)) =>
+            {
+                bytes.push(0x00);
+                *i += 2;
+            }
+            (Some(0x00), _) | (None, _) =>
+            {
+                return Err(DecodeError::InvalidValue { value_type: container_type });
+            }
+            (Some(byte), _) =>
+            {
+                bytes.push(*byte);
+                *i += 1;
+            }
+        }
+    }
+}
+
+// dbio::dbfield::length_to_bytes - Encodes a length as a variable length integer, 7 bits per byte, most significant group first.
+// Every byte but the last has its top bit set.
+//
+// ARGUMENTS:
+//  length: usize - The length to encode
+pub fn length_to_bytes(length: usize) -> Vec<u8>
+{
+    let mut data = vec![(length & 0x7F) as u8];
+    let mut remaining = length >> 7;
+
+    while remaining > 0
+    {
+        data.insert(0, ((remaining & 0x7F) as u8) | 0x80);
+        remaining >>= 7;
     }
+
+    return data;
+}
+
+// dbio::dbfield::length_from_bytes - Decodes a variable length integer, returning the length and the number of bytes it took up
+//
+// ARGUMENTS:
+//  data: &[u8] - A slice of bytes starting with the length
+pub fn length_from_bytes(data: &[u8]) -> Result<(usize, usize), DecodeError>
+{
+    let mut length: usize = 0;
+    let mut i: usize = 0;
+
+    loop
+    {
+        if i >= data.len()
+        {
+            return Err(DecodeError::Truncated { needed: i + 1, have: data.len() });
+        }
+
+        // Anything that would overflow a usize can't be a real length
+        if length > (usize::MAX >> 7)
+        {
+            return Err(DecodeError::LengthOverflow);
+        }
+
+        length = (length << 7) | ((data[i] & 0x7F) as usize);
+        i += 1;
+
+        if (data[i - 1] & 0x80) == 0
+        {
+            return Ok((length, i));
+        }
+    }
+}
+
+// dbio::dbfield::take_bytes - Gets length bytes of data starting at i, without running past the end of the data
+//
+// ARGUMENTS:
+//  data: &[u8] - The data to take the bytes from
+//  i: usize - Where to start taking bytes
+//  length: usize - How many bytes to take
+fn take_bytes(data: &[u8], i: usize, length: usize) -> Result<&[u8], DecodeError>
+{
+    let end = match i.checked_add(length)
+    {
+        Some(end) => end,
+        None => return Err(DecodeError::LengthOverflow),
+    };
+
+    if end > data.len()
+    {
+        return Err(DecodeError::Truncated { needed: end, have: data.len() });
+    }
+
+    return Ok(&data[i..end]);
+}
+
+// dbio::dbfield::take_value_bytes - Gets a length prefixed value starting at i, moving i past it
+//
+// ARGUMENTS:
+//  data: &[u8] - The data to take the value from
+//  i: &mut usize - Where the length of the value starts, moved to the end of the value
+fn take_value_bytes<'a>(data: &'a [u8], i: &mut usize) -> Result<&'a [u8], DecodeError>
+{
+    let (value_length, length_len) = match length_from_bytes(&data[*i..])
+    {
+        Ok(length) => length,
+        // The length's own offsets start at i, move them to the start of the field
+        Err(DecodeError::Truncated { needed, .. }) => return Err(DecodeError::Truncated { needed: *i + needed, have: data.len() }),
+        Err(e) => return Err(e),
+    };
+
+    let value_data = take_bytes(data, *i + length_len, value_length)?;
+    *i += length_len + value_length;
+
+    return Ok(value_data);
 }
 
 // Tests!
@@ -351,46 +894,447 @@ impl Field
 #[cfg(test)]
 mod test
 {
-    use std::fs::OpenOptions;
-use std::fs::remove_file;
-use std::io::Write;
+    use std::fs::remove_file;
     use std::mem::drop;
+    use crate::dbio::dbchunk::*;
     use super::*;
 
     // Test data for all string field tests. Consists of an id equal to "Hello" and a value equal to "World"
     // The total size should be equal to 13 bytes.
     // The first byte should be a 'S' to denote the type, the second byte should be the length of the string "Hello"(5). After those two bytes, the string "Hello" should be stored.
     // The byte following the id string should be the length of the string "World"(5). After that, the string "World" should be stored.
-    const test_string_data:[u8; 13] = [b'S', 5, b'H', b'e', b'l', b'l', b'o', 5, b'W', b'o', b'r', b'l', b'd'];
-    const test_string_data_invalid:[u8; 12] = [b'S', 5, b'H', b'e', b'l', b'l', b'o', 5, b'W', b'o', b'r', b'l'];
+    const TEST_STRING_DATA:[u8; 13] = [b'S', 5, b'H', b'e', b'l', b'l', b'o', 5, b'W', b'o', b'r', b'l', b'd'];
+    const TEST_STRING_DATA_INVALID:[u8; 12] = [b'S', 5, b'H', b'e', b'l', b'l', b'o', 5, b'W', b'o', b'r', b'l'];
 
-    const test_filename: &str = "test.foobar";
+    const TEST_FILENAME: &str = "test.foobar";
 
     #[test]
     fn test_in_file_cmp_equal()
     {
-        let mut file = OpenOptions::new()
-            .write(true)
-            .read(true)
-            .create(true)
-            .open(test_filename)
-            .unwrap();
+        let _ = remove_file(TEST_FILENAME);
+        let mut file = ChunkyFile::create(TEST_FILENAME, DbHeadChunk::new("test", "tester")).unwrap();
 
         let field_a = Field::new("Hello", Type::S(Some(S::new("World"))));
         let field_b = field_a.clone();
 
-        let insertion_point_a = file.seek(std::io::SeekFrom::End(0)).unwrap();
-        file.write(&field_a.to_bytes().unwrap()).unwrap();
-        
-        let insertion_point_b = file.seek(std::io::SeekFrom::End(0)).unwrap();
-        file.write(&field_b.to_bytes().unwrap()).unwrap();
+        let insertion_points = file.add_entry_chunk(EntryChunk::from_fields(vec![field_a, field_b])).unwrap();
 
-        let cmp = Field::cmp_in_file(&mut file, insertion_point_a, insertion_point_b).unwrap();
+        let cmp = Field::cmp_in_file(&mut file, insertion_points[0], insertion_points[1]).unwrap();
 
         assert_eq!(cmp, FieldCmp::Equal);
 
         drop(file);
 
-        remove_file(test_filename).unwrap();
+        remove_file(TEST_FILENAME).unwrap();
+    }
+
+    // dbio::dbfield::test::test_length_round_trip - Tests the variable length integers used for value lengths
+    //
+    #[test]
+    fn test_length_round_trip()
+    {
+        assert_eq!(length_to_bytes(5), vec![5]);
+        assert_eq!(length_to_bytes(300), vec![0x82, 0x2C]);
+
+        for length in [0, 127, 128, 255, 256, 16383, 16384, 1 << 32]
+        {
+            let data = length_to_bytes(length);
+            assert_eq!(length_from_bytes(&data).unwrap(), (length, data.len()));
+        }
+
+        assert!(length_from_bytes(&[0x80]).is_err());
+    }
+
+    // dbio::dbfield::test::test_long_string_round_trip - Tests that strings longer than 255 bytes survive to_bytes/from_bytes
+    //
+    #[test]
+    fn test_long_string_round_trip()
+    {
+        let field = Field::new("numbers", Type::S(Some(S::new(&"0123456789".repeat(100)))));
+
+        let data = field.to_bytes().unwrap();
+
+        assert_eq!(Field::from_bytes_with_len(&data).unwrap(), (field, data.len()));
+    }
+
+    // dbio::dbfield::test::test_in_file_cmp_long - Tests comparing long strings in a file, continued over several chunks
+    //
+    #[test]
+    fn test_in_file_cmp_long()
+    {
+        let file_name = "test_in_file_cmp_long.foobar";
+        let _ = remove_file(file_name);
+        let mut file = ChunkyFile::create(file_name, DbHeadChunk::new("test", "tester")).unwrap();
+
+        let long = "a".repeat(600);
+        let fields = vec!
+        [
+            Field::new("id", Type::S(Some(S::new(&(long.clone() + "b"))))),
+            Field::new("id", Type::S(Some(S::new(&(long.clone() + "a"))))),
+            Field::new("id", Type::S(Some(S::new(&long)))),
+            Field::new("id", Type::S(Some(S::new("b")))),
+        ];
+
+        let insertion_points = file.add_entry_chunk(EntryChunk::from_fields(fields)).unwrap();
+
+        assert_eq!(Field::cmp_in_file(&mut file, insertion_points[0], insertion_points[1]).unwrap(), FieldCmp::GreaterThan);
+        assert_eq!(Field::cmp_in_file(&mut file, insertion_points[2], insertion_points[1]).unwrap(), FieldCmp::LessThan);
+        assert_eq!(Field::cmp_in_file(&mut file, insertion_points[3], insertion_points[0]).unwrap(), FieldCmp::GreaterThan);
+        assert_eq!(Field::cmp_in_file(&mut file, insertion_points[1], insertion_points[1]).unwrap(), FieldCmp::Equal);
+
+        drop(file);
+
+        remove_file(file_name).unwrap();
+    }
+
+    // dbio::dbfield::test::test_in_file_cmp_integers - Tests that integers of any size and sign compare correctly in a file
+    //
+    #[test]
+    fn test_in_file_cmp_integers()
+    {
+        let file_name = "test_in_file_cmp_integers.foobar";
+        let _ = remove_file(file_name);
+        let mut file = ChunkyFile::create(file_name, DbHeadChunk::new("test", "tester")).unwrap();
+
+        let values = ["-100000000000000000000000", "-300", "-1", "0", "2", "256", "100000000000000000000000"];
+        let mut fields = Vec::<Field>::new();
+
+        for value in values
+        {
+            fields.push(Field::new("n", Type::I(Some(value.parse().unwrap()))));
+        }
+
+        let insertion_points = file.add_entry_chunk(EntryChunk::from_fields(fields.clone())).unwrap();
+
+        for i in 1..insertion_points.len()
+        {
+            assert_eq!(Field::cmp_in_file(&mut file, insertion_points[i - 1], insertion_points[i]).unwrap(), FieldCmp::LessThan);
+            assert_eq!(fields[i - 1].cmp(&fields[i]).unwrap(), FieldCmp::LessThan);
+            assert_eq!(file.read_field(insertion_points[i]).unwrap(), fields[i]);
+        }
+
+        drop(file);
+
+        remove_file(file_name).unwrap();
+    }
+
+    // dbio::dbfield::test::test_in_file_cmp_mixed - Tests that booleans, decimals, floats and mixed types compare the same way in a file as in memory
+    //
+    #[test]
+    fn test_in_file_cmp_mixed()
+    {
+        let file_name = "test_in_file_cmp_mixed.foobar";
+        let _ = remove_file(file_name);
+        let mut file = ChunkyFile::create(file_name, DbHeadChunk::new("test", "tester")).unwrap();
+
+        let fields = vec!
+        [
+            Field::new("n", Type::B(Some(B::new(false)))),
+            Field::new("n", Type::B(Some(B::new(true)))),
+            Field::new("n", Type::D(Some(D::parse("-12.5", 6, 3).unwrap()))),
+            Field::new("n", Type::D(Some(D::parse("-12.4", 6, 1).unwrap()))),
+            Field::new("n", Type::D(Some(D::parse("0", 6, 1).unwrap()))),
+            Field::new("n", Type::D(Some(D::parse("0.5", 6, 1).unwrap()))),
+            Field::new("n", Type::D(Some(D::parse("0.55", 6, 2).unwrap()))),
+            Field::new("n", Type::D(Some(D::parse("3.25", 6, 2).unwrap()))),
+            Field::new("n", Type::F(Some(F::new(-1000.0)))),
+            Field::new("n", Type::F(Some(F::new(-0.5)))),
+            Field::new("n", Type::F(Some(F::new(0.0)))),
+            Field::new("n", Type::F(Some(F::new(2.75)))),
+            Field::new("n", Type::I(Some(I::new(-1)))),
+            Field::new("n", Type::S(Some(S::new("a")))),
+        ];
+
+        let insertion_points = file.add_entry_chunk(EntryChunk::from_fields(fields.clone())).unwrap();
+
+        for i in 1..insertion_points.len()
+        {
+            assert_eq!(Field::cmp_in_file(&mut file, insertion_points[i - 1], insertion_points[i]).unwrap(), FieldCmp::LessThan);
+            assert_eq!(fields[i - 1].cmp(&fields[i]).unwrap(), FieldCmp::LessThan);
+            assert_eq!(file.read_field(insertion_points[i]).unwrap(), fields[i]);
+        }
+
+        // Decimals are equal whatever their scale, on their own and inside lists and documents
+        let half = |precision: u32, scale: u32| Type::D(Some(D::parse("0.5", precision, scale).unwrap()));
+        let equal = vec!
+        [
+            (Field::new("n", half(6, 1)), Field::new("n", half(6, 2))),
+            (Field::new("n", half(6, 1)), Field::new("n", half(2, 1))),
+            (Field::new("l", Type::List(Some(vec![half(6, 1)]))), Field::new("l", Type::List(Some(vec![half(6, 2)])))),
+            (Field::new("o", Type::Doc(Some(vec![Field::new("n", half(6, 1))]))), Field::new("o", Type::Doc(Some(vec![Field::new("n", half(6, 2))])))),
+        ];
+
+        for (field_a, field_b) in equal
+        {
+            let insertion_points = file.add_entry_chunk(EntryChunk::from_fields(vec![field_a.clone(), field_b.clone()])).unwrap();
+            assert_eq!(Field::cmp_in_file(&mut file, insertion_points[0], insertion_points[1]).unwrap(), FieldCmp::Equal);
+            assert_eq!(field_a.cmp(&field_b).unwrap(), FieldCmp::Equal);
+        }
+
+        let list = |value: &str| Type::List(Some(vec![Type::D(Some(D::parse(value, 6, 2).unwrap()))]));
+        let insertion_points = file.add_entry_chunk(EntryChunk::from_fields(vec![Field::new("l", list("0.5")), Field::new("l", list("0.55"))])).unwrap();
+        assert_eq!(Field::cmp_in_file(&mut file, insertion_points[0], insertion_points[1]).unwrap(), FieldCmp::LessThan);
+
+        drop(file);
+
+        remove_file(file_name).unwrap();
+    }
+
+    // dbio::dbfield::test::test_in_file_cmp_nulls - Tests that nulls round trip and sort first, in memory and in a file
+    //
+    #[test]
+    fn test_in_file_cmp_nulls()
+    {
+        let file_name = "test_in_file_cmp_nulls.foobar";
+        let _ = remove_file(file_name);
+        let mut file = ChunkyFile::create(file_name, DbHeadChunk::new("test", "tester")).unwrap();
+
+        let fields = vec!
+        [
+            Field::new("a", Type::B(None)),
+            Field::new("a", Type::I(None)),
+            Field::new("a", Type::S(None)),
+            Field::new("a", Type::B(Some(B::new(false)))),
+            Field::new("a", Type::I(Some(I::new(-5)))),
+            Field::new("a", Type::S(Some(S::new("")))),
+            Field::new("b", Type::T(None)),
+            Field::new("b", Type::Bin(Some(Bin::new(b"")))),
+        ];
+
+        let insertion_points = file.add_entry_chunk(EntryChunk::from_fields(fields.clone())).unwrap();
+
+        for i in 1..insertion_points.len()
+        {
+            assert_eq!(Field::cmp_in_file(&mut file, insertion_points[i - 1], insertion_points[i]).unwrap(), FieldCmp::LessThan);
+            assert_eq!(fields[i - 1].cmp(&fields[i]).unwrap(), FieldCmp::LessThan);
+            assert_eq!(file.read_field(insertion_points[i]).unwrap(), fields[i]);
+        }
+
+        // Nulls of the same type are equal as far as ordering goes
+        let more_points = file.add_entry_chunk(EntryChunk::from_fields(vec![Field::new("a", Type::I(None))])).unwrap();
+        assert_eq!(Field::cmp_in_file(&mut file, insertion_points[1], more_points[0]).unwrap(), FieldCmp::Equal);
+        assert_eq!(fields[1].cmp(&Field::new("a", Type::I(None))).unwrap(), FieldCmp::Equal);
+
+        drop(file);
+
+        remove_file(file_name).unwrap();
+    }
+
+    // dbio::dbfield::test::test_from_bytes_null_invalid - Tests that nulls of unknown types are refused
+    //
+    #[test]
+    fn test_from_bytes_null_invalid()
+    {
+        let mut data = Field::new("id", Type::S(None)).to_bytes().unwrap();
+        let last = data.len() - 1;
+        data[last] = b'?';
+
+        assert_eq!(Field::from_bytes(&data), Err(DecodeError::InvalidValue { value_type: NULL_TYPE }));
+    }
+
+    // dbio::dbfield::test::test_nested_round_trip - Tests that lists and documents survive being converted to bytes and back
+    //
+    #[test]
+    fn test_nested_round_trip()
+    {
+        let address = Type::Doc(Some(vec!
+        [
+            Field::new("city", Type::S(Some(S::new("Par\0is")))),
+            Field::new("zip", Type::S(None)),
+            Field::new("geo", Type::List(Some(vec![Type::F(Some(F::new(48.85))), Type::F(Some(F::new(2.35)))]))),
+        ]));
+
+        let fields = vec!
+        [
+            Field::new("address", address),
+            Field::new("tags", Type::List(Some(vec![Type::S(Some(S::new("a"))), Type::B(Some(B::new(false))), Type::I(Some(I::new(0))), Type::List(Some(vec![])), Type::Doc(None)]))),
+            Field::new("author", Type::Ref(Some(UuidV4::new()))),
+            Field::new("editors", Type::List(Some(vec![Type::Ref(Some(UuidV4::new())), Type::Ref(None)]))),
+            Field::new("empty", Type::List(Some(vec![]))),
+            Field::new("nothing", Type::Doc(None)),
+        ];
+
+        for field in fields
+        {
+            assert_eq!(Field::from_bytes(&field.to_bytes().unwrap()).unwrap(), field);
+        }
+    }
+
+    // dbio::dbfield::test::test_in_file_cmp_nested - Tests that lists and documents compare the same way in a file as in memory
+    //
+    #[test]
+    fn test_in_file_cmp_nested()
+    {
+        let file_name = "test_in_file_cmp_nested.foobar";
+        let _ = remove_file(file_name);
+        let mut file = ChunkyFile::create(file_name, DbHeadChunk::new("test", "tester")).unwrap();
+
+        let s = |string: &str| Type::S(Some(S::new(string)));
+        let doc = |id: &str, value: Type| Type::Doc(Some(vec![Field::new(id, value)]));
+
+        let fields = vec!
+        [
+            Field::new("n", Type::List(Some(vec![]))),
+            Field::new("n", Type::List(Some(vec![Type::S(None)]))),
+            Field::new("n", Type::List(Some(vec![Type::B(Some(B::new(false)))]))),
+            Field::new("n", Type::List(Some(vec![Type::B(Some(B::new(true)))]))),
+            Field::new("n", Type::List(Some(vec![s("a")]))),
+            Field::new("n", Type::List(Some(vec![s("a"), s("")]))),
+            Field::new("n", Type::List(Some(vec![s("a\0")]))),
+            Field::new("n", Type::List(Some(vec![s("ab")]))),
+            Field::new("n", doc("a", s("z"))),
+            Field::new("n", doc("a\0", s("a"))),
+            Field::new("n", doc("b", Type::I(Some(I::new(-1))))),
+            Field::new("n", doc("b", Type::I(Some(I::new(1))))),
+            Field::new("n", doc("b", Type::List(Some(vec![s("x")])))),
+        ];
+
+        let insertion_points = file.add_entry_chunk(EntryChunk::from_fields(fields.clone())).unwrap();
+
+        for i in 1..insertion_points.len()
+        {
+            assert_eq!(Field::cmp_in_file(&mut file, insertion_points[i - 1], insertion_points[i]).unwrap(), FieldCmp::LessThan, "{:?} < {:?}", fields[i - 1], fields[i]);
+            assert_eq!(fields[i - 1].cmp(&fields[i]).unwrap(), FieldCmp::LessThan, "{:?} < {:?}", fields[i - 1], fields[i]);
+        }
+
+        drop(file);
+
+        remove_file(file_name).unwrap();
     }
-}
\ No newline at end of file
+
+    // dbio::dbfield::test::test_from_bytes_nested_invalid - Tests that broken and overly deep lists are refused, both ways
+    //
+    #[test]
+    fn test_from_bytes_nested_invalid()
+    {
+        // An element that never ends
+        let mut data = Field::new("l", Type::List(Some(vec![Type::S(Some(S::new("abc")))]))).to_bytes().unwrap();
+        let last = data.len() - 1;
+        data[last] = 0xFF;
+        assert_eq!(Field::from_bytes(&data), Err(DecodeError::InvalidValue { value_type: b'L' }));
+
+        let mut deep = Type::List(Some(vec![]));
+
+        for _ in 0..MAX_NESTING
+        {
+            deep = Type::List(Some(vec![deep]));
+        }
+
+        assert!(Field::new("l", deep.clone()).to_bytes().is_err());
+
+        // The same list written by hand is refused when it is read, each level is the list type byte and the escaped level inside
+        let mut value_data = Vec::<u8>::new();
+
+        for _ in 0..MAX_NESTING
+        {
+            let mut level = vec![deep.sort_class()];
+            escape_bytes(&value_data, &mut level);
+            value_data = level;
+        }
+
+        let mut data = with_tree_header(&[b'L', 1, b'l']);
+        data.extend_from_slice(&length_to_bytes(value_data.len()));
+        data.extend_from_slice(&value_data);
+        assert_eq!(Field::from_bytes(&data), Err(DecodeError::InvalidValue { value_type: b'L' }));
+
+        // One level less is fine both ways
+        let Type::List(Some(elements)) = deep else { panic!("Test list isn't a list, you shouldn't see this!") };
+        let shallower = Field::new("l", elements[0].clone());
+        assert_eq!(Field::from_bytes(&shallower.to_bytes().unwrap()).unwrap(), shallower);
+
+        // Document field IDs have the same limit as field IDs
+        let long_id = Type::Doc(Some(vec![Field::new(&"x".repeat(256), Type::B(Some(B::new(true))))]));
+        assert!(Field::new("d", long_id).to_bytes().is_err());
+        assert!(Field::new(&"x".repeat(256), Type::B(Some(B::new(true)))).to_bytes().is_err());
+        assert!(Field::new(&"x".repeat(255), Type::B(Some(B::new(true)))).to_bytes().is_ok());
+    }
+
+    // dbio::dbfield::test::with_tree_header - Puts an empty tree header in front of some field data
+    //
+    fn with_tree_header(data: &[u8]) -> Vec<u8>
+    {
+        let mut field_data = vec![0; FIELDHEADSZ - 1];
+        field_data.extend_from_slice(data);
+
+        return field_data;
+    }
+
+    // dbio::dbfield::test::test_from_bytes_string - Tests decoding the test string data
+    //
+    #[test]
+    fn test_from_bytes_string()
+    {
+        let field = Field::from_bytes(&with_tree_header(&TEST_STRING_DATA)).unwrap();
+
+        assert_eq!(field, Field::new("Hello", Type::S(Some(S::new("World")))));
+    }
+
+    // dbio::dbfield::test::test_from_bytes_truncated - Tests that decoding cut off data reports how much is missing
+    //
+    #[test]
+    fn test_from_bytes_truncated()
+    {
+        let data = with_tree_header(&TEST_STRING_DATA_INVALID);
+
+        assert_eq!(Field::from_bytes(&data), Err(DecodeError::Truncated { needed: data.len() + 1, have: data.len() }));
+        assert_eq!(Field::from_bytes(&data[..5]), Err(DecodeError::Truncated { needed: FIELDHEADSZ + 1, have: 5 }));
+    }
+
+    // dbio::dbfield::test::test_from_bytes_invalid - Tests the errors for bad type bytes and bad UTF-8
+    //
+    #[test]
+    fn test_from_bytes_invalid()
+    {
+        let mut data = with_tree_header(&TEST_STRING_DATA);
+        data[FIELDHEADSZ - 1] = b'?';
+        assert_eq!(Field::from_bytes(&data), Err(DecodeError::UnknownTypeByte(b'?')));
+
+        let mut data = with_tree_header(&TEST_STRING_DATA);
+        data[FIELDHEADSZ + 1] = 0xFF;
+        assert_eq!(Field::from_bytes(&data), Err(DecodeError::InvalidUtf8));
+
+        let data = with_tree_header(&[b'I', 1, b'x', 3, 0, 0, 0]);
+        assert_eq!(Field::from_bytes(&data), Err(DecodeError::InvalidValue { value_type: b'I' }));
+    }
+
+    // dbio::dbfield::test::test_from_bytes_garbage - Runs every prefix of some fields and a pile of pseudo-random bytes through the decoder, none of it may panic
+    //
+    #[test]
+    fn test_from_bytes_garbage()
+    {
+        let fields = vec!
+        [
+            Field::new("id", Type::S(Some(S::new(&"long".repeat(100))))),
+            Field::new("number", Type::I(Some(I::new(-42)))),
+            Field::new("flag", Type::B(Some(B::new(false)))),
+        ];
+
+        for field in fields
+        {
+            let data = field.to_bytes().unwrap();
+
+            for length in 0..data.len()
+            {
+                assert!(matches!(Field::from_bytes(&data[..length]), Err(DecodeError::Truncated { .. })));
+            }
+        }
+
+        // A small xorshift generator, the exact bytes don't matter as long as they are all over the place
+        let mut state: u64 = 0x2545F4914F6CDD1D;
+        let mut data = Vec::<u8>::new();
+
+        for _ in 0..10000
+        {
+            state ^= state << 13;
+            state ^= state >> 7;
+            state ^= state << 17;
+
+            data.push(state as u8);
+
+            let start = (state as usize >> 8) % data.len();
+            let _ = Field::from_bytes(&data[start..]);
+            let _ = Field::from_stream(&data[start..]);
+            let _ = Field::split_bytes(&data[start..]);
+        }
+    }
+}
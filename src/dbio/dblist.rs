@@ -1,23 +1,24 @@
 
-use crate::dbio::dbtree::LazyAVL;
+use crate::dbio::dbtree::{LazyAVL, LAZY_AVL_CONST};
 use std::error::Error;
+use std::ops::{Bound, RangeBounds};
+use std::rc::Rc;
+use std::cell::RefCell;
 use simple_error::*;
-use crate::dbio::dbstruct::Structure;
-use crate::dbio::dbstruct::Requirement;
+use crate::dbio::dbstruct::{Migration, Structure, StructureError};
 use crate::dbio::dbfield::Field;
 use crate::dbio::dbuuid::UuidV4;
 use crate::dbio::dbchunk::*;
-use crate::apetypes::S;
-use crate::apetypes::Type;
-use apebdlm::*;
-
+use crate::apetypes::{Bin, BIN_INLINE_MAX, T, Type};
 
+const LIST_JOURNAL_CAPACITY: usize = 1024; // Room for the writes of a tree change, more than a few dozen and a bigger journal is added
 
 #[derive(Debug, Clone, PartialEq)]
 pub struct Entry
 {
     pub uuid: UuidV4,
     pub fields: Vec<Field>,
+    pub modified: T, // When the entry was last modified
 }
 
 impl Entry
@@ -31,138 +32,2077 @@ impl Entry
             {
                 uuid: uuid,
                 fields: fields,
+                modified: T::now(),
             }
         );
     }
 
+    // dbio::dblist::Entry::with_structure - Build an entry for a structure, filling in defaults and checking it meets the structure
+    //
+    // ARGUMENTS:
+    //  uuid: UuidV4 - The UUID of the entry
+    //  fields: Vec<Field> - The fields of the entry, defaulted fields may be left out
+    //  structure: &Structure - The structure the entry has to meet
+    pub fn with_structure(uuid: UuidV4, mut fields: Vec<Field>, structure: &Structure) -> Result<Entry, Box<dyn Error>>
+    {
+        structure.fill_defaults(&mut fields);
+        structure.check(&fields)?;
+
+        return Entry::new(uuid, fields);
+    }
+
     pub fn get_field(&self, field_id: &str) -> Option<&Field>
     {
         for field in &self.fields
         {
             if field.id == field_id
             {
-                return Some(&field);
+                return Some(field);
             }
         }
 
         return None;
     }
+
+    // dbio::dblist::Entry::get_path - Get a value nested inside documents and lists, such as "address.city" or "tags.0"
+    //
+    // Each part of the path after the first is a field ID for documents, or an index for lists.
+    //
+    // ARGUMENTS:
+    //  path: &str - The path of the value, separated by dots
+    pub fn get_path(&self, path: &str) -> Option<&Type>
+    {
+        let mut parts = path.split('.');
+        let mut value = &self.get_field(parts.next()?)?.value;
+
+        for part in parts
+        {
+            value = match value
+            {
+                Type::Doc(Some(fields)) => &fields.iter().find(|field| field.id == part)?.value,
+                Type::List(Some(elements)) => elements.get(part.parse::<usize>().ok()?)?,
+                _ => return None,
+            };
+        }
+
+        return Some(value);
+    }
+
+    // dbio::dblist::Entry::from_chunk - Rebuild an entry from an entry chunk read back from a file
+    //
+    // ARGUMENTS:
+    //  chunk: EntryChunk - The entry chunk
+    pub fn from_chunk(chunk: EntryChunk) -> Entry
+    {
+        return Entry
+        {
+            uuid: chunk.uuid,
+            // Drop the tree pointers, they belong to the file and not the entry
+            fields: chunk.fields.into_iter().map(|field| Field::new(&field.id, field.value)).collect(),
+            modified: chunk.modified,
+        };
+    }
+
+    // dbio::dblist::Entry::resolve - Load the entry a reference points to, or None if the reference is null
+    //
+    // ARGUMENTS:
+    //  path: &str - The path of the reference, see get_path()
+    //  list: &mut List - The list the referenced entry is in
+    pub fn resolve(&self, path: &str, list: &mut List) -> Result<Option<Entry>, Box<dyn Error>>
+    {
+        match self.get_path(path)
+        {
+            Some(Type::Ref(Some(uuid))) =>
+            {
+                match list.get_entry(uuid)?
+                {
+                    Some(entry) => return Ok(Some(entry)),
+                    None => bail!("{} references {}, which isn't in the list!", path, uuid),
+                }
+            }
+            Some(Type::Ref(None)) => return Ok(None),
+            Some(_) => bail!("{} is not a reference!", path),
+            None => bail!("Entry has no {}!", path),
+        }
+    }
 }
 
+// dbio::dblist::Index - The tree of every value of one field in a list, see List::create_index()
+//
+struct Index
+{
+    field_id: String,
+    tree: LazyAVL,
+    index_chunk: u64, // The position of the index chunk, which keeps the tree head and state
+}
+
+// dbio::dblist::List - A list of entries sharing a structure, indexed by their UUIDs and any fields given an index
+//
 pub struct List
 {
     pub structure: Structure,
-    pub tree: LazyAVL,
+    pub uuid_tree: Rc<RefCell<LazyAVL>>, // Tree of entry UUIDs, shared with lists that reference this one
     pub db_file: ChunkyFile,
-    pub entry_count: u64
+    pub entry_count: u64,
+    list_chunk: u64, // The position of the list chunk, which keeps the UUID tree head and entry count
+    indexes: Vec<Index>, // One tree per indexed field, unique fields always have one
+    targets: Vec<(String, Rc<RefCell<LazyAVL>>)>, // The UUID trees of linked lists, by structure ID
+    migrations: Vec<Migration>, // Migrations from older versions of the structure, sorted by the version they migrate from
+    outdated: Option<bool>, // Whether entries written under older versions may be left, None until it is looked up, see has_outdated()
 }
 
 impl List
 {
-    pub fn new(db_file: ChunkyFile, structure: Structure) -> Result<Self, Box<dyn Error>>
+    // dbio::dblist::List::new - Create a new list, storing its structure in the file so it can be opened again
+    //
+    // ARGUMENTS:
+    //  db_file: ChunkyFile - The file the list lives in
+    //  structure: Structure - The structure of the list, its ID names the list and must not be taken
+    //  laze: u8 - How far the UUID tree may lean before it is rebalanced, see LazyAVL::max_balance()
+    pub fn new(mut db_file: ChunkyFile, structure: Structure, laze: u8) -> Result<Self, Box<dyn Error>>
     {
-        let tree = LazyAVL::new(db_file.file.try_clone()?, 0, 0);
+        if db_file.schemas()?.iter().any(|schema| schema.id() == structure.id())
+        {
+            bail!("List {} already exists!", structure.id());
+        }
 
-        return Ok
+        if laze > LAZY_AVL_CONST::LAZE_MAX
+        {
+            bail!("List {} can't have a laze of {}, the most is {}!", structure.id(), laze, LAZY_AVL_CONST::LAZE_MAX);
+        }
+
+        db_file.add_schema_chunk(&structure)?;
+        let list_chunk = List::add_list_chunk(&mut db_file, structure.id(), laze)?;
+
+        return List::with_structure(db_file, structure, list_chunk, &[]);
+    }
+
+    // dbio::dblist::List::open - Open a list stored in the file, rebuilding it with the latest version of its structure
+    //
+    // Custom constraints have to be bound again through structure.bind(), until then they refuse every value. Migrations
+    // have to be added again too.
+    //
+    // ARGUMENTS:
+    //  db_file: ChunkyFile - The file the list lives in
+    //  list_id: &str - The ID of the structure of the list
+    pub fn open(mut db_file: ChunkyFile, list_id: &str) -> Result<Self, Box<dyn Error>>
+    {
+        let structure = match db_file.schemas()?.into_iter().rfind(|schema| schema.id() == list_id)
+        {
+            Some(structure) => structure,
+            None => bail!("List {} does not exist!", list_id),
+        };
+
+        let list_chunks = db_file.list_chunks()?;
+        let list_chunk = List::find_list_chunk(&mut db_file, &list_chunks, list_id)?;
+        let index_chunks = db_file.index_chunks()?;
+
+        return List::with_structure(db_file, structure, list_chunk, &index_chunks);
+    }
+
+    // dbio::dblist::List::open_all - Open every list stored in the file, in the order they were created
+    //
+    // ARGUMENTS:
+    //  db_file: &mut ChunkyFile - The file the lists live in, each list gets its own handle
+    pub fn open_all(db_file: &mut ChunkyFile) -> Result<Vec<Self>, Box<dyn Error>>
+    {
+        let mut structures = Vec::<Structure>::new();
+
+        // Later versions of a structure replace the earlier ones, but the list keeps its place
+        for structure in db_file.schemas()?
+        {
+            match structures.iter_mut().find(|earlier| earlier.id() == structure.id())
+            {
+                Some(earlier) => *earlier = structure,
+                None => structures.push(structure),
+            }
+        }
+
+        let list_chunks = db_file.list_chunks()?;
+        let index_chunks = db_file.index_chunks()?;
+        let mut lists = Vec::<List>::new();
+
+        for structure in structures
+        {
+            let list_chunk = List::find_list_chunk(db_file, &list_chunks, structure.id())?;
+            lists.push(List::with_structure(db_file.try_clone()?, structure, list_chunk, &index_chunks)?);
+        }
+
+        return Ok(lists);
+    }
+
+    // dbio::dblist::List::add_list_chunk - Add a list chunk for an empty list, along with the journal its trees share
+    //
+    // ARGUMENTS:
+    //  db_file: &mut ChunkyFile - The file the list lives in
+    //  list_id: &str - The ID of the list
+    //  laze: u8 - The laze of the UUID tree
+    fn add_list_chunk(db_file: &mut ChunkyFile, list_id: &str, laze: u8) -> Result<(u64, ListChunk), Box<dyn Error>>
+    {
+        let journal = db_file.add_journal(LIST_JOURNAL_CAPACITY)?;
+        let list_chunk = ListChunk::new(list_id, journal, laze);
+
+        return Ok((db_file.add_list_chunk(&list_chunk)?, list_chunk));
+    }
+
+    // dbio::dblist::List::find_list_chunk - Find the list chunk of a list, adding one if the list was cut short before it had one
+    //
+    // ARGUMENTS:
+    //  db_file: &mut ChunkyFile - The file the list lives in
+    //  list_chunks: &[(u64, ListChunk)] - Every list chunk in the file, see ChunkyFile::list_chunks()
+    //  list_id: &str - The ID of the list
+    fn find_list_chunk(db_file: &mut ChunkyFile, list_chunks: &[(u64, ListChunk)], list_id: &str) -> Result<(u64, ListChunk), Box<dyn Error>>
+    {
+        match list_chunks.iter().find(|(_, list_chunk)| list_chunk.list_id == list_id)
+        {
+            Some(list_chunk) => return Ok(list_chunk.clone()),
+            None => return List::add_list_chunk(db_file, list_id, 0),
+        }
+    }
+
+    // dbio::dblist::List::with_structure - Build the list in memory, the structure and list chunk have to be stored already
+    //
+    // Indexes that were never finished are left out, and unique fields missing an index get one.
+    //
+    // ARGUMENTS:
+    //  db_file: ChunkyFile - The file the list lives in
+    //  structure: Structure - The structure of the list
+    //  list_chunk: (u64, ListChunk) - The list chunk and its position
+    //  index_chunks: &[(u64, IndexChunk)] - Every index chunk in the file, see ChunkyFile::index_chunks()
+    fn with_structure(db_file: ChunkyFile, structure: Structure, list_chunk: (u64, ListChunk), index_chunks: &[(u64, IndexChunk)]) -> Result<Self, Box<dyn Error>>
+    {
+        let (list_chunk_pos, list_chunk) = list_chunk;
+
+        // Every tree of the list changes one at a time, so they can all share a journal
+        let uuid_tree = LazyAVL::new(db_file.try_clone()?, list_chunk.uuid_tree_head, list_chunk.uuid_tree_laze)
+            .with_journal(list_chunk.journal)
+            .with_head_store(list_chunk_pos + LIST_CHUNK_OFFSET::UUID_TREE_HEAD);
+
+        let mut indexes = Vec::<Index>::new();
+
+        for (index_chunk_pos, index_chunk) in index_chunks
+        {
+            if index_chunk.list_id != list_chunk.list_id || index_chunk.state != INDEX_STATE::READY
+            {
+                continue;
+            }
+
+            let tree = LazyAVL::new(db_file.try_clone()?, index_chunk.head, index_chunk.laze)
+                .with_journal(list_chunk.journal)
+                .with_head_store(index_chunk_pos + INDEX_CHUNK_OFFSET::HEAD);
+
+            indexes.push
+            (
+                Index
+                {
+                    field_id: index_chunk.field_id.clone(),
+                    tree: tree,
+                    index_chunk: *index_chunk_pos,
+                }
+            );
+        }
+
+        let mut list = Self
+        {
+            structure: structure,
+            uuid_tree: Rc::new(RefCell::new(uuid_tree)),
+            db_file: db_file,
+            entry_count: list_chunk.entry_count,
+            list_chunk: list_chunk_pos,
+            indexes: indexes,
+            targets: Vec::new(),
+            migrations: Vec::new(),
+            outdated: None,
+        };
+
+        list.index_unique_fields()?;
+
+        return Ok(list);
+    }
+
+    // dbio::dblist::List::index_unique_fields - Create an index for every unique field that doesn't have one, see check_unique()
+    //
+    // The indexes are as lazy as the UUID tree.
+    fn index_unique_fields(&mut self) -> Result<(), Box<dyn Error>>
+    {
+        let laze = self.uuid_tree.borrow().laze();
+        let unique_fields: Vec<String> = self.structure.requirements().iter()
+            .filter(|requirement| requirement.unique)
+            .map(|requirement| requirement.field_id.clone())
+            .collect();
+
+        for field_id in unique_fields
+        {
+            if self.index(&field_id).is_none()
+            {
+                self.create_index(&field_id, laze)?;
+            }
+        }
+
+        return Ok(());
+    }
+
+    // dbio::dblist::List::create_index - Give a field of the list its own tree, filled from the entries already in the list
+    //
+    // The index is stored as it is built, but it only counts once every entry is in it. An index cut short is ignored
+    // when the list is opened again, and has to be created again. An index on a unique field is refused if two entries
+    // share a value.
+    //
+    // The index holds the values as they were written, so while entries under older versions are left it is passed over,
+    // and migrate_all() rebuilds it from the upcast entries, see has_outdated().
+    //
+    // ARGUMENTS:
+    //  field_id: &str - The ID of the field, which the structure has to declare
+    //  laze: u8 - How far the index tree may lean before it is rebalanced, see LazyAVL::max_balance()
+    pub fn create_index(&mut self, field_id: &str, laze: u8) -> Result<(), Box<dyn Error>>
+    {
+        if !self.structure.requirements().iter().any(|requirement| requirement.field_id == field_id)
+        {
+            bail!("List {} has no field {} to index!", self.structure.id(), field_id);
+        }
+
+        if self.index(field_id).is_some()
+        {
+            bail!("Field {} of list {} is already indexed!", field_id, self.structure.id());
+        }
+
+        if laze > LAZY_AVL_CONST::LAZE_MAX
+        {
+            bail!("Index on {} of list {} can't have a laze of {}, the most is {}!", field_id, self.structure.id(), laze, LAZY_AVL_CONST::LAZE_MAX);
+        }
+
+        let journal = self.db_file.read_list_chunk(self.list_chunk)?.journal;
+        let index_chunk = self.db_file.add_index_chunk(&IndexChunk::new(self.structure.id(), field_id, laze))?;
+        let mut tree = LazyAVL::new(self.db_file.try_clone()?, 0, laze)
+            .with_journal(journal)
+            .with_head_store(index_chunk + INDEX_CHUNK_OFFSET::HEAD);
+
+        let unique = self.structure.requirements().iter().any(|requirement| requirement.field_id == field_id && requirement.unique);
+        let uuid_points = self.uuid_tree.borrow_mut().positions()?;
+
+        for uuid_point in uuid_points
+        {
+            let (chain, entry_chunk) = self.db_file.read_entry_chunk(ChunkyFile::chunk_pos(uuid_point))?;
+
+            for (insertion_point, field) in chain.insertion_points()?.into_iter().zip(entry_chunk.fields)
+            {
+                if field.id != field_id
+                {
+                    continue;
+                }
+
+                // A duplicate leaves the index unfinished, so it is ignored
+                if unique && !field.value.is_null()
+                {
+                    if let Some(existing) = tree.find_entry(field_id, &field.value)?
+                    {
+                        return Err(Box::new(StructureError::Duplicate { field_id: field.id, existing: existing.uuid }));
+                    }
+                }
+
+                tree.insert(insertion_point)?;
+            }
+        }
+
+        self.db_file.write_at(index_chunk + INDEX_CHUNK_OFFSET::STATE, &[INDEX_STATE::READY])?;
+
+        self.indexes.push
         (
-            Self
+            Index
             {
-                structure: structure,
+                field_id: field_id.to_string(),
                 tree: tree,
-                db_file: db_file,
-                entry_count: 0,
+                index_chunk: index_chunk,
             }
         );
+
+        return Ok(());
     }
 
-    pub fn add_entry(&mut self, entry: Entry) -> Result<(), Box<dyn Error>>
-    {   
-        let entry_chunk = EntryChunk::new(entry);
-        let mut insertion_points = self.db_file.add_entry_chunk(entry_chunk)?;
+    // dbio::dblist::List::drop_index - Stop indexing a field, returning whether it was indexed
+    //
+    // Unique fields can't lose their index, it's what keeps them unique.
+    //
+    // ARGUMENTS:
+    //  field_id: &str - The ID of the field
+    pub fn drop_index(&mut self, field_id: &str) -> Result<bool, Box<dyn Error>>
+    {
+        let position = match self.indexes.iter().position(|index| index.field_id == field_id)
+        {
+            Some(position) => position,
+            None => return Ok(false),
+        };
 
-        if insertion_points.len() == 0
+        if self.structure.requirements().iter().any(|requirement| requirement.field_id == field_id && requirement.unique)
         {
-            bail!("Fieldless entry!");
+            bail!("Field {} of list {} is unique, its index can't be dropped!", field_id, self.structure.id());
         }
 
-        if self.tree.head == 0
+        self.db_file.write_at(self.indexes[position].index_chunk + INDEX_CHUNK_OFFSET::STATE, &[INDEX_STATE::DROPPED])?;
+        self.indexes.remove(position);
+
+        return Ok(true);
+    }
+
+    // dbio::dblist::List::check_index_unique - Make sure no two entries share a value in the index of a field, if it has one
+    //
+    // Equal values sit next to each other in the index, so only neighbours are compared. Nulls may repeat.
+    //
+    // ARGUMENTS:
+    //  field_id: &str - The ID of the field
+    fn check_index_unique(&mut self, field_id: &str) -> Result<(), Box<dyn Error>>
+    {
+        // Only the indexes are borrowed, so the fields can be read through the list's own handle
+        let tree = match self.indexes.iter_mut().find(|index| index.field_id == field_id)
         {
-            self.tree.head = insertion_points.pop().expect("Insertion point length check failed! you shouldn't see this!");
-        }
+            Some(index) => &mut index.tree,
+            None => return Ok(()),
+        };
+
+        let mut previous: Option<(u64, Type)> = None;
 
-        for insertion_point in insertion_points
+        for field_pos in tree.positions()?
         {
-            self.tree.insert(insertion_point)?;
-        }
+            let value = self.db_file.read_field(field_pos)?.value;
+
+            if let Some((previous_pos, previous_value)) = &previous
+            {
+                if !value.is_null() && *previous_value == value
+                {
+                    let existing = Entry::from_chunk(tree.entry_chunk(*previous_pos)?);
+
+                    return Err(Box::new(StructureError::Duplicate { field_id: field_id.to_string(), existing: existing.uuid }));
+                }
+            }
 
+            previous = Some((field_pos, value));
+        }
 
         return Ok(());
     }
-}
 
-// Tests!
-//
-#[cfg(test)]
-mod tests
-{
-    use super::*;
+    // dbio::dblist::List::index - Get the tree of an indexed field, or None if the field isn't indexed
+    //
+    // ARGUMENTS:
+    //  field_id: &str - The ID of the field
+    pub fn index(&mut self, field_id: &str) -> Option<&mut LazyAVL>
+    {
+        return self.indexes.iter_mut().find(|index| index.field_id == field_id).map(|index| &mut index.tree);
+    }
 
-    #[test]
-    fn test_entry_new()
+    // dbio::dblist::List::indexed_fields - Get the ID of every indexed field, in the order they were indexed
+    //
+    pub fn indexed_fields(&self) -> Vec<&str>
     {
-        let uuid = UuidV4::new();
-        let uuid2 = uuid.clone();
-        let fields = vec![Field::new("id", Type::S(Some(S::new("Test"))))];
-        let fields2 = fields.clone();
-        let entry = Entry::new(uuid, fields).unwrap();
-        assert_eq!(entry.uuid, uuid2);
-        assert_eq!(entry.fields, fields2);
+        return self.indexes.iter().map(|index| index.field_id.as_str()).collect();
     }
 
-    #[test]
-    fn test_entry_get_field()
+    // dbio::dblist::List::link - Let references in this list point into another list, checked when entries are added
+    //
+    // ARGUMENTS:
+    //  target: &List - The list references may point into, see Requirement::references()
+    pub fn link(&mut self, target: &List)
     {
-        let uuid = UuidV4::new();
-        let fields = vec![Field::new("id", Type::S(Some(S::new("Test"))))];
-        let entry = Entry::new(uuid, fields).unwrap();
-        assert_eq!(entry.get_field("id").unwrap().value, Type::S(Some(S::new("Test"))));
+        self.targets.retain(|(list_id, _)| list_id != target.structure.id());
+        self.targets.push((target.structure.id().to_string(), Rc::clone(&target.uuid_tree)));
     }
 
-    #[test]
-    fn test_list_new()
+    // dbio::dblist::List::evolve - Move the list to a new version of its structure, storing it in the file
+    //
+    // Entries written under older versions are upcast when they are read, see upcast() and add_migration(). Indexes on
+    // fields the new structure no longer declares are dropped, and new unique fields are indexed. If a new unique field
+    // already has duplicates the list is left as it was.
+    //
+    // ARGUMENTS:
+    //  structure: Structure - The new structure, with the same ID and a greater version
+    pub fn evolve(&mut self, structure: Structure) -> Result<(), Box<dyn Error>>
     {
-        let structure = Structure::new("test", vec![Requirement::new("id", std::mem::discriminant(&Type::S(None)))]);
-        let db_file = ChunkyFile::create("tests/test_list_new.db").unwrap();
+        if structure.id() != self.structure.id()
+        {
+            bail!("Can't evolve list {} into {}!", self.structure.id(), structure.id());
+        }
 
-        let list = List::new(db_file, structure.clone()).unwrap();
+        if structure.version() <= self.structure.version()
+        {
+            bail!("List {} is already at version {}!", self.structure.id(), self.structure.version());
+        }
 
-        assert_eq!(list.structure, structure);
-        assert_eq!(list.tree.head, 0);
-        assert_eq!(list.entry_count, 0);
+        let previous = std::mem::replace(&mut self.structure, structure);
+        let indexed = self.indexes.len();
+
+        let newly_unique: Vec<String> = self.structure.requirements().iter()
+            .filter(|requirement| requirement.unique && !previous.requirements().iter().any(|earlier| earlier.field_id == requirement.field_id && earlier.unique))
+            .map(|requirement| requirement.field_id.clone())
+            .collect();
+
+        // New unique fields are checked and indexed before the structure is stored, so duplicates can still be backed out of
+        let mut evolved = newly_unique.iter().try_for_each(|field_id| self.check_index_unique(field_id)).and_then(|_| self.index_unique_fields());
+
+        if evolved.is_ok()
+        {
+            evolved = self.db_file.add_schema_chunk(&self.structure).map(|_| ());
+        }
+
+        if let Err(error) = evolved
+        {
+            for index in self.indexes.drain(indexed..)
+            {
+                self.db_file.write_at(index.index_chunk + INDEX_CHUNK_OFFSET::STATE, &[INDEX_STATE::DROPPED])?;
+            }
+
+            self.structure = previous;
+
+            return Err(error);
+        }
+
+        // Every entry so far is under an older version now
+        self.outdated = Some(self.entry_count > 0);
+
+        let undeclared: Vec<String> = self.indexes.iter()
+            .filter(|index| !self.structure.requirements().iter().any(|requirement| requirement.field_id == index.field_id))
+            .map(|index| index.field_id.clone())
+            .collect();
+
+        for field_id in undeclared
+        {
+            self.drop_index(&field_id)?;
+        }
+
+        return Ok(());
     }
 
-    #[test]
-    fn test_list_add_entry()
+    // dbio::dblist::List::add_migration - Declare how entries are migrated from one version of the structure to another
+    //
+    // Migrations form a chain, see upcast(), so there can only be one from each version.
+    //
+    // ARGUMENTS:
+    //  migration: Migration - The migration, which must not go past the current version
+    pub fn add_migration(&mut self, migration: Migration) -> Result<(), Box<dyn Error>>
     {
-        let structure = Structure::new("test", vec![Requirement::new("id", std::mem::discriminant(&Type::S(None)))]);
-        let db_file = ChunkyFile::create("tests/test_list_add_entry.db").unwrap();
+        if migration.to_version() > self.structure.version()
+        {
+            bail!("Migration to version {} is past the current version {}!", migration.to_version(), self.structure.version());
+        }
 
-        let mut list = List::new(db_file, structure).unwrap();
+        if self.migrations.iter().any(|earlier| earlier.from_version() == migration.from_version())
+        {
+            bail!("List {} already has a migration from version {}!", self.structure.id(), migration.from_version());
+        }
 
-        let uuid1 = UuidV4::new();
-        let fields1 = vec![Field::new("id", Type::S(Some(S::new("Test1"))))];
-        let entry1 = Entry::new(uuid1, fields1).unwrap();
+        let index = self.migrations.partition_point(|earlier| earlier.from_version() <= migration.from_version());
+        self.migrations.insert(index, migration);
 
-        let uuid2 = UuidV4::new();
-        let fields2 = vec![Field::new("id", Type::S(Some(S::new("Test2"))))];
-        let entry2 = Entry::new(uuid2, fields2).unwrap();
+        return Ok(());
+    }
 
-        let uuid3 = UuidV4::new();
-        let fields3 = vec![Field::new("id", Type::S(Some(S::new("Test3"))))];
-        let entry3 = Entry::new(uuid3, fields3).unwrap();
+    // dbio::dblist::List::upcast - Bring an entry written under an older version of the structure up to the current one
+    //
+    // Migrations are followed as a chain from the entry's version, each one picking up at the version the last one went
+    // to, then defaults are filled in. A migration that skips versions skips the migrations from them too. Versions
+    // without a migration are stepped over, which is enough when all they did was add optional or defaulted fields. The
+    // result has to meet the current structure.
+    //
+    // ARGUMENTS:
+    //  entry_chunk: EntryChunk - The entry chunk as read from the file
+    pub fn upcast(&self, entry_chunk: EntryChunk) -> Result<Entry, Box<dyn Error>>
+    {
+        let version = entry_chunk.version;
+        let mut entry = Entry::from_chunk(entry_chunk);
 
-        list.add_entry(entry1).unwrap();
-        list.add_entry(entry2).unwrap();
-        list.add_entry(entry3).unwrap();
+        if version > self.structure.version()
+        {
+            bail!("Entry {} was written under version {} of {}, which is newer than this one!", entry.uuid, version, self.structure.id());
+        }
+
+        if version == self.structure.version()
+        {
+            return Ok(entry);
+        }
+
+        let mut at = version;
+
+        while at < self.structure.version()
+        {
+            match self.migrations.iter().find(|migration| migration.from_version() == at)
+            {
+                Some(migration) =>
+                {
+                    migration.apply(&mut entry.fields)?;
+                    at = migration.to_version();
+                }
+                None => at += 1,
+            }
+        }
+
+        self.structure.fill_defaults(&mut entry.fields);
+        self.structure.check(&entry.fields)?;
+
+        return Ok(entry);
+    }
+
+    pub fn add_entry(&mut self, mut entry: Entry) -> Result<(), Box<dyn Error>>
+    {   
+        // A UUID in the tree twice would leave lookups finding whichever copy they reach first
+        if self.uuid_tree.borrow_mut().search(&Field::new("uuid", Type::Ref(Some(entry.uuid.clone()))))?.is_some()
+        {
+            bail!("Entry {} is already in list {}!", entry.uuid, self.structure.id());
+        }
+
+        self.structure.fill_defaults(&mut entry.fields);
+        self.structure.check(&entry.fields)?;
+        self.check_references(&entry)?;
+        self.check_unique(&entry)?;
+
+        return self.insert_entry(entry, true);
+    }
+
+    // dbio::dblist::List::insert_entry - Write an entry under the current version of the structure and link it into the trees
+    //
+    // The fields go into their indexes and the UUID into the UUID tree all at once, along with the new entry count, see
+    // link_entry(). An entry cut short is left in the file but never makes it into the list. Binary values too big to
+    // keep inline are moved to overflow chunks first, see move_out_bins().
+    //
+    // ARGUMENTS:
+    //  entry: Entry - The entry, already checked against the structure
+    //  counted: bool - Whether the entry is new to the list and counts towards its entries
+    fn insert_entry(&mut self, mut entry: Entry, counted: bool) -> Result<(), Box<dyn Error>>
+    {
+        for field in entry.fields.iter_mut()
+        {
+            List::move_out_bins(&mut self.db_file, &mut field.value)?;
+        }
+
+        let field_ids: Vec<String> = entry.fields.iter().map(|field| field.id.clone()).collect();
+        let mut entry_chunk = EntryChunk::new(entry);
+        entry_chunk.version = self.structure.version();
+
+        let (uuid_point, insertion_points) = self.db_file.add_entry_chunk_points(entry_chunk)?;
+
+        if insertion_points.len() == 0
+        {
+            bail!("Fieldless entry!");
+        }
+
+        let field_points: Vec<(u64, String)> = insertion_points.into_iter().zip(field_ids).collect();
+        let entry_count = if counted { Some(self.entry_count + 1) } else { None };
+
+        self.link_entry(uuid_point, field_points, false, entry_count)?;
+
+        if counted
+        {
+            self.entry_count += 1;
+        }
+
+        return Ok(());
     }
-}
\ No newline at end of file
+
+    // dbio::dblist::List::migrate_all - Rewrite every entry under the current version of the structure, returning how many were upcast
+    //
+    // Entries are copied to the end of the file and linked into new trees, which only replace the old ones once every
+    // entry made it across. The indexes are rebuilt from the upcast values, so unique fields are checked again. If
+    // anything fails, duplicates included, the old trees are put back, so the list is left as it was. Nothing is
+    // rewritten if every entry is already current.
+    //
+    // The rewrite happens all at once and blocks until it is done, it isn't spread out in the background. A rewrite cut
+    // short starts over from the beginning the next time, until then entries keep being upcast whenever they are read.
+    pub fn migrate_all(&mut self) -> Result<u64, Box<dyn Error>>
+    {
+        let uuid_points = self.uuid_tree.borrow_mut().positions()?;
+        let mut entry_chunks = Vec::<u64>::with_capacity(uuid_points.len());
+        let mut outdated = 0;
+
+        for uuid_point in uuid_points
+        {
+            let chunk_pos = ChunkyFile::chunk_pos(uuid_point);
+            let (_, entry_chunk) = self.db_file.read_entry_chunk(chunk_pos)?;
+
+            if entry_chunk.version != self.structure.version()
+            {
+                outdated += 1;
+            }
+
+            entry_chunks.push(chunk_pos);
+        }
+
+        if outdated == 0
+        {
+            self.outdated = Some(false);
+
+            return Ok(0);
+        }
+
+        // The new trees only keep their heads in memory until every entry made it across
+        let journal = self.db_file.read_list_chunk(self.list_chunk)?.journal;
+        let mut old_trees = Vec::<LazyAVL>::with_capacity(self.indexes.len());
+
+        for index in self.indexes.iter_mut()
+        {
+            let new_tree = LazyAVL::new(self.db_file.try_clone()?, 0, index.tree.laze()).with_journal(journal);
+            old_trees.push(std::mem::replace(&mut index.tree, new_tree));
+        }
+
+        let new_uuid_tree = LazyAVL::new(self.db_file.try_clone()?, 0, self.uuid_tree.borrow().laze()).with_journal(journal);
+        let old_uuid_tree = self.uuid_tree.replace(new_uuid_tree);
+
+        // The new trees only ever hold current entries, so check_unique() can go by them
+        self.outdated = Some(false);
+
+        let mut rewritten = Ok(());
+
+        for chunk_pos in entry_chunks
+        {
+            rewritten = self.db_file.read_entry_chunk(chunk_pos).and_then(|(_, entry_chunk)| self.upcast(entry_chunk)).and_then(|entry|
+            {
+                self.check_unique(&entry)?;
+                return self.insert_entry(entry, false);
+            });
+
+            if rewritten.is_err()
+            {
+                break;
+            }
+        }
+
+        // The heads are spread over the list chunk and the index chunks, so they are switched over together through the journal
+        if rewritten.is_ok()
+        {
+            let mut uuid_tree = self.uuid_tree.borrow_mut();
+            let uuid_head = uuid_tree.head;
+
+            uuid_tree.stage_write(self.list_chunk + LIST_CHUNK_OFFSET::UUID_TREE_HEAD, uuid_head.to_be_bytes().to_vec());
+
+            for index in &self.indexes
+            {
+                uuid_tree.stage_write(index.index_chunk + INDEX_CHUNK_OFFSET::HEAD, index.tree.head.to_be_bytes().to_vec());
+            }
+
+            rewritten = uuid_tree.commit();
+        }
+
+        if let Err(error) = rewritten
+        {
+            for (index, old_tree) in self.indexes.iter_mut().zip(old_trees)
+            {
+                index.tree = old_tree;
+            }
+
+            self.uuid_tree.replace(old_uuid_tree);
+            self.outdated = Some(true);
+
+            return Err(error);
+        }
+
+        for index in self.indexes.iter_mut()
+        {
+            index.tree.set_head_store(index.index_chunk + INDEX_CHUNK_OFFSET::HEAD);
+        }
+
+        self.uuid_tree.borrow_mut().set_head_store(self.list_chunk + LIST_CHUNK_OFFSET::UUID_TREE_HEAD);
+
+        return Ok(outdated);
+    }
+
+    // dbio::dblist::List::has_outdated - Check whether any entry of the list was written under an older version of the structure
+    //
+    // Indexes hold the values as they were written, which can differ from the upcast ones, so they can only be trusted
+    // once every entry is current. Every entry is read the first time, after that the answer is kept until evolve() or
+    // migrate_all() changes it.
+    fn has_outdated(&mut self) -> Result<bool, Box<dyn Error>>
+    {
+        if let Some(outdated) = self.outdated
+        {
+            return Ok(outdated);
+        }
+
+        let uuid_points = self.uuid_tree.borrow_mut().positions()?;
+        let mut outdated = false;
+
+        for uuid_point in uuid_points
+        {
+            if self.db_file.read_entry_chunk(ChunkyFile::chunk_pos(uuid_point))?.1.version != self.structure.version()
+            {
+                outdated = true;
+                break;
+            }
+        }
+
+        self.outdated = Some(outdated);
+
+        return Ok(outdated);
+    }
+
+    // dbio::dblist::List::get_entry - Load an entry of the list by its UUID, upcasting it to the current version of the structure
+    //
+    // ARGUMENTS:
+    //  uuid: &UuidV4 - The UUID of the entry
+    pub fn get_entry(&mut self, uuid: &UuidV4) -> Result<Option<Entry>, Box<dyn Error>>
+    {
+        let uuid_point = match self.uuid_tree.borrow_mut().search(&Field::new("uuid", Type::Ref(Some(uuid.clone()))))?
+        {
+            Some(uuid_point) => uuid_point,
+            None => return Ok(None),
+        };
+
+        // The UUID is in the first chunk of its entry
+        let (_, entry_chunk) = self.db_file.read_entry_chunk(ChunkyFile::chunk_pos(uuid_point))?;
+
+        return Ok(Some(self.upcast(entry_chunk)?));
+    }
+
+    // dbio::dblist::List::remove_entry - Remove an entry from the list by its UUID, returning whether it was there
+    //
+    // The fields leave their indexes and the UUID leaves the UUID tree all at once, along with the new entry count, see
+    // link_entry(). The entry stays in the file, and references to it from other lists are left dangling.
+    //
+    // ARGUMENTS:
+    //  uuid: &UuidV4 - The UUID of the entry
+    pub fn remove_entry(&mut self, uuid: &UuidV4) -> Result<bool, Box<dyn Error>>
+    {
+        let uuid_point = match self.uuid_tree.borrow_mut().search(&Field::new("uuid", Type::Ref(Some(uuid.clone()))))?
+        {
+            Some(uuid_point) => uuid_point,
+            None => return Ok(false),
+        };
+
+        let (chain, entry_chunk) = self.db_file.read_entry_chunk(ChunkyFile::chunk_pos(uuid_point))?;
+        let field_points: Vec<(u64, String)> = chain.insertion_points()?.into_iter().zip(entry_chunk.fields).map(|(insertion_point, field)| (insertion_point, field.id)).collect();
+
+        self.link_entry(uuid_point, field_points, true, Some(self.entry_count.saturating_sub(1)))?;
+
+        self.entry_count = self.entry_count.saturating_sub(1);
+
+        return Ok(true);
+    }
+
+    // dbio::dblist::List::move_out_bins - Move binary values longer than BIN_INLINE_MAX out of a value and into overflow chunks
+    //
+    // ARGUMENTS:
+    //  db_file: &mut ChunkyFile - The file to add the overflow chunks to
+    //  value: &mut Type - The value, lists and documents are searched too
+    fn move_out_bins(db_file: &mut ChunkyFile, value: &mut Type) -> Result<(), Box<dyn Error>>
+    {
+        match value
+        {
+            Type::Bin(Some(Bin::Inline(data))) if data.len() > BIN_INLINE_MAX =>
+            {
+                let binary = db_file.add_bin(data)?;
+                *value = Type::Bin(Some(binary));
+            }
+            Type::List(Some(elements)) =>
+            {
+                for element in elements.iter_mut()
+                {
+                    List::move_out_bins(db_file, element)?;
+                }
+            }
+            Type::Doc(Some(fields)) =>
+            {
+                for field in fields.iter_mut()
+                {
+                    List::move_out_bins(db_file, &mut field.value)?;
+                }
+            }
+            _ => {}
+        }
+
+        return Ok(());
+    }
+
+    // dbio::dblist::List::link_entry - Link an entry into the UUID tree and the indexes of its fields, or unlink it, in one commit
+    //
+    // Every tree of the list shares its journal, so the changes are staged in each tree and committed together through
+    // the UUID tree, see LazyAVL::commit_with(). If anything fails every staged change is thrown away. A tree can only
+    // hold one staged change, so an entry that repeats a field ID is refused before anything is staged.
+    //
+    // ARGUMENTS:
+    //  uuid_point: u64 - The insertion point of the UUID of the entry
+    //  field_points: Vec<(u64, String)> - The insertion point and ID of every field of the entry
+    //  unlink: bool - Whether to unlink the entry instead of linking it
+    //  entry_count: Option<u64> - The entry count to store along with the change, if it changes
+    fn link_entry(&mut self, uuid_point: u64, field_points: Vec<(u64, String)>, unlink: bool, entry_count: Option<u64>) -> Result<(), Box<dyn Error>>
+    {
+        for (i, (_, field_id)) in field_points.iter().enumerate()
+        {
+            if field_points[..i].iter().any(|(_, earlier)| earlier == field_id)
+            {
+                bail!("Field {} is in the entry more than once!", field_id);
+            }
+        }
+
+        let stage = |tree: &mut LazyAVL, field_pos: u64| if unlink { tree.stage_remove(field_pos).map(|_| ()) } else { tree.stage_insert(field_pos) };
+
+        // Only the indexes are borrowed, so the UUID tree can be borrowed alongside them
+        let mut uuid_tree = self.uuid_tree.borrow_mut();
+        let mut staged = Ok(());
+
+        for (field_pos, field_id) in field_points
+        {
+            if let Some(index) = self.indexes.iter_mut().find(|index| index.field_id == field_id)
+            {
+                staged = stage(&mut index.tree, field_pos);
+
+                if staged.is_err()
+                {
+                    break;
+                }
+            }
+        }
+
+        if staged.is_ok()
+        {
+            if let Some(entry_count) = entry_count
+            {
+                uuid_tree.stage_write(self.list_chunk + LIST_CHUNK_OFFSET::ENTRY_COUNT, entry_count.to_be_bytes().to_vec());
+            }
+
+            staged = stage(&mut uuid_tree, uuid_point);
+        }
+
+        let mut index_trees: Vec<&mut LazyAVL> = self.indexes.iter_mut().map(|index| &mut index.tree).collect();
+
+        if staged.is_ok()
+        {
+            staged = uuid_tree.commit_with(&mut index_trees);
+        }
+
+        if staged.is_err()
+        {
+            uuid_tree.discard();
+
+            for tree in index_trees
+            {
+                tree.discard();
+            }
+        }
+
+        return staged;
+    }
+
+    // dbio::dblist::List::find - Find every entry of the list with a field of the given value, upcasting them to the current structure
+    //
+    // The lookup goes through the field's index if it has one, otherwise every entry in the list is read. Null never
+    // matches anything, even another null.
+    //
+    // ARGUMENTS:
+    //  id: &str - The ID of the field
+    //  value: &Type - The value to look for
+    pub fn find(&mut self, id: &str, value: &Type) -> Result<Vec<Entry>, Box<dyn Error>>
+    {
+        if value.is_null()
+        {
+            return Ok(Vec::new());
+        }
+
+        return self.range(id, Bound::Included(value), Bound::Included(value));
+    }
+
+    // dbio::dblist::List::range - Find every entry of the list with a field between two values, from least to greatest
+    //
+    // Like find(), the lookup goes through the field's index if it has one, otherwise every entry in the list is read.
+    // Indexes are passed over while entries under older versions are left, see has_outdated(). Nulls are never in range.
+    //
+    // ARGUMENTS:
+    //  id: &str - The ID of the field
+    //  lower: Bound<&Type> - The least value, if any
+    //  upper: Bound<&Type> - The greatest value, if any
+    pub fn range(&mut self, id: &str, lower: Bound<&Type>, upper: Bound<&Type>) -> Result<Vec<Entry>, Box<dyn Error>>
+    {
+        let mut entries = Vec::<Entry>::new();
+        let indexed = self.indexes.iter().any(|index| index.field_id == id) && !self.has_outdated()?;
+
+        // Only the indexes are borrowed, so the fields can be read through the list's own handle
+        if let Some(index) = self.indexes.iter_mut().find(|index| indexed && index.field_id == id)
+        {
+            let tree = &mut index.tree;
+            let field_positions = tree.range(id, lower, upper)?.collect::<Result<Vec<u64>, Box<dyn Error>>>()?;
+            let mut entry_chunks = Vec::<EntryChunk>::with_capacity(field_positions.len());
+
+            for field_pos in field_positions
+            {
+                // Nulls sort before every value, so they only turn up without a lower bound
+                if !self.db_file.read_field(field_pos)?.value.is_null()
+                {
+                    entry_chunks.push(tree.entry_chunk(field_pos)?);
+                }
+            }
+
+            for entry_chunk in entry_chunks
+            {
+                entries.push(self.upcast(entry_chunk)?);
+            }
+
+            return Ok(entries);
+        }
+
+        let uuid_points = self.uuid_tree.borrow_mut().positions()?;
+
+        for uuid_point in uuid_points
+        {
+            let (_, entry_chunk) = self.db_file.read_entry_chunk(ChunkyFile::chunk_pos(uuid_point))?;
+            let entry = self.upcast(entry_chunk)?;
+
+            if entry.get_field(id).is_some_and(|field| !field.value.is_null() && (lower, upper).contains(&field.value))
+            {
+                entries.push(entry);
+            }
+        }
+
+        // Same order the index would have given
+        entries.sort_by(|entry_a, entry_b| entry_a.get_field(id).map(|field| &field.value).partial_cmp(&entry_b.get_field(id).map(|field| &field.value)).unwrap_or(std::cmp::Ordering::Equal));
+
+        return Ok(entries);
+    }
+
+    // dbio::dblist::List::check_unique - Make sure no entry in the list already has the value of a unique field, looking it up in its index
+    //
+    // While entries under older versions are left, every entry is read and upcast instead, see has_outdated().
+    //
+    // ARGUMENTS:
+    //  entry: &Entry - The entry about to be added
+    fn check_unique(&mut self, entry: &Entry) -> Result<(), Box<dyn Error>>
+    {
+        let unique_fields: Vec<String> = self.structure.requirements().iter()
+            .filter(|requirement| requirement.unique)
+            .map(|requirement| requirement.field_id.clone())
+            .collect();
+
+        for field_id in unique_fields
+        {
+            let field = match entry.get_field(&field_id)
+            {
+                Some(field) if !field.value.is_null() => field,
+                _ => continue,
+            };
+
+            if self.has_outdated()?
+            {
+                if let Some(existing) = self.find(&field.id, &field.value)?.into_iter().next()
+                {
+                    return Err(Box::new(StructureError::Duplicate { field_id: field.id.clone(), existing: existing.uuid }));
+                }
+
+                continue;
+            }
+
+            let tree = match self.indexes.iter_mut().find(|index| index.field_id == field.id)
+            {
+                Some(index) => &mut index.tree,
+                None => bail!("Unique field {} has no index! You shouldn't see this!", field.id),
+            };
+
+            if let Some(existing) = tree.find_entry(&field.id, &field.value)?
+            {
+                return Err(Box::new(StructureError::Duplicate { field_id: field.id.clone(), existing: existing.uuid }));
+            }
+        }
+
+        return Ok(());
+    }
+
+    // dbio::dblist::List::check_references - Make sure every reference the structure declares a target list for points at an entry
+    //
+    // References in nested documents and lists of documents are checked too, see collect_references().
+    //
+    // ARGUMENTS:
+    //  entry: &Entry - The entry about to be added
+    fn check_references(&self, entry: &Entry) -> Result<(), Box<dyn Error>>
+    {
+        let mut references = Vec::<(String, &str, &UuidV4)>::new();
+        List::collect_references(&self.structure, &entry.fields, "", &mut references);
+
+        for (path, target_list, uuid) in references
+        {
+            // An entry may reference itself
+            if target_list == self.structure.id() && *uuid == entry.uuid
+            {
+                continue;
+            }
+
+            // References into this list use its own tree, anything else has to be linked first
+            let uuid_tree = if target_list == self.structure.id()
+            {
+                &self.uuid_tree
+            }
+            else
+            {
+                match self.targets.iter().find(|(list_id, _)| list_id == target_list)
+                {
+                    Some((_, uuid_tree)) => uuid_tree,
+                    None => bail!("Field {} references list {}, which isn't linked!", path, target_list),
+                }
+            };
+
+            if uuid_tree.borrow_mut().search(&Field::new("uuid", Type::Ref(Some(uuid.clone()))))?.is_none()
+            {
+                bail!("Field {} references {}, which isn't in list {}!", path, uuid, target_list);
+            }
+        }
+
+        return Ok(());
+    }
+
+    // dbio::dblist::List::collect_references - Gather every non-null reference with a target list, along with its path, see Entry::get_path()
+    //
+    // ARGUMENTS:
+    //  structure: &Structure - The structure the fields meet
+    //  fields: &[Field] - The fields of the entry, or of a document nested in it
+    //  prefix: &str - The path of the document the fields are in, empty for the entry itself
+    //  references: &mut Vec<(String, &str, &UuidV4)> - Where to put the path, target list and UUID of each reference
+    fn collect_references<'a>(structure: &'a Structure, fields: &'a [Field], prefix: &str, references: &mut Vec<(String, &'a str, &'a UuidV4)>)
+    {
+        for requirement in structure.requirements()
+        {
+            let field = match fields.iter().find(|field| field.id == requirement.field_id)
+            {
+                Some(field) => field,
+                None => continue,
+            };
+
+            let path = format!("{}{}", prefix, field.id);
+            let target_list = requirement.target_list.as_deref();
+
+            match (&field.value, target_list, &requirement.structure)
+            {
+                (Type::Ref(Some(uuid)), Some(target_list), _) => references.push((path, target_list, uuid)),
+                (Type::Doc(Some(nested)), _, Some(nested_structure)) => List::collect_references(nested_structure, nested, &format!("{}.", path), references),
+                (Type::List(Some(elements)), _, _) =>
+                {
+                    for (index, element) in elements.iter().enumerate()
+                    {
+                        match (element, target_list, &requirement.structure)
+                        {
+                            (Type::Ref(Some(uuid)), Some(target_list), _) => references.push((format!("{}.{}", path, index), target_list, uuid)),
+                            (Type::Doc(Some(nested)), _, Some(nested_structure)) => List::collect_references(nested_structure, nested, &format!("{}.{}.", path, index), references),
+                            _ => {}
+                        }
+                    }
+                }
+                _ => {}
+            }
+        }
+    }
+}
+
+// Tests!
+//
+#[cfg(test)]
+mod tests
+{
+    use std::fs::remove_file;
+    use crate::dbio::dbstruct::{Constraint, Requirement, StructureError};
+    use std::io::Read;
+    use crate::apetypes::{B, I, S};
+    use super::*;
+
+    #[test]
+    fn test_entry_new()
+    {
+        let uuid = UuidV4::new();
+        let uuid2 = uuid.clone();
+        let fields = vec![Field::new("id", Type::S(Some(S::new("Test"))))];
+        let fields2 = fields.clone();
+        let entry = Entry::new(uuid, fields).unwrap();
+        assert_eq!(entry.uuid, uuid2);
+        assert_eq!(entry.fields, fields2);
+    }
+
+    #[test]
+    fn test_entry_get_field()
+    {
+        let uuid = UuidV4::new();
+        let fields = vec![Field::new("id", Type::S(Some(S::new("Test"))))];
+        let entry = Entry::new(uuid, fields).unwrap();
+        assert_eq!(entry.get_field("id").unwrap().value, Type::S(Some(S::new("Test"))));
+    }
+
+    #[test]
+    fn test_entry_get_path()
+    {
+        let address = Type::Doc(Some(vec![Field::new("city", Type::S(Some(S::new("Paris")))), Field::new("zip", Type::S(None))]));
+        let tags = Type::List(Some(vec![Type::S(Some(S::new("a"))), Type::Doc(Some(vec![Field::new("b", Type::S(Some(S::new("c"))))]))]));
+        let entry = Entry::new(UuidV4::new(), vec![Field::new("address", address.clone()), Field::new("tags", tags)]).unwrap();
+
+        assert_eq!(entry.get_path("address"), Some(&address));
+        assert_eq!(entry.get_path("address.city"), Some(&Type::S(Some(S::new("Paris")))));
+        assert_eq!(entry.get_path("address.zip"), Some(&Type::S(None)));
+        assert_eq!(entry.get_path("tags.0"), Some(&Type::S(Some(S::new("a")))));
+        assert_eq!(entry.get_path("tags.1.b"), Some(&Type::S(Some(S::new("c")))));
+
+        assert_eq!(entry.get_path("address.country"), None);
+        assert_eq!(entry.get_path("address.city.name"), None);
+        assert_eq!(entry.get_path("tags.2"), None);
+        assert_eq!(entry.get_path("tags.x"), None);
+        assert_eq!(entry.get_path("missing"), None);
+    }
+
+    #[test]
+    fn test_list_new()
+    {
+        let structure = Structure::new("test", vec![Requirement::new("id", std::mem::discriminant(&Type::S(None)))]).unwrap();
+        let _ = remove_file("test_list_new.db");
+        let db_file = ChunkyFile::create("test_list_new.db", DbHeadChunk::new("test", "tester")).unwrap();
+
+        let list = List::new(db_file, structure.clone(), 0).unwrap();
+
+        assert_eq!(list.structure, structure);
+        assert!(list.indexed_fields().is_empty());
+        assert_eq!(list.entry_count, 0);
+
+        drop(list);
+
+        remove_file("test_list_new.db").unwrap();
+    }
+
+    #[test]
+    fn test_list_add_entry()
+    {
+        let structure = Structure::new("test", vec![Requirement::new("id", std::mem::discriminant(&Type::S(None)))]).unwrap();
+        let _ = remove_file("test_list_add_entry.db");
+        let db_file = ChunkyFile::create("test_list_add_entry.db", DbHeadChunk::new("test", "tester")).unwrap();
+
+        let mut list = List::new(db_file, structure, 0).unwrap();
+
+        let uuid1 = UuidV4::new();
+        let fields1 = vec![Field::new("id", Type::S(Some(S::new("Test1"))))];
+        let entry1 = Entry::new(uuid1, fields1).unwrap();
+
+        let uuid2 = UuidV4::new();
+        let fields2 = vec![Field::new("id", Type::S(Some(S::new("Test2"))))];
+        let entry2 = Entry::new(uuid2, fields2).unwrap();
+
+        let uuid3 = UuidV4::new();
+        let fields3 = vec![Field::new("id", Type::S(Some(S::new("Test3"))))];
+        let entry3 = Entry::new(uuid3, fields3).unwrap();
+
+        list.add_entry(entry1.clone()).unwrap();
+        list.add_entry(entry2).unwrap();
+        list.add_entry(entry3).unwrap();
+
+        // The UUID is taken, whatever the fields
+        let copy = Entry::new(entry1.uuid.clone(), vec![Field::new("id", Type::S(Some(S::new("Test4"))))]).unwrap();
+        assert!(list.add_entry(copy).is_err());
+        assert_eq!(list.entry_count, 3);
+        assert_eq!(list.get_entry(&entry1.uuid).unwrap().unwrap(), entry1);
+
+        drop(list);
+
+        remove_file("test_list_add_entry.db").unwrap();
+    }
+
+    #[test]
+    fn test_list_add_entry_structure()
+    {
+        let structure = Structure::new("test", vec!
+        [
+            Requirement::new("id", std::mem::discriminant(&Type::S(None))).not_null(),
+            Requirement::new("count", std::mem::discriminant(&Type::I(None))),
+        ]).unwrap();
+        let _ = remove_file("test_list_add_entry_structure.db");
+        let db_file = ChunkyFile::create("test_list_add_entry_structure.db", DbHeadChunk::new("test", "tester")).unwrap();
+
+        let mut list = List::new(db_file, structure, 0).unwrap();
+        let size = list.db_file.size;
+
+        let add = |list: &mut List, fields: Vec<Field>| list.add_entry(Entry::new(UuidV4::new(), fields).unwrap()).map_err(|error| *error.downcast::<StructureError>().unwrap());
+
+        assert_eq!(add(&mut list, vec![Field::new("count", Type::I(None))]), Err(StructureError::Missing { field_id: "id".to_string() }));
+        assert_eq!(add(&mut list, vec![Field::new("id", Type::I(None))]), Err(StructureError::WrongType { field_id: "id".to_string() }));
+        assert_eq!(add(&mut list, vec![Field::new("id", Type::S(Some(S::new("Test")))), Field::new("name", Type::S(None))]), Err(StructureError::NotAllowed { field_id: "name".to_string() }));
+
+        // Nothing was written for the refused entries
+        assert_eq!(list.db_file.size, size);
+        assert_eq!(add(&mut list, vec![Field::new("id", Type::S(Some(S::new("Test"))))]), Ok(()));
+
+        drop(list);
+
+        remove_file("test_list_add_entry_structure.db").unwrap();
+    }
+
+    #[test]
+    fn test_list_add_entry_defaults()
+    {
+        let structure = Structure::new("test", vec!
+        [
+            Requirement::new("id", std::mem::discriminant(&Type::S(None))).required(),
+            Requirement::new("tag", std::mem::discriminant(&Type::S(None))).default(Type::S(Some(S::new("none")))),
+        ]).unwrap();
+        let _ = remove_file("test_list_add_entry_defaults.db");
+        let db_file = ChunkyFile::create("test_list_add_entry_defaults.db", DbHeadChunk::new("test", "tester")).unwrap();
+
+        let mut list = List::new(db_file, structure, 0).unwrap();
+
+        assert!(Entry::with_structure(UuidV4::new(), vec![], &list.structure).is_err());
+
+        let built = Entry::with_structure(UuidV4::new(), vec![Field::new("id", Type::S(Some(S::new("Test1"))))], &list.structure).unwrap();
+        assert_eq!(built.get_field("tag").unwrap().value, Type::S(Some(S::new("none"))));
+        list.add_entry(built.clone()).unwrap();
+
+        // Entries built without the structure get their defaults when they are added
+        let plain = Entry::new(UuidV4::new(), vec![Field::new("id", Type::S(Some(S::new("Test2"))))]).unwrap();
+        list.add_entry(plain.clone()).unwrap();
+
+        assert_eq!(list.get_entry(&built.uuid).unwrap(), Some(built));
+        assert_eq!(list.get_entry(&plain.uuid).unwrap().unwrap().get_field("tag").unwrap().value, Type::S(Some(S::new("none"))));
+
+        drop(list);
+
+        remove_file("test_list_add_entry_defaults.db").unwrap();
+    }
+
+    #[test]
+    fn test_list_add_entry_unique()
+    {
+        let structure = Structure::new("users", vec!
+        [
+            Requirement::new("email", std::mem::discriminant(&Type::S(None))).unique(),
+            Requirement::new("name", std::mem::discriminant(&Type::S(None))),
+        ]).unwrap();
+        let _ = remove_file("test_list_add_entry_unique.db");
+        let db_file = ChunkyFile::create("test_list_add_entry_unique.db", DbHeadChunk::new("test", "tester")).unwrap();
+
+        let mut list = List::new(db_file, structure, 0).unwrap();
+        let user = |email: Type, name: &str| Entry::new(UuidV4::new(), vec![Field::new("email", email), Field::new("name", Type::S(Some(S::new(name))))]).unwrap();
+        let email = |email: &str| Type::S(Some(S::new(email)));
+
+        let mut users = Vec::<Entry>::new();
+
+        for i in 0..10
+        {
+            let entry = user(email(&format!("user{}@example.com", i)), "Test");
+            users.push(entry.clone());
+            list.add_entry(entry).unwrap();
+        }
+
+        // Names aren't unique, and neither are nulls
+        list.add_entry(user(email("other@example.com"), "Test")).unwrap();
+        list.add_entry(user(Type::S(None), "Test")).unwrap();
+        list.add_entry(user(Type::S(None), "Test")).unwrap();
+
+        for (i, existing) in users.iter().enumerate()
+        {
+            let error = list.add_entry(user(email(&format!("user{}@example.com", i)), "Copy")).unwrap_err();
+            assert_eq!(*error.downcast::<StructureError>().unwrap(), StructureError::Duplicate { field_id: "email".to_string(), existing: existing.uuid.clone() });
+        }
+
+        // A repeated ID can't sneak a copy past the check
+        let entry = Entry::new(UuidV4::new(), vec![Field::new("email", Type::S(None)), Field::new("email", email("user0@example.com")), Field::new("name", Type::S(Some(S::new("Copy"))))]).unwrap();
+        let error = list.add_entry(entry).unwrap_err();
+        assert_eq!(*error.downcast::<StructureError>().unwrap(), StructureError::Repeated { field_id: "email".to_string() });
+        assert_eq!(list.find("email", &email("user0@example.com")).unwrap().len(), 1);
+
+        drop(list);
+
+        remove_file("test_list_add_entry_unique.db").unwrap();
+    }
+
+    #[test]
+    fn test_list_find()
+    {
+        let structure = Structure::new("users", vec!
+        [
+            Requirement::new("name", std::mem::discriminant(&Type::S(None))),
+            Requirement::new("age", std::mem::discriminant(&Type::I(None))),
+        ]).unwrap();
+        let _ = remove_file("test_list_find.db");
+        let db_file = ChunkyFile::create("test_list_find.db", DbHeadChunk::new("test", "tester")).unwrap();
+
+        let mut list = List::new(db_file, structure, 0).unwrap();
+        let user = |name: &str, age: Type| Entry::new(UuidV4::new(), vec![Field::new("name", Type::S(Some(S::new(name)))), Field::new("age", age)]).unwrap();
+
+        let mut thirties = Vec::<UuidV4>::new();
+
+        for i in 0..40
+        {
+            let entry = user(&format!("user{}", i), Type::I(Some(I::new(20 + i % 20))));
+
+            if i % 20 == 10
+            {
+                thirties.push(entry.uuid.clone());
+            }
+
+            list.add_entry(entry).unwrap();
+        }
+
+        list.add_entry(user("nobody", Type::I(None))).unwrap();
+
+        let found = list.find("age", &Type::I(Some(I::new(30)))).unwrap();
+        let mut found_uuids: Vec<UuidV4> = found.iter().map(|entry| entry.uuid.clone()).collect();
+        found_uuids.sort_by_key(|uuid| uuid.to_string());
+        thirties.sort_by_key(|uuid| uuid.to_string());
+        assert_eq!(found_uuids, thirties);
+
+        let found = list.find("name", &Type::S(Some(S::new("user7")))).unwrap();
+        assert_eq!(found.len(), 1);
+        assert_eq!(found[0].get_field("age").unwrap().value, Type::I(Some(I::new(27))));
+
+        assert!(list.find("name", &Type::S(Some(S::new("user40")))).unwrap().is_empty());
+        assert!(list.find("age", &Type::I(None)).unwrap().is_empty());
+
+        drop(list);
+
+        remove_file("test_list_find.db").unwrap();
+    }
+
+    #[test]
+    fn test_list_indexes()
+    {
+        let _ = remove_file("test_list_indexes.db");
+        let db_file = ChunkyFile::create("test_list_indexes.db", DbHeadChunk::new("test", "tester")).unwrap();
+
+        let structure = Structure::new("users", vec!
+        [
+            Requirement::new("email", std::mem::discriminant(&Type::S(None))).unique(),
+            Requirement::new("name", std::mem::discriminant(&Type::S(None))),
+            Requirement::new("age", std::mem::discriminant(&Type::I(None))),
+        ]).unwrap();
+        let user = |i: i64, age: Type| Entry::new(UuidV4::new(), vec![Field::new("email", Type::S(Some(S::new(&format!("user{}@example.com", i))))), Field::new("name", Type::S(Some(S::new("Test")))), Field::new("age", age)]).unwrap();
+        let age = |age: i64| Type::I(Some(I::new(age)));
+        let uuids = |entries: &[Entry]| -> Vec<String>
+        {
+            let mut uuids: Vec<String> = entries.iter().map(|entry| entry.uuid.to_string()).collect();
+            uuids.sort();
+            return uuids;
+        };
+
+        // Unique fields are indexed from the start, as lazy as the UUID tree
+        assert!(List::new(db_file.try_clone().unwrap(), structure.clone(), LAZY_AVL_CONST::LAZE_MAX + 1).is_err());
+        let mut list = List::new(db_file.try_clone().unwrap(), structure, 2).unwrap();
+        assert_eq!(list.indexed_fields(), vec!["email"]);
+        assert_eq!(list.index("email").unwrap().laze(), 2);
+
+        let mut users = Vec::<Entry>::new();
+
+        for i in 0..30
+        {
+            let entry = user(i, age(i % 10));
+            users.push(entry.clone());
+            list.add_entry(entry).unwrap();
+        }
+
+        list.add_entry(user(30, Type::I(None))).unwrap();
+
+        // Without an index every entry is read, with one only the tree is walked, either way the answer is the same
+        let scanned = list.find("age", &age(4)).unwrap();
+        let scanned_range = list.range("age", Bound::Included(&age(3)), Bound::Excluded(&age(6))).unwrap();
+        let scanned_all = list.range("age", Bound::Unbounded, Bound::Unbounded).unwrap();
+        assert_eq!(scanned.len(), 3);
+        assert_eq!(scanned_range.len(), 9);
+        assert_eq!(scanned_all.len(), 30); // The null isn't in range
+
+        assert!(list.create_index("age", LAZY_AVL_CONST::LAZE_MAX + 1).is_err());
+        list.create_index("age", 3).unwrap();
+        assert_eq!(list.indexed_fields(), vec!["email", "age"]);
+        list.index("age").unwrap().check().unwrap();
+
+        assert_eq!(uuids(&list.find("age", &age(4)).unwrap()), uuids(&scanned));
+        assert_eq!(uuids(&list.range("age", Bound::Included(&age(3)), Bound::Excluded(&age(6))).unwrap()), uuids(&scanned_range));
+
+        let indexed_all = list.range("age", Bound::Unbounded, Bound::Unbounded).unwrap();
+        assert_eq!(uuids(&indexed_all), uuids(&scanned_all));
+
+        // Both come back from least to greatest
+        let ages = |entries: &[Entry]| -> Vec<Type> { entries.iter().map(|entry| entry.get_field("age").unwrap().value.clone()).collect() };
+        assert_eq!(ages(&indexed_all), ages(&scanned_all));
+        assert!(ages(&indexed_all).windows(2).all(|pair| pair[0] <= pair[1]));
+
+        assert!(list.create_index("age", 0).is_err()); // Already indexed
+        assert!(list.create_index("height", 0).is_err()); // Not declared
+
+        // The index keeps up with the list
+        let new = user(31, age(4));
+        list.add_entry(new.clone()).unwrap();
+        assert!(list.remove_entry(&users[4].uuid).unwrap());
+
+        let found = list.find("age", &age(4)).unwrap();
+        assert_eq!(found.len(), 3);
+        assert!(found.contains(&new));
+        assert!(!found.contains(&users[4]));
+        list.index("age").unwrap().check().unwrap();
+
+        // Entries that skip the structure check still can't link a field twice
+        let twice = Entry::new(UuidV4::new(), vec![Field::new("age", age(4)), Field::new("age", age(5))]).unwrap();
+        assert!(list.insert_entry(twice, true).is_err());
+        assert_eq!(list.find("age", &age(4)).unwrap().len(), 3);
+        assert_eq!(list.find("age", &age(5)).unwrap().len(), 3);
+        list.index("age").unwrap().check().unwrap();
+        list.index("email").unwrap().check().unwrap();
+
+        // Unique fields need their index, fields without one have nothing to drop
+        assert!(list.drop_index("email").is_err());
+        assert!(!list.drop_index("name").unwrap());
+
+        let age_head = list.index("age").unwrap().head;
+        drop(list);
+
+        // Indexes come back when the list is reopened, but not ones that were never finished
+        let mut db_file = ChunkyFile::open("test_list_indexes.db").unwrap();
+        db_file.add_index_chunk(&IndexChunk::new("users", "name", 0)).unwrap();
+
+        let mut list = List::open(db_file.try_clone().unwrap(), "users").unwrap();
+        assert_eq!(list.indexed_fields(), vec!["email", "age"]);
+        assert_eq!(list.index("age").unwrap().head, age_head);
+        assert_eq!(list.index("age").unwrap().laze(), 3);
+        assert_eq!(list.uuid_tree.borrow().laze(), 2);
+        assert_eq!(list.find("age", &age(4)).unwrap().len(), 3);
+
+        assert!(list.drop_index("age").unwrap());
+        assert!(list.index("age").is_none());
+        assert_eq!(list.find("age", &age(4)).unwrap().len(), 3);
+        drop(list);
+
+        let mut lists = List::open_all(&mut db_file).unwrap();
+        assert_eq!(lists[0].indexed_fields(), vec!["email"]);
+
+        // A dropped index can be created again
+        lists[0].create_index("age", 0).unwrap();
+        assert_eq!(lists[0].find("age", &age(4)).unwrap().len(), 3);
+        lists[0].index("age").unwrap().check().unwrap();
+
+        drop(lists);
+        drop(db_file);
+
+        remove_file("test_list_indexes.db").unwrap();
+    }
+
+    #[test]
+    fn test_list_bins()
+    {
+        let structure = Structure::new("files", vec!
+        [
+            Requirement::new("data", std::mem::discriminant(&Type::Bin(None))),
+            Requirement::new("parts", std::mem::discriminant(&Type::List(None))),
+        ]).unwrap();
+        let _ = remove_file("test_list_bins.db");
+        let db_file = ChunkyFile::create("test_list_bins.db", DbHeadChunk::new("test", "tester")).unwrap();
+
+        let mut list = List::new(db_file, structure, 0).unwrap();
+        let big: Vec<u8> = (0..1000u32).map(|i| (i * 7) as u8).collect();
+        let small = vec![1u8, 2, 3];
+        let binary = |data: &[u8]| Type::Bin(Some(Bin::new(data)));
+
+        // Nothing writes big values inline
+        assert!(Field::new("data", binary(&big)).to_bytes().is_err());
+
+        let entry = Entry::new(UuidV4::new(), vec![Field::new("data", binary(&big)), Field::new("parts", Type::List(Some(vec![binary(&small), binary(&big)])))]).unwrap();
+        list.add_entry(entry.clone()).unwrap();
+
+        // Big values are moved out of the entry when it is written, small ones stay inline
+        let stored = list.get_entry(&entry.uuid).unwrap().unwrap();
+        let read = |list: &mut List, value: &Type| -> Vec<u8>
+        {
+            let mut data = Vec::<u8>::new();
+
+            match value
+            {
+                Type::Bin(Some(bin)) => list.db_file.bin_reader(bin).read_to_end(&mut data).unwrap(),
+                _ => panic!("Not a binary value!"),
+            };
+
+            return data;
+        };
+
+        let data = stored.get_field("data").unwrap().value.clone();
+        assert!(matches!(data, Type::Bin(Some(Bin::Overflow { length: 1000, .. }))));
+        assert_eq!(read(&mut list, &data), big);
+
+        let parts = match stored.get_field("parts").unwrap().value.clone()
+        {
+            Type::List(Some(parts)) => parts,
+            _ => panic!("Not a list!"),
+        };
+
+        assert_eq!(parts[0], binary(&small));
+        assert!(matches!(parts[1], Type::Bin(Some(Bin::Overflow { length: 1000, .. }))));
+        assert_eq!(read(&mut list, &parts[1]), big);
+
+        drop(list);
+
+        remove_file("test_list_bins.db").unwrap();
+    }
+
+    #[test]
+    fn test_list_remove_entry()
+    {
+        let structure = Structure::new("users", vec!
+        [
+            Requirement::new("email", std::mem::discriminant(&Type::S(None))).unique(),
+            Requirement::new("age", std::mem::discriminant(&Type::I(None))),
+        ]).unwrap();
+        let _ = remove_file("test_list_remove_entry.db");
+        let db_file = ChunkyFile::create("test_list_remove_entry.db", DbHeadChunk::new("test", "tester")).unwrap();
+
+        let mut list = List::new(db_file, structure, 0).unwrap();
+        let email = |i: i64| Type::S(Some(S::new(&format!("user{}@example.com", i))));
+        let mut users = Vec::<Entry>::new();
+
+        for i in 0..30
+        {
+            let entry = Entry::new(UuidV4::new(), vec![Field::new("email", email(i)), Field::new("age", Type::I(Some(I::new(i % 3))))]).unwrap();
+            users.push(entry.clone());
+            list.add_entry(entry).unwrap();
+        }
+
+        for user in users.iter().step_by(2)
+        {
+            assert!(list.remove_entry(&user.uuid).unwrap());
+            assert!(!list.remove_entry(&user.uuid).unwrap());
+        }
+
+        list.index("email").unwrap().check().unwrap();
+        list.uuid_tree.borrow_mut().check().unwrap();
+
+        for (i, user) in users.iter().enumerate()
+        {
+            let found = list.find("email", &email(i as i64)).unwrap();
+
+            if i % 2 == 0
+            {
+                assert_eq!(list.get_entry(&user.uuid).unwrap(), None);
+                assert!(found.is_empty());
+            }
+            else
+            {
+                assert_eq!(list.get_entry(&user.uuid).unwrap().as_ref(), Some(user));
+                assert_eq!(found, vec![user.clone()]);
+            }
+        }
+
+        assert_eq!(list.find("age", &Type::I(Some(I::new(1)))).unwrap().len(), 5);
+
+        // A removed entry's unique values are free again
+        list.add_entry(Entry::new(UuidV4::new(), vec![Field::new("email", email(0)), Field::new("age", Type::I(None))]).unwrap()).unwrap();
+        assert!(list.add_entry(Entry::new(UuidV4::new(), vec![Field::new("email", email(1)), Field::new("age", Type::I(None))]).unwrap()).is_err());
+
+        drop(list);
+
+        remove_file("test_list_remove_entry.db").unwrap();
+    }
+
+    #[test]
+    fn test_list_entries_survive_tree()
+    {
+        let structure = Structure::new("test", vec![Requirement::new("id", std::mem::discriminant(&Type::S(None)))]).unwrap();
+        let _ = remove_file("test_list_entries_survive_tree.db");
+        let db_file = ChunkyFile::create("test_list_entries_survive_tree.db", DbHeadChunk::new("test", "tester")).unwrap();
+
+        let mut list = List::new(db_file, structure, 0).unwrap();
+        list.create_index("id", 0).unwrap();
+        let mut entry_positions = Vec::<u64>::new();
+
+        for i in 0..10
+        {
+            let fields = vec![Field::new("id", Type::S(Some(S::new(&format!("Test{}", i)))))];
+            entry_positions.push(list.db_file.size as u64);
+            list.add_entry(Entry::new(UuidV4::new(), fields).unwrap()).unwrap();
+        }
+
+        // Linking the fields into the tree rewrites their chunks, which must still pass their CRC checks
+        for (i, entry_position) in entry_positions.iter().enumerate()
+        {
+            let (_, entry_chunk) = list.db_file.read_entry_chunk(*entry_position).unwrap();
+            assert_eq!(entry_chunk.fields[0].value, Type::S(Some(S::new(&format!("Test{}", i)))));
+        }
+
+        drop(list);
+
+        remove_file("test_list_entries_survive_tree.db").unwrap();
+    }
+
+    #[test]
+    fn test_list_get_entry()
+    {
+        let structure = Structure::new("test", vec![Requirement::new("id", std::mem::discriminant(&Type::S(None)))]).unwrap();
+        let _ = remove_file("test_list_get_entry.db");
+        let db_file = ChunkyFile::create("test_list_get_entry.db", DbHeadChunk::new("test", "tester")).unwrap();
+
+        let mut list = List::new(db_file, structure, 0).unwrap();
+        let mut entries = Vec::<Entry>::new();
+
+        for i in 0..10
+        {
+            let entry = Entry::new(UuidV4::new(), vec![Field::new("id", Type::S(Some(S::new(&format!("Test{}", i)))))]).unwrap();
+            entries.push(entry.clone());
+            list.add_entry(entry).unwrap();
+        }
+
+        for entry in entries
+        {
+            assert_eq!(list.get_entry(&entry.uuid).unwrap(), Some(entry));
+        }
+
+        assert_eq!(list.get_entry(&UuidV4::new()).unwrap(), None);
+
+        drop(list);
+
+        remove_file("test_list_get_entry.db").unwrap();
+    }
+
+    #[test]
+    fn test_list_references()
+    {
+        let _ = remove_file("test_list_references.db");
+        let db_file = ChunkyFile::create("test_list_references.db", DbHeadChunk::new("test", "tester")).unwrap();
+
+        let ref_type = std::mem::discriminant(&Type::Ref(None));
+        let people_structure = Structure::new("people", vec!
+        [
+            Requirement::new("name", std::mem::discriminant(&Type::S(None))),
+            Requirement::new("friend", ref_type).references("people"),
+        ]).unwrap();
+        let review = Structure::new("review", vec![Requirement::new("reviewer", ref_type).references("people")]).unwrap();
+        let posts_structure = Structure::new("posts", vec!
+        [
+            Requirement::new("author", ref_type).references("people"),
+            Requirement::new("editors", std::mem::discriminant(&Type::List(None))).elements(ref_type).references("people"),
+            Requirement::new("review", std::mem::discriminant(&Type::Doc(None))).structure(review.clone()),
+            Requirement::new("reviews", std::mem::discriminant(&Type::List(None))).elements(std::mem::discriminant(&Type::Doc(None))).structure(review),
+        ]).unwrap();
+
+        let mut people = List::new(db_file.try_clone().unwrap(), people_structure, 0).unwrap();
+        let mut posts = List::new(db_file, posts_structure, 0).unwrap();
+
+        // People can reference themselves and each other
+        let alice = Entry::new(UuidV4::new(), vec![Field::new("name", Type::S(Some(S::new("Alice")))), Field::new("friend", Type::Ref(None))]).unwrap();
+        let mut bob = Entry::new(UuidV4::new(), vec![Field::new("name", Type::S(Some(S::new("Bob")))), Field::new("friend", Type::Ref(Some(alice.uuid.clone())))]).unwrap();
+        let mut carol = Entry::new(UuidV4::new(), vec![Field::new("name", Type::S(Some(S::new("Carol"))))]).unwrap();
+        carol.fields.push(Field::new("friend", Type::Ref(Some(carol.uuid.clone()))));
+
+        assert!(people.add_entry(bob.clone()).is_err()); // Alice isn't there yet
+        people.add_entry(alice.clone()).unwrap();
+        people.add_entry(bob.clone()).unwrap();
+        people.add_entry(carol.clone()).unwrap();
+
+        assert_eq!(bob.resolve("friend", &mut people).unwrap(), Some(alice.clone()));
+        assert_eq!(alice.resolve("friend", &mut people).unwrap(), None);
+        assert!(alice.resolve("name", &mut people).is_err());
+
+        // Posts can only reference people once the lists are linked
+        let post = Entry::new(UuidV4::new(), vec!
+        [
+            Field::new("author", Type::Ref(Some(bob.uuid.clone()))),
+            Field::new("editors", Type::List(Some(vec![Type::Ref(Some(alice.uuid.clone())), Type::Ref(Some(carol.uuid.clone()))]))),
+        ]).unwrap();
+
+        assert!(posts.add_entry(post.clone()).is_err());
+        posts.link(&people);
+        posts.add_entry(post.clone()).unwrap();
+
+        assert_eq!(post.resolve("author", &mut people).unwrap(), Some(bob.clone()));
+        assert_eq!(post.resolve("editors.1", &mut people).unwrap(), Some(carol));
+
+        // Dangling references are refused, and so are references to entries that were never added
+        let dangling = Entry::new(UuidV4::new(), vec![Field::new("author", Type::Ref(Some(UuidV4::new())))]).unwrap();
+        assert!(posts.add_entry(dangling).is_err());
+
+        bob.fields[1] = Field::new("friend", Type::Ref(Some(post.uuid.clone())));
+        assert!(people.add_entry(bob).is_err());
+
+        // References nested in documents, and in lists of documents, are checked too
+        let reviewed_by = |uuid: &UuidV4| Type::Doc(Some(vec![Field::new("reviewer", Type::Ref(Some(uuid.clone())))]));
+        let reviewed = |review: Type, reviews: Vec<Type>| Entry::new(UuidV4::new(), vec![Field::new("review", review), Field::new("reviews", Type::List(Some(reviews)))]).unwrap();
+
+        let error = posts.add_entry(reviewed(reviewed_by(&UuidV4::new()), vec![])).unwrap_err();
+        assert!(error.to_string().contains("review.reviewer"), "{}", error);
+        let error = posts.add_entry(reviewed(reviewed_by(&alice.uuid), vec![reviewed_by(&alice.uuid), reviewed_by(&post.uuid)])).unwrap_err();
+        assert!(error.to_string().contains("reviews.1.reviewer"), "{}", error);
+        posts.add_entry(reviewed(reviewed_by(&alice.uuid), vec![reviewed_by(&alice.uuid), reviewed_by(&alice.uuid)])).unwrap();
+
+        drop(people);
+        drop(posts);
+
+        remove_file("test_list_references.db").unwrap();
+    }
+
+    #[test]
+    fn test_list_reopen()
+    {
+        let _ = remove_file("test_list_reopen.db");
+        let db_file = ChunkyFile::create("test_list_reopen.db", DbHeadChunk::new("test", "tester")).unwrap();
+
+        let people_structure = Structure::new("people", vec!
+        [
+            Requirement::new("name", std::mem::discriminant(&Type::S(None))).required().constrain(Constraint::custom("capitalised", |_| true)),
+            Requirement::new("age", std::mem::discriminant(&Type::I(None))).constrain(Constraint::Min(I::new(0))),
+        ]).unwrap();
+        let posts_structure = Structure::new("posts", vec![Requirement::new("author", std::mem::discriminant(&Type::Ref(None))).references("people")]).unwrap();
+
+        let mut people = List::new(db_file.try_clone().unwrap(), people_structure.clone(), 0).unwrap();
+        List::new(db_file.try_clone().unwrap(), posts_structure.clone(), 0).unwrap();
+        assert!(List::new(db_file.try_clone().unwrap(), posts_structure.clone(), 0).is_err()); // The ID is taken
+
+        people.add_entry(Entry::new(UuidV4::new(), vec![Field::new("name", Type::S(Some(S::new("Alice"))))]).unwrap()).unwrap();
+
+        drop(people);
+        drop(db_file);
+
+        let mut db_file = ChunkyFile::open("test_list_reopen.db").unwrap();
+        let mut lists = List::open_all(&mut db_file).unwrap();
+
+        assert_eq!(lists.len(), 2);
+        assert_eq!(lists[1].structure, posts_structure);
+        assert_eq!(lists[0].structure.to_fields(), people_structure.to_fields());
+
+        // The structure still holds, custom constraints refuse everything until they are bound
+        let entry = |name: &str, age: i64| Entry::new(UuidV4::new(), vec![Field::new("name", Type::S(Some(S::new(name)))), Field::new("age", Type::I(Some(I::new(age))))]).unwrap();
+        assert!(lists[0].add_entry(entry("Bob", -1)).is_err());
+        assert!(lists[0].add_entry(entry("Bob", 30)).is_err());
+
+        lists[0].structure.bind("capitalised", |value| matches!(value, Type::S(Some(name)) if name.as_str().starts_with(char::is_uppercase)));
+        assert!(lists[0].add_entry(entry("bob", 30)).is_err());
+        lists[0].add_entry(entry("Bob", 30)).unwrap();
+
+        let posts = List::open(db_file.try_clone().unwrap(), "posts").unwrap();
+        assert_eq!(posts.structure, posts_structure);
+        assert!(List::open(db_file.try_clone().unwrap(), "comments").is_err());
+
+        drop(lists);
+        drop(posts);
+        drop(db_file);
+
+        remove_file("test_list_reopen.db").unwrap();
+    }
+
+    #[test]
+    fn test_list_reopen_trees()
+    {
+        let _ = remove_file("test_list_reopen_trees.db");
+        let db_file = ChunkyFile::create("test_list_reopen_trees.db", DbHeadChunk::new("test", "tester")).unwrap();
+
+        let structure = Structure::new("users", vec!
+        [
+            Requirement::new("email", std::mem::discriminant(&Type::S(None))).unique(),
+            Requirement::new("age", std::mem::discriminant(&Type::I(None))),
+        ]).unwrap();
+        let user = |i: i64| Entry::new(UuidV4::new(), vec![Field::new("email", Type::S(Some(S::new(&format!("user{}@example.com", i))))), Field::new("age", Type::I(Some(I::new(i % 7))))]).unwrap();
+
+        let mut list = List::new(db_file.try_clone().unwrap(), structure, 0).unwrap();
+        let mut users = Vec::<Entry>::new();
+
+        for i in 0..40
+        {
+            let entry = user(i);
+            users.push(entry.clone());
+            list.add_entry(entry).unwrap();
+        }
+
+        assert_eq!(list.entry_count, 40);
+        assert!(list.remove_entry(&users[0].uuid).unwrap());
+        assert_eq!(list.entry_count, 39);
+
+        let heads = (list.index("email").unwrap().head, list.uuid_tree.borrow().head);
+        drop(list);
+
+        // Reopening picks the trees up where they were left
+        let mut list = List::open(db_file.try_clone().unwrap(), "users").unwrap();
+        assert_eq!((list.index("email").unwrap().head, list.uuid_tree.borrow().head), heads);
+        assert_eq!(list.entry_count, 39);
+
+        list.index("email").unwrap().check().unwrap();
+        list.uuid_tree.borrow_mut().check().unwrap();
+
+        assert_eq!(list.get_entry(&users[0].uuid).unwrap(), None);
+        assert_eq!(list.get_entry(&users[1].uuid).unwrap().as_ref(), Some(&users[1]));
+        assert_eq!(list.find("age", &Type::I(Some(I::new(3)))).unwrap().len(), 6);
+        assert!(list.add_entry(user(5)).is_err()); // Still unique
+
+        // Changes made after reopening stick too
+        list.add_entry(user(0)).unwrap();
+        assert!(list.remove_entry(&users[1].uuid).unwrap());
+        drop(list);
+
+        let mut lists = List::open_all(&mut db_file.try_clone().unwrap()).unwrap();
+        assert_eq!(lists[0].entry_count, 39);
+        assert_eq!(lists[0].get_entry(&users[1].uuid).unwrap(), None);
+        assert_eq!(lists[0].find("email", &Type::S(Some(S::new("user0@example.com")))).unwrap().len(), 1);
+        lists[0].index("email").unwrap().check().unwrap();
+
+        drop(lists);
+        drop(db_file);
+
+        remove_file("test_list_reopen_trees.db").unwrap();
+    }
+
+    #[test]
+    fn test_list_evolve()
+    {
+        let _ = remove_file("test_list_evolve.db");
+        let db_file = ChunkyFile::create("test_list_evolve.db", DbHeadChunk::new("test", "tester")).unwrap();
+
+        let v1 = Structure::new("people", vec!
+        [
+            Requirement::new("nick", std::mem::discriminant(&Type::S(None))),
+            Requirement::new("age", std::mem::discriminant(&Type::S(None))),
+        ]).unwrap();
+        let v2 = Structure::new("people", vec!
+        [
+            Requirement::new("name", std::mem::discriminant(&Type::S(None))).required().unique(),
+            Requirement::new("age", std::mem::discriminant(&Type::I(None))),
+            Requirement::new("active", std::mem::discriminant(&Type::B(None))).default(Type::B(Some(B::new(true)))),
+        ]).unwrap().versioned(2).unwrap();
+        let v3 = Structure::new("people", vec!
+        [
+            Requirement::new("name", std::mem::discriminant(&Type::S(None))).required().unique(),
+            Requirement::new("age", std::mem::discriminant(&Type::I(None))).unique(),
+            Requirement::new("active", std::mem::discriminant(&Type::B(None))).default(Type::B(Some(B::new(true)))),
+        ]).unwrap().versioned(3).unwrap();
+        let migration = || Migration::new(1, 2).unwrap().rename("nick", "name").retype("age", |value| match value
+        {
+            Type::S(Some(age)) => return Ok(Type::I(Some(age.as_str().parse::<I>()?))),
+            _ => return Ok(Type::I(None)),
+        });
+
+        let mut list = List::new(db_file.try_clone().unwrap(), v1.clone(), 0).unwrap();
+        let old = |nick: &str, age: &str| Entry::new(UuidV4::new(), vec![Field::new("nick", Type::S(Some(S::new(nick)))), Field::new("age", Type::S(Some(S::new(age))))]).unwrap();
+        let entries = vec![old("Alice", "30"), old("Bob", "41"), old("Carol", "52")];
+
+        for entry in &entries
+        {
+            list.add_entry(entry.clone()).unwrap();
+        }
+
+        list.create_index("nick", 0).unwrap();
+        list.create_index("age", 0).unwrap();
+
+        assert!(list.evolve(v1.clone()).is_err()); // Same version
+        assert!(list.evolve(Structure::new("posts", vec![]).unwrap().versioned(2).unwrap()).is_err());
+        list.evolve(v2.clone()).unwrap();
+        assert_eq!(list.indexed_fields(), vec!["age", "name"]); // The new version has no nick, and a unique name
+        assert!(list.add_migration(Migration::new(2, 3).unwrap()).is_err()); // Past the current version
+
+        // Without a migration old entries don't meet the new structure
+        assert!(list.get_entry(&entries[0].uuid).is_err());
+
+        list.add_migration(migration()).unwrap();
+
+        let upcast = list.get_entry(&entries[1].uuid).unwrap().unwrap();
+        assert_eq!(upcast.get_field("name").unwrap().value, Type::S(Some(S::new("Bob"))));
+        assert_eq!(upcast.get_field("age").unwrap().value, Type::I(Some(I::new(41))));
+        assert_eq!(upcast.get_field("active").unwrap().value, Type::B(Some(B::new(true))));
+        assert!(list.structure.meets(&upcast.fields));
+
+        // The indexes hold the old values, so until every entry is upcast lookups and unique fields go by reading them all
+        assert_eq!(list.find("age", &Type::I(Some(I::new(41)))).unwrap()[0].uuid, entries[1].uuid);
+        assert_eq!(list.find("name", &Type::S(Some(S::new("Carol")))).unwrap()[0].uuid, entries[2].uuid);
+
+        let error = list.add_entry(Entry::new(UuidV4::new(), vec![Field::new("name", Type::S(Some(S::new("Alice"))))]).unwrap()).unwrap_err();
+        assert_eq!(*error.downcast::<StructureError>().unwrap(), StructureError::Duplicate { field_id: "name".to_string(), existing: entries[0].uuid.clone() });
+
+        // New entries are written under the new version
+        let new = Entry::new(UuidV4::new(), vec![Field::new("name", Type::S(Some(S::new("Dave"))))]).unwrap();
+        list.add_entry(new.clone()).unwrap();
+
+        let version_of = |list: &mut List, uuid: &UuidV4| -> u64
+        {
+            let uuid_point = list.uuid_tree.borrow_mut().search(&Field::new("uuid", Type::Ref(Some(uuid.clone())))).unwrap().unwrap();
+            return list.db_file.read_entry_chunk(ChunkyFile::chunk_pos(uuid_point)).unwrap().1.version;
+        };
+
+        assert_eq!(version_of(&mut list, &entries[0].uuid), 1);
+        assert_eq!(version_of(&mut list, &new.uuid), 2);
+
+        // Rewriting brings every old entry up to date, and only has to happen once
+        let before = list.get_entry(&entries[0].uuid).unwrap();
+        assert_eq!(list.migrate_all().unwrap(), 3);
+        assert_eq!(list.migrate_all().unwrap(), 0);
+
+        for entry in entries.iter().chain([&new])
+        {
+            assert_eq!(version_of(&mut list, &entry.uuid), 2);
+        }
+
+        assert_eq!(list.get_entry(&entries[0].uuid).unwrap(), before);
+        assert_eq!(list.find("age", &Type::I(Some(I::new(41)))).unwrap()[0].uuid, entries[1].uuid);
+        assert_eq!(list.find("name", &Type::S(Some(S::new("Carol")))).unwrap()[0].uuid, entries[2].uuid);
+        list.index("age").unwrap().check().unwrap();
+        list.index("name").unwrap().check().unwrap();
+        assert_eq!(list.get_entry(&new.uuid).unwrap().unwrap().fields, new.fields.iter().cloned().chain([Field::new("active", Type::B(Some(B::new(true))))]).collect::<Vec<Field>>());
+
+        // The latest version of the structure comes back when the list is reopened, along with the rewritten trees
+        drop(list);
+
+        let mut list = List::open(db_file.try_clone().unwrap(), "people").unwrap();
+        assert_eq!(list.structure, v2);
+        assert_eq!(list.entry_count, 4);
+        assert_eq!(list.get_entry(&entries[0].uuid).unwrap(), before);
+        assert_eq!(list.find("age", &Type::I(Some(I::new(52)))).unwrap()[0].uuid, entries[2].uuid);
+
+        // A field that already has duplicates can't become unique, indexed or not, and the list stays as it was
+        let erin = Entry::new(UuidV4::new(), vec![Field::new("name", Type::S(Some(S::new("Erin")))), Field::new("age", Type::I(Some(I::new(41))))]).unwrap();
+        list.add_entry(erin.clone()).unwrap();
+
+        let duplicate = |list: &mut List| -> bool
+        {
+            let error = list.evolve(v3.clone()).unwrap_err();
+            return matches!(*error.downcast::<StructureError>().unwrap(), StructureError::Duplicate { .. });
+        };
+
+        assert!(duplicate(&mut list));
+        assert_eq!(list.structure, v2);
+        assert_eq!(list.indexed_fields(), vec!["age", "name"]);
+
+        assert!(list.drop_index("age").unwrap());
+        assert!(duplicate(&mut list));
+        assert_eq!(list.structure, v2);
+        assert_eq!(list.indexed_fields(), vec!["name"]);
+
+        assert!(list.remove_entry(&erin.uuid).unwrap());
+        list.evolve(v3.clone()).unwrap();
+        assert_eq!(list.indexed_fields(), vec!["name", "age"]);
+        drop(list);
+
+        let list = List::open(db_file.try_clone().unwrap(), "people").unwrap();
+        assert_eq!(list.structure, v3);
+
+        drop(list);
+        drop(db_file);
+
+        remove_file("test_list_evolve.db").unwrap();
+    }
+
+    #[test]
+    fn test_list_migration_chain()
+    {
+        let _ = remove_file("test_list_migration_chain.db");
+        let db_file = ChunkyFile::create("test_list_migration_chain.db", DbHeadChunk::new("test", "tester")).unwrap();
+
+        let structure = |version: u64| Structure::new("counts", vec![Requirement::new("n", std::mem::discriminant(&Type::I(None)))]).unwrap().versioned(version).unwrap();
+        let number = |n: i64| Type::I(Some(I::new(n)));
+        let entry = |n: i64| Entry::new(UuidV4::new(), vec![Field::new("n", number(n))]).unwrap();
+        let scale = |factor: i64, offset: i64| move |value: &Type| -> Result<Type, Box<dyn Error>>
+        {
+            match value
+            {
+                Type::I(Some(n)) => return Ok(Type::I(Some(I::new(n.to_i64().unwrap() * factor + offset)))),
+                _ => return Ok(value.clone()),
+            }
+        };
+
+        // One entry under each version
+        let mut list = List::new(db_file.try_clone().unwrap(), structure(1), 0).unwrap();
+        let (v1, v2, v3) = (entry(1), entry(1), entry(1));
+        list.add_entry(v1.clone()).unwrap();
+        list.evolve(structure(2)).unwrap();
+        list.add_entry(v2.clone()).unwrap();
+        list.evolve(structure(3)).unwrap();
+        list.add_entry(v3.clone()).unwrap();
+
+        // Overlapping migrations, entries from version 1 jump straight to 3 and never go through 2
+        list.add_migration(Migration::new(1, 3).unwrap().retype("n", scale(10, 0))).unwrap();
+        list.add_migration(Migration::new(2, 3).unwrap().retype("n", scale(1, 1))).unwrap();
+        assert!(list.add_migration(Migration::new(1, 2).unwrap()).is_err()); // There already is one from version 1
+
+        let n = |list: &mut List, entry: &Entry| list.get_entry(&entry.uuid).unwrap().unwrap().get_field("n").unwrap().value.clone();
+        assert_eq!(n(&mut list, &v1), number(10));
+        assert_eq!(n(&mut list, &v2), number(2));
+        assert_eq!(n(&mut list, &v3), number(1));
+
+        assert_eq!(list.migrate_all().unwrap(), 2);
+        assert_eq!(n(&mut list, &v1), number(10));
+        assert_eq!(n(&mut list, &v2), number(2));
+
+        drop(list);
+        drop(db_file);
+
+        remove_file("test_list_migration_chain.db").unwrap();
+    }
+}
@@ -4,106 +4,1113 @@
 
 
 use std::mem::*;
+use std::error::Error;
+use std::fmt;
+use std::rc::Rc;
+use regex_lite::Regex;
+use simple_error::*;
 use crate::dbio::dbfield::*;
+use crate::dbio::dbuuid::UuidV4;
 use crate::apetypes::*;
 
 
 
+// Enums!
+//
+
+
+
+// dbio::dbstruct::StructureError - Why a set of fields doesn't meet a structure
+//
+// field_id is the path of the offending field, such as "address.city" for fields inside documents.
+#[derive(Debug, Clone, PartialEq)]
+pub enum StructureError
+{
+    Missing { field_id: String }, // A field the structure needs isn't there
+    WrongType { field_id: String }, // The field has the wrong type, a refused null, or nested values that don't meet the requirement
+    NotAllowed { field_id: String }, // The structure has no requirement for the field
+    Constraint { field_id: String, constraint: Constraint }, // The value of the field fails a constraint
+    Duplicate { field_id: String, existing: UuidV4 }, // The field is unique and an existing entry already has the value
+    Repeated { field_id: String }, // More than one field has the ID
+}
+
+impl StructureError
+{
+    // dbio::dbstruct::StructureError::field_id - Get the path of the offending field
+    //
+    pub fn field_id(&self) -> &str
+    {
+        match self
+        {
+            StructureError::Missing { field_id } |
+            StructureError::WrongType { field_id } |
+            StructureError::NotAllowed { field_id } |
+            StructureError::Constraint { field_id, .. } |
+            StructureError::Duplicate { field_id, .. } |
+            StructureError::Repeated { field_id } => return field_id,
+        }
+    }
+
+    // dbio::dbstruct::StructureError::nested - Prefix the offending field with the path of the field it is nested in
+    //
+    // ARGUMENTS:
+    //  prefix: &str - The path of the enclosing document or list element
+    fn nested(self, prefix: &str) -> StructureError
+    {
+        match self
+        {
+            StructureError::Missing { field_id } => return StructureError::Missing { field_id: format!("{}.{}", prefix, field_id) },
+            StructureError::WrongType { field_id } => return StructureError::WrongType { field_id: format!("{}.{}", prefix, field_id) },
+            StructureError::NotAllowed { field_id } => return StructureError::NotAllowed { field_id: format!("{}.{}", prefix, field_id) },
+            StructureError::Constraint { field_id, constraint } => return StructureError::Constraint { field_id: format!("{}.{}", prefix, field_id), constraint: constraint },
+            StructureError::Duplicate { field_id, existing } => return StructureError::Duplicate { field_id: format!("{}.{}", prefix, field_id), existing: existing },
+            StructureError::Repeated { field_id } => return StructureError::Repeated { field_id: format!("{}.{}", prefix, field_id) },
+        }
+    }
+}
+
+impl fmt::Display for StructureError
+{
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result
+    {
+        match self
+        {
+            StructureError::Missing { field_id } => write!(f, "Field {} is missing!", field_id),
+            StructureError::WrongType { field_id } => write!(f, "Field {} has the wrong type!", field_id),
+            StructureError::NotAllowed { field_id } => write!(f, "Field {} is not allowed!", field_id),
+            StructureError::Constraint { field_id, constraint } => write!(f, "Field {} fails constraint {}!", field_id, constraint),
+            StructureError::Duplicate { field_id, existing } => write!(f, "Field {} has the same value as entry {}!", field_id, existing),
+            StructureError::Repeated { field_id } => write!(f, "Field {} is there more than once!", field_id),
+        }
+    }
+}
+
+impl Error for StructureError {}
+
+// dbio::dbstruct::Presence - Whether a field has to be in an entry
+//
+#[derive(Debug, Clone, PartialEq)]
+pub enum Presence
+{
+    Required, // The field must be there
+    Optional, // The field may be left out, unless it can't be null
+    Default(Type), // The field may be left out, and is filled in with the value when it is
+}
+
+// dbio::dbstruct::Constraint - A rule the value of a field has to follow on top of its type
+//
+// Null values aren't checked against constraints, use Requirement::not_null() to refuse them.
+#[derive(Clone)]
+pub enum Constraint
+{
+    Min(I), // Integers must be at least this
+    Max(I), // Integers must be at most this
+    Length { min: usize, max: usize }, // Strings must have between min and max characters, inclusive
+    Pattern(Regex), // Strings must match the regex somewhere, anchor it to match the whole string
+    OneOf(Vec<Type>), // The value must be one of these
+    Custom(String, Rc<dyn Fn(&Type) -> bool>), // The closure must return true, the string names it in errors
+}
+
+impl Constraint
+{
+    // dbio::dbstruct::Constraint::pattern - Create a pattern constraint, failing if the regex doesn't compile
+    //
+    // ARGUMENTS:
+    //  pattern: &str - The regex strings must match
+    pub fn pattern(pattern: &str) -> Result<Constraint, Box<dyn Error>>
+    {
+        return Ok(Constraint::Pattern(Regex::new(pattern)?));
+    }
+
+    // dbio::dbstruct::Constraint::custom - Create a constraint from a closure
+    //
+    // ARGUMENTS:
+    //  name: &str - The name of the constraint, used in errors
+    //  validator: impl Fn(&Type) -> bool - Returns whether a value meets the constraint
+    pub fn custom(name: &str, validator: impl Fn(&Type) -> bool + 'static) -> Constraint
+    {
+        return Constraint::Custom(name.to_string(), Rc::new(validator));
+    }
+
+    // dbio::dbstruct::Constraint::allows - Checks a value against the constraint, values of the wrong type never pass
+    //
+    // ARGUMENTS:
+    //  value: &Type - The value to check
+    pub fn allows(&self, value: &Type) -> bool
+    {
+        match (self, value)
+        {
+            (Constraint::Min(min), Type::I(Some(integer))) => return integer >= min,
+            (Constraint::Max(max), Type::I(Some(integer))) => return integer <= max,
+            (Constraint::Length { min, max }, Type::S(Some(string))) =>
+            {
+                let length = string.as_str().chars().count();
+
+                return length >= *min && length <= *max;
+            }
+            (Constraint::Pattern(regex), Type::S(Some(string))) => return regex.is_match(string.as_str()),
+            (Constraint::OneOf(values), value) => return values.contains(value),
+            (Constraint::Custom(_, validator), value) => return validator(value),
+            _ => return false,
+        }
+    }
+
+    // dbio::dbstruct::Constraint::to_fields - Describe the constraint as fields, for storing it in a schema chunk
+    //
+    // Custom constraints only store their name, the closure has to be bound again once the schema is read back.
+    pub fn to_fields(&self) -> Vec<Field>
+    {
+        let (kind, value) = match self
+        {
+            Constraint::Min(min) => ("min", Type::I(Some(min.clone()))),
+            Constraint::Max(max) => ("max", Type::I(Some(max.clone()))),
+            Constraint::Length { min, max } =>
+            {
+                // A maximum of usize::MAX means there is no maximum, which is stored as null
+                let length = |length: usize| Type::I(Some(I::new(i64::try_from(length).unwrap_or(i64::MAX))));
+                let max = if *max == usize::MAX { Type::I(None) } else { length(*max) };
+
+                ("length", Type::List(Some(vec![length(*min), max])))
+            }
+            Constraint::Pattern(regex) => ("pattern", Type::S(Some(S::new(regex.as_str())))),
+            Constraint::OneOf(values) => ("one_of", Type::List(Some(values.clone()))),
+            Constraint::Custom(name, _) => ("custom", Type::S(Some(S::new(name)))),
+        };
+
+        return vec![Field::new("kind", Type::S(Some(S::new(kind)))), Field::new("value", value)];
+    }
+
+    // dbio::dbstruct::Constraint::from_fields - Rebuild a constraint from the fields written by to_fields()
+    //
+    // Custom constraints come back unbound and refuse every value until Structure::bind() is called.
+    //
+    // ARGUMENTS:
+    //  fields: &[Field] - The fields describing the constraint
+    pub fn from_fields(fields: &[Field]) -> Result<Constraint, Box<dyn Error>>
+    {
+        let length = |value: &Type| -> Result<usize, Box<dyn Error>>
+        {
+            match value
+            {
+                Type::I(None) => return Ok(usize::MAX),
+                Type::I(Some(length)) => match length.to_i64().and_then(|length| usize::try_from(length).ok())
+                {
+                    Some(length) => return Ok(length),
+                    None => bail!("Length constraint out of range!"),
+                },
+                _ => bail!("Length constraint is not an integer!"),
+            }
+        };
+
+        match (find_string(fields, "kind")?.as_str(), find_value(fields, "value")?)
+        {
+            ("min", Type::I(Some(min))) => return Ok(Constraint::Min(min.clone())),
+            ("max", Type::I(Some(max))) => return Ok(Constraint::Max(max.clone())),
+            ("length", Type::List(Some(bounds))) if bounds.len() == 2 => return Ok(Constraint::Length { min: length(&bounds[0])?, max: length(&bounds[1])? }),
+            ("pattern", Type::S(Some(pattern))) => return Constraint::pattern(pattern.as_str()),
+            ("one_of", Type::List(Some(values))) => return Ok(Constraint::OneOf(values.clone())),
+            ("custom", Type::S(Some(name))) => return Ok(Constraint::custom(name.as_str(), |_| false)),
+            (kind, _) => bail!("Invalid {} constraint!", kind),
+        }
+    }
+}
+
+impl fmt::Debug for Constraint
+{
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result
+    {
+        match self
+        {
+            Constraint::Min(min) => write!(f, "Min({})", min),
+            Constraint::Max(max) => write!(f, "Max({})", max),
+            Constraint::Length { min, max } => write!(f, "Length {{ min: {}, max: {} }}", min, max),
+            Constraint::Pattern(regex) => write!(f, "Pattern({:?})", regex.as_str()),
+            Constraint::OneOf(values) => write!(f, "OneOf({:?})", values),
+            Constraint::Custom(name, _) => write!(f, "Custom({:?})", name),
+        }
+    }
+}
+
+impl fmt::Display for Constraint
+{
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result
+    {
+        match self
+        {
+            Constraint::Min(min) => write!(f, "at least {}", min),
+            Constraint::Max(max) => write!(f, "at most {}", max),
+            Constraint::Length { min, max } => write!(f, "between {} and {} characters", min, max),
+            Constraint::Pattern(regex) => write!(f, "matching /{}/", regex.as_str()),
+            Constraint::OneOf(values) => write!(f, "one of {} values", values.len()),
+            Constraint::Custom(name, _) => write!(f, "{}", name),
+        }
+    }
+}
+
+// Patterns are equal if their regexes are, custom constraints if they share a name and closure
+impl PartialEq for Constraint
+{
+    fn eq(&self, other: &Constraint) -> bool
+    {
+        match (self, other)
+        {
+            (Constraint::Min(a), Constraint::Min(b)) => return a == b,
+            (Constraint::Max(a), Constraint::Max(b)) => return a == b,
+            (Constraint::Length { min: a_min, max: a_max }, Constraint::Length { min: b_min, max: b_max }) => return a_min == b_min && a_max == b_max,
+            (Constraint::Pattern(a), Constraint::Pattern(b)) => return a.as_str() == b.as_str(),
+            (Constraint::OneOf(a), Constraint::OneOf(b)) => return a == b,
+            (Constraint::Custom(a_name, a), Constraint::Custom(b_name, b)) => return a_name == b_name && Rc::ptr_eq(a, b),
+            _ => return false,
+        }
+    }
+}
+
+
+
 // Structs!
 //
 
 
 
-// dbio::dbstruct::Requirement - A single requirement in a database structure
-//
-#[derive(Debug, Clone, PartialEq)]
-pub struct Requirement
-{
-    pub field_id: String, // The ID of the required field
-    pub field_type: Discriminant<Type>, // The type of the required field
-}
+// dbio::dbstruct::Requirement - A single requirement in a database structure
+//
+#[derive(Debug, Clone, PartialEq)]
+pub struct Requirement
+{
+    pub field_id: String, // The ID of the required field
+    pub field_type: Discriminant<Type>, // The type of the required field
+    pub nullable: bool, // Whether the field may be null
+    pub unique: bool, // Whether two entries of a list may share a non-null value, checked when entries are added
+    pub presence: Presence, // Whether the field has to be there
+    pub constraints: Vec<Constraint>, // Rules non-null values have to follow
+    pub element_type: Option<Discriminant<Type>>, // The type every element must have, for lists
+    pub structure: Option<Structure>, // The structure documents must meet, for documents and lists of documents
+    pub target_list: Option<String>, // The ID of the list references must point into, checked when entries are added
+}
+
+impl Requirement
+{
+    // dbio::dbstruct::Requirement::new - Simple requirement constructor, the field is optional and may be null unless told otherwise
+    //
+    // ARGUMENTS:
+    //  field_id: &str - A string containing the ID of the required field
+    //  field_type: Type - The type of the required field
+    pub fn new(field_id: &str, field_type: Discriminant<Type>) -> Requirement
+    {
+        return Requirement
+        {
+            field_id: field_id.to_string(),
+            field_type: field_type,
+            nullable: true,
+            unique: false,
+            presence: Presence::Optional,
+            constraints: Vec::new(),
+            element_type: None,
+            structure: None,
+            target_list: None,
+        };
+    }
+
+    // dbio::dbstruct::Requirement::references - Require a reference field, or every reference in a list field, to point at an entry of a list
+    //
+    // ARGUMENTS:
+    //  list_id: &str - The ID of the structure of the target list
+    pub fn references(mut self, list_id: &str) -> Requirement
+    {
+        self.target_list = Some(list_id.to_string());
+
+        return self;
+    }
+
+    // dbio::dbstruct::Requirement::elements - Require every element of a list field to have the given type
+    //
+    // ARGUMENTS:
+    //  element_type: Discriminant<Type> - The type of the elements
+    pub fn elements(mut self, element_type: Discriminant<Type>) -> Requirement
+    {
+        self.element_type = Some(element_type);
+
+        return self;
+    }
+
+    // dbio::dbstruct::Requirement::structure - Require a document field, or every document in a list field, to meet a structure
+    //
+    // ARGUMENTS:
+    //  structure: Structure - The structure of the documents
+    pub fn structure(mut self, structure: Structure) -> Requirement
+    {
+        self.structure = Some(structure);
+
+        return self;
+    }
+
+    // dbio::dbstruct::Requirement::not_null - Refuse null values for the field
+    //
+    pub fn not_null(mut self) -> Requirement
+    {
+        self.nullable = false;
+
+        return self;
+    }
+
+    // dbio::dbstruct::Requirement::required - Refuse entries without the field
+    //
+    pub fn required(mut self) -> Requirement
+    {
+        self.presence = Presence::Required;
+
+        return self;
+    }
+
+    // dbio::dbstruct::Requirement::default - Fill the field in with a value when an entry leaves it out
+    //
+    // ARGUMENTS:
+    //  value: Type - The default value, which has to meet the requirement like any other
+    pub fn default(mut self, value: Type) -> Requirement
+    {
+        self.presence = Presence::Default(value);
+
+        return self;
+    }
+
+    // dbio::dbstruct::Requirement::unique - Refuse entries with the same non-null value as an entry already in the list
+    //
+    pub fn unique(mut self) -> Requirement
+    {
+        self.unique = true;
+
+        return self;
+    }
+
+    // dbio::dbstruct::Requirement::constrain - Add a constraint non-null values have to follow
+    //
+    // ARGUMENTS:
+    //  constraint: Constraint - The constraint
+    pub fn constrain(mut self, constraint: Constraint) -> Requirement
+    {
+        self.constraints.push(constraint);
+
+        return self;
+    }
+
+    // dbio::dbstruct::Requirement::is_missing - Checks if leaving the field out is refused
+    //
+    // Fields that can't be null are missing if they aren't there and have no default, since an absent field reads as null.
+    pub fn is_missing(&self) -> bool
+    {
+        match self.presence
+        {
+            Presence::Required => return true,
+            Presence::Optional => return !self.nullable,
+            Presence::Default(_) => return false,
+        }
+    }
+
+    // dbio::dbstruct::Requirement::to_fields - Describe the requirement as fields, for storing it in a schema chunk
+    //
+    // Types are stored as a null of the type.
+    pub fn to_fields(&self) -> Vec<Field>
+    {
+        let mut fields = Vec::<Field>::new();
+
+        fields.push(Field::new("id", Type::S(Some(S::new(&self.field_id)))));
+        fields.push(Field::new("type", type_null(self.field_type)));
+        fields.push(Field::new("nullable", Type::B(Some(B::new(self.nullable)))));
+        fields.push(Field::new("unique", Type::B(Some(B::new(self.unique)))));
+
+        match &self.presence
+        {
+            Presence::Required => fields.push(Field::new("presence", Type::S(Some(S::new("required"))))),
+            Presence::Optional => fields.push(Field::new("presence", Type::S(Some(S::new("optional"))))),
+            Presence::Default(value) =>
+            {
+                fields.push(Field::new("presence", Type::S(Some(S::new("default")))));
+                fields.push(Field::new("default", value.clone()));
+            }
+        }
+
+        let constraints = self.constraints.iter().map(|constraint| Type::Doc(Some(constraint.to_fields()))).collect();
+        fields.push(Field::new("constraints", Type::List(Some(constraints))));
+
+        if let Some(element_type) = self.element_type
+        {
+            fields.push(Field::new("elements", type_null(element_type)));
+        }
+
+        if let Some(structure) = &self.structure
+        {
+            fields.push(Field::new("structure", Type::Doc(Some(structure.to_fields()))));
+        }
+
+        if let Some(target_list) = &self.target_list
+        {
+            fields.push(Field::new("references", Type::S(Some(S::new(target_list)))));
+        }
+
+        return fields;
+    }
+
+    // dbio::dbstruct::Requirement::from_fields - Rebuild a requirement from the fields written by to_fields()
+    //
+    // ARGUMENTS:
+    //  fields: &[Field] - The fields describing the requirement
+    pub fn from_fields(fields: &[Field]) -> Result<Requirement, Box<dyn Error>>
+    {
+        let mut requirement = Requirement::new(&find_string(fields, "id")?, std::mem::discriminant(find_value(fields, "type")?));
+
+        match find_value(fields, "nullable")?
+        {
+            Type::B(Some(nullable)) => requirement.nullable = nullable.is_true(),
+            _ => bail!("Requirement {} nullable is not a boolean!", requirement.field_id),
+        }
+
+        match find_value(fields, "unique")?
+        {
+            Type::B(Some(unique)) => requirement.unique = unique.is_true(),
+            _ => bail!("Requirement {} unique is not a boolean!", requirement.field_id),
+        }
+
+        match find_string(fields, "presence")?.as_str()
+        {
+            "required" => requirement.presence = Presence::Required,
+            "optional" => requirement.presence = Presence::Optional,
+            "default" => requirement.presence = Presence::Default(find_value(fields, "default")?.clone()),
+            presence => bail!("Requirement {} has unknown presence {}!", requirement.field_id, presence),
+        }
+
+        match find_value(fields, "constraints")?
+        {
+            Type::List(Some(constraints)) =>
+            {
+                for constraint in constraints
+                {
+                    match constraint
+                    {
+                        Type::Doc(Some(constraint_fields)) => requirement.constraints.push(Constraint::from_fields(constraint_fields)?),
+                        _ => bail!("Requirement {} has a constraint that is not a document!", requirement.field_id),
+                    }
+                }
+            }
+            _ => bail!("Requirement {} constraints are not a list!", requirement.field_id),
+        }
+
+        if let Ok(element_type) = find_value(fields, "elements")
+        {
+            requirement.element_type = Some(std::mem::discriminant(element_type));
+        }
+
+        match find_value(fields, "structure")
+        {
+            Ok(Type::Doc(Some(structure_fields))) => requirement.structure = Some(Structure::from_fields(structure_fields)?),
+            Ok(_) => bail!("Requirement {} structure is not a document!", requirement.field_id),
+            Err(_) => {}
+        }
+
+        if let Ok(target_list) = find_string(fields, "references")
+        {
+            requirement.target_list = Some(target_list);
+        }
+
+        return Ok(requirement);
+    }
+
+    // dbio::dbstruct::Requirement::meets - Checks if a field meets the requirement
+    //
+    // ARGUMENTS:
+    //  field: &Field - The field to check
+    pub fn meets(&self, field: &Field) -> bool
+    {
+        return self.field_id == field.id && self.check(field).is_ok();
+    }
+
+    // dbio::dbstruct::Requirement::check - Checks the value of a field against the requirement, saying what is wrong with it
+    //
+    // The field ID isn't checked, Structure::check() matches fields to requirements by ID.
+    //
+    // ARGUMENTS:
+    //  field: &Field - The field to check
+    pub fn check(&self, field: &Field) -> Result<(), StructureError>
+    {
+        if self.field_type != std::mem::discriminant(&field.value) || (!self.nullable && field.value.is_null())
+        {
+            return Err(StructureError::WrongType { field_id: field.id.clone() });
+        }
+
+        if !field.value.is_null()
+        {
+            for constraint in &self.constraints
+            {
+                if !constraint.allows(&field.value)
+                {
+                    return Err(StructureError::Constraint { field_id: field.id.clone(), constraint: constraint.clone() });
+                }
+            }
+        }
+
+        return self.check_nested(&field.value).map_err(|error| error.nested(&field.id));
+    }
+
+    // dbio::dbstruct::Requirement::fill_nested - Fill in the defaults of documents nested in a value
+    //
+    // ARGUMENTS:
+    //  value: &mut Type - The value of the field
+    fn fill_nested(&self, value: &mut Type)
+    {
+        let structure = match &self.structure
+        {
+            Some(structure) => structure,
+            None => return,
+        };
+
+        match value
+        {
+            Type::List(Some(elements)) =>
+            {
+                for element in elements
+                {
+                    if let Type::Doc(Some(fields)) = element
+                    {
+                        structure.fill_defaults(fields);
+                    }
+                }
+            }
+            Type::Doc(Some(fields)) =>
+            {
+                structure.fill_defaults(fields);
+            }
+            _ => {}
+        }
+    }
+
+    // dbio::dbstruct::Requirement::check_nested - Checks the elements of a list, or the fields of a document, against the requirement
+    //
+    // Errors are relative to the field, list elements are named by their index.
+    //
+    // ARGUMENTS:
+    //  value: &Type - The value of the field
+    fn check_nested(&self, value: &Type) -> Result<(), StructureError>
+    {
+        match value
+        {
+            Type::List(Some(elements)) =>
+            {
+                for (index, element) in elements.iter().enumerate()
+                {
+                    if self.element_type.is_some_and(|element_type| element_type != std::mem::discriminant(element))
+                    {
+                        return Err(StructureError::WrongType { field_id: index.to_string() });
+                    }
+
+                    if let (Some(structure), Type::Doc(Some(fields))) = (&self.structure, element)
+                    {
+                        structure.check(fields).map_err(|error| error.nested(&index.to_string()))?;
+                    }
+                }
+
+                return Ok(());
+            }
+            Type::Doc(Some(fields)) =>
+            {
+                match &self.structure
+                {
+                    Some(structure) => return structure.check(fields),
+                    None => return Ok(()),
+                }
+            }
+            _ =>
+            {
+                return Ok(());
+            }
+        }
+    }
+}
+
+
+
+// dbio::dbstruct::Structure - A database structure
+//
+#[derive(Debug, Clone, PartialEq)]
+pub struct Structure
+{
+    id: String, // The ID of the structure
+    version: u64, // The version of the structure, see List::evolve()
+    requirements: Vec<Requirement>,
+}
+
+impl Structure
+{
+    // dbio::dbstruct::Structure::new - Simple structure constructor, the structure starts at version 1
+    //
+    // Field IDs are stored with a one byte length, so requirements on longer IDs are refused.
+    //
+    // ARGUMENTS:
+    //  id: &str - A string containing the ID of the structure
+    //  requirements: Vec<Requirement> - A vector of requirements to be met
+    pub fn new(id: &str, mut requirements: Vec<Requirement>) -> Result<Structure, Box<dyn Error>>
+    {
+        if let Some(requirement) = requirements.iter().find(|requirement| requirement.field_id.len() > u8::MAX as usize)
+        {
+            bail!("Structure {} has a field ID longer than 255 bytes: {}!", id, requirement.field_id);
+        }
+
+        requirements.sort_by(|a, b| a.field_id.cmp(&b.field_id)); // Sort the requirements by ID so we can binary search them later
+
+        return Ok
+        (
+            Structure
+            {
+                id: id.to_string(),
+                version: 1,
+                requirements: requirements,
+            }
+        );
+    }
+
+    // dbio::dbstruct::Structure::versioned - Set the version of the structure
+    //
+    // ARGUMENTS:
+    //  version: u64 - The version, must not be 0 since entries use it for "no structure"
+    pub fn versioned(mut self, version: u64) -> Result<Structure, Box<dyn Error>>
+    {
+        if version == 0
+        {
+            bail!("Structure {} can't be at version 0!", self.id);
+        }
+
+        self.version = version;
+
+        return Ok(self);
+    }
+
+    // dbio::dbstruct::Structure::version - Get the version of the structure
+    //
+    pub fn version(&self) -> u64
+    {
+        return self.version;
+    }
+
+    // dbio::dbstruct::Structure::id - Get the ID of the structure
+    //
+    pub fn id(&self) -> &str
+    {
+        return &self.id;
+    }
+
+    // dbio::dbstruct::Structure::requirements - Get the requirements of the structure, sorted by field ID
+    //
+    pub fn requirements(&self) -> &[Requirement]
+    {
+        return &self.requirements;
+    }
+
+    // dbio::dbstruct::Structure::meets - Checks if a structure meets the requirements
+    //
+    // ARGUMENTS:
+    // fields: &[Field] - The fields to check
+    pub fn meets(&self, fields: &[Field]) -> bool
+    {
+        return self.check(fields).is_ok();
+    }
+
+    // dbio::dbstruct::Structure::check - Checks fields against the requirements, returning the first field that doesn't meet them
+    //
+    // Fields with a default aren't missing, call fill_defaults() to put them in. Each field ID may only be there once.
+    //
+    // ARGUMENTS:
+    // fields: &[Field] - The fields to check
+    pub fn check(&self, fields: &[Field]) -> Result<(), StructureError>
+    {
+        for (i, field) in fields.iter().enumerate()
+        {
+            if fields[..i].iter().any(|earlier| earlier.id == field.id)
+            {
+                return Err(StructureError::Repeated { field_id: field.id.clone() });
+            }
+
+            match self.requirements.binary_search_by(|req| req.field_id.cmp(&field.id))
+            {
+                Ok(index) =>
+                {
+                    self.requirements[index].check(field)?;
+                }
+                Err(_) =>
+                {
+                    return Err(StructureError::NotAllowed { field_id: field.id.clone() });
+                }
+            }
+        }
+
+        if let Some(field_id) = self.missing(fields).first()
+        {
+            return Err(StructureError::Missing { field_id: field_id.to_string() });
+        }
+
+        return Ok(());
+    }
+
+    // dbio::dbstruct::Structure::missing - Get the IDs of every field that has to be there but isn't, sorted
+    //
+    // ARGUMENTS:
+    // fields: &[Field] - The fields to check
+    pub fn missing(&self, fields: &[Field]) -> Vec<&str>
+    {
+        let mut missing = Vec::new();
 
-impl Requirement
-{
-    // dbio::dbstruct::Requirement::new - Simple requirement constructor
+        for requirement in &self.requirements
+        {
+            if requirement.is_missing() && !fields.iter().any(|field| field.id == requirement.field_id)
+            {
+                missing.push(requirement.field_id.as_str());
+            }
+        }
+
+        return missing;
+    }
+
+    // dbio::dbstruct::Structure::fill_defaults - Add the default of every field that was left out, including in nested documents
     //
     // ARGUMENTS:
-    //  field_id: &str - A string containing the ID of the required field
-    //  field_type: Type - The type of the required field
-    pub fn new(field_id: &str, field_type: Discriminant<Type>) -> Requirement
+    // fields: &mut Vec<Field> - The fields to fill in
+    pub fn fill_defaults(&self, fields: &mut Vec<Field>)
     {
-        return Requirement
+        for field in fields.iter_mut()
         {
-            field_id: field_id.to_string(),
-            field_type: field_type,
-        };
+            if let Ok(index) = self.requirements.binary_search_by(|req| req.field_id.cmp(&field.id))
+            {
+                self.requirements[index].fill_nested(&mut field.value);
+            }
+        }
+
+        for requirement in &self.requirements
+        {
+            if let Presence::Default(value) = &requirement.presence
+            {
+                if !fields.iter().any(|field| field.id == requirement.field_id)
+                {
+                    fields.push(Field::new(&requirement.field_id, value.clone()));
+                }
+            }
+        }
     }
 
-    // dbio::dbstruct::Requirement::meets - Checks if a field meets the requirement
+    // dbio::dbstruct::Structure::bind - Give custom constraints with a name their closure, including in nested structures
+    //
+    // Custom constraints read back from a schema chunk refuse every value until they are bound.
     //
     // ARGUMENTS:
-    //  field: &Field - The field to check
-    pub fn meets(&self, field: &Field) -> bool
+    //  name: &str - The name of the custom constraints
+    //  validator: impl Fn(&Type) -> bool - Returns whether a value meets the constraint
+    pub fn bind(&mut self, name: &str, validator: impl Fn(&Type) -> bool + 'static)
+    {
+        let validator: Rc<dyn Fn(&Type) -> bool> = Rc::new(validator);
+
+        self.bind_rc(name, &validator);
+    }
+
+    // dbio::dbstruct::Structure::bind_rc - Share one closure between every custom constraint with a name, see bind()
+    //
+    // ARGUMENTS:
+    //  name: &str - The name of the custom constraints
+    //  validator: &Rc<dyn Fn(&Type) -> bool> - The closure
+    fn bind_rc(&mut self, name: &str, validator: &Rc<dyn Fn(&Type) -> bool>)
+    {
+        for requirement in &mut self.requirements
+        {
+            for constraint in &mut requirement.constraints
+            {
+                if let Constraint::Custom(custom_name, custom_validator) = constraint
+                {
+                    if custom_name == name
+                    {
+                        *custom_validator = Rc::clone(validator);
+                    }
+                }
+            }
+
+            if let Some(structure) = &mut requirement.structure
+            {
+                structure.bind_rc(name, validator);
+            }
+        }
+    }
+
+    // dbio::dbstruct::Structure::to_fields - Describe the structure as fields, for storing it in a schema chunk
+    //
+    pub fn to_fields(&self) -> Vec<Field>
+    {
+        let requirements = self.requirements.iter().map(|requirement| Type::Doc(Some(requirement.to_fields()))).collect();
+
+        return vec!
+        [
+            Field::new("id", Type::S(Some(S::new(&self.id)))),
+            Field::new("version", Type::I(Some(I::from(self.version as i64)))),
+            Field::new("requirements", Type::List(Some(requirements))),
+        ];
+    }
+
+    // dbio::dbstruct::Structure::from_fields - Rebuild a structure from the fields written by to_fields()
+    //
+    // ARGUMENTS:
+    //  fields: &[Field] - The fields describing the structure
+    pub fn from_fields(fields: &[Field]) -> Result<Structure, Box<dyn Error>>
     {
-        return self.field_id == field.id &&
-            self.field_type == std::mem::discriminant(&field.value);
+        let id = find_string(fields, "id")?;
+        let mut requirements = Vec::<Requirement>::new();
+
+        let version = match find_value(fields, "version")?
+        {
+            Type::I(Some(version)) => match version.to_i64().and_then(|version| u64::try_from(version).ok())
+            {
+                Some(version) if version != 0 => version,
+                _ => bail!("Structure {} version out of range!", id),
+            },
+            _ => bail!("Structure {} version is not an integer!", id),
+        };
+
+        match find_value(fields, "requirements")?
+        {
+            Type::List(Some(requirement_docs)) =>
+            {
+                for requirement in requirement_docs
+                {
+                    match requirement
+                    {
+                        Type::Doc(Some(requirement_fields)) => requirements.push(Requirement::from_fields(requirement_fields)?),
+                        _ => bail!("Structure {} has a requirement that is not a document!", id),
+                    }
+                }
+            }
+            _ => bail!("Structure {} requirements are not a list!", id),
+        }
+
+        return Structure::new(&id, requirements)?.versioned(version);
     }
 }
 
 
 
-// dbio::dbstruct::Structure - A database structure
+// Closures used by migration steps, see Migration::retype() and Migration::custom()
+pub type RetypeFn = Rc<dyn Fn(&Type) -> Result<Type, Box<dyn Error>>>;
+pub type MigrateFn = Rc<dyn Fn(&mut Vec<Field>) -> Result<(), Box<dyn Error>>>;
+
+// dbio::dbstruct::MigrationStep - A single change made to the fields of an entry by a migration
 //
-#[derive(Debug, Clone, PartialEq)]
-pub struct Structure
+#[derive(Clone)]
+pub enum MigrationStep
 {
-    id: String, // The ID of the structure
-    requirements: Vec<Requirement>,
+    Add { field_id: String, value: Type }, // Add the field to entries that don't have it
+    Remove(String), // Remove the field
+    Rename { from: String, to: String }, // Change the ID of the field
+    Retype { field_id: String, convert: RetypeFn }, // Convert the value of the field
+    Custom(MigrateFn), // Change the fields in any other way
 }
 
-impl Structure
+// dbio::dbstruct::Migration - How to bring the fields of an entry from one version of a structure to another
+//
+// Migrations are declared on a list with List::add_migration(), like custom constraints they aren't stored in the file.
+#[derive(Clone)]
+pub struct Migration
+{
+    from_version: u64, // The version entries are migrated from
+    to_version: u64, // The version entries are migrated to
+    steps: Vec<MigrationStep>, // The changes, made in order
+}
+
+impl Migration
 {
-    // dbio::dbstruct::Structure::new - Simple structure constructor
+    // dbio::dbstruct::Migration::new - Create a migration without any steps
     //
     // ARGUMENTS:
-    //  id: &str - A string containing the ID of the structure
-    //  requirements: Vec<Requirement> - A vector of requirements to be met
-    pub fn new(id: &str, mut requirements: Vec<Requirement>) -> Structure
+    //  from_version: u64 - The version entries are migrated from
+    //  to_version: u64 - The version entries are migrated to, must be greater than from_version
+    pub fn new(from_version: u64, to_version: u64) -> Result<Migration, Box<dyn Error>>
     {
-        requirements.sort_by(|a, b| a.field_id.cmp(&b.field_id)); // Sort the requirements by ID so we can binary search them later
-
-        return Structure
+        if to_version <= from_version
         {
-            id: id.to_string(),
-            requirements: requirements,
-        };
+            bail!("Migration from version {} to {} doesn't go forwards!", from_version, to_version);
+        }
+
+        return Ok
+        (
+            Migration
+            {
+                from_version: from_version,
+                to_version: to_version,
+                steps: Vec::new(),
+            }
+        );
     }
 
-    // dbio::dbstruct::Structure::meets - Checks if a structure meets the requirements
+    // dbio::dbstruct::Migration::from_version - Get the version entries are migrated from
+    //
+    pub fn from_version(&self) -> u64
+    {
+        return self.from_version;
+    }
+
+    // dbio::dbstruct::Migration::to_version - Get the version entries are migrated to
+    //
+    pub fn to_version(&self) -> u64
+    {
+        return self.to_version;
+    }
+
+    // dbio::dbstruct::Migration::add - Add a field to entries that don't have it
+    //
+    // ARGUMENTS:
+    //  field_id: &str - The ID of the field
+    //  value: Type - The value of the field
+    pub fn add(mut self, field_id: &str, value: Type) -> Migration
+    {
+        self.steps.push(MigrationStep::Add { field_id: field_id.to_string(), value: value });
+
+        return self;
+    }
+
+    // dbio::dbstruct::Migration::remove - Remove a field
+    //
+    // ARGUMENTS:
+    //  field_id: &str - The ID of the field
+    pub fn remove(mut self, field_id: &str) -> Migration
+    {
+        self.steps.push(MigrationStep::Remove(field_id.to_string()));
+
+        return self;
+    }
+
+    // dbio::dbstruct::Migration::rename - Change the ID of a field
+    //
+    // ARGUMENTS:
+    //  from: &str - The old ID of the field
+    //  to: &str - The new ID of the field
+    pub fn rename(mut self, from: &str, to: &str) -> Migration
+    {
+        self.steps.push(MigrationStep::Rename { from: from.to_string(), to: to.to_string() });
+
+        return self;
+    }
+
+    // dbio::dbstruct::Migration::retype - Convert the value of a field, such as turning a string into an integer
+    //
+    // ARGUMENTS:
+    //  field_id: &str - The ID of the field
+    //  convert: impl Fn(&Type) -> Result<Type, Box<dyn Error>> - Returns the new value of the field
+    pub fn retype(mut self, field_id: &str, convert: impl Fn(&Type) -> Result<Type, Box<dyn Error>> + 'static) -> Migration
+    {
+        self.steps.push(MigrationStep::Retype { field_id: field_id.to_string(), convert: Rc::new(convert) });
+
+        return self;
+    }
+
+    // dbio::dbstruct::Migration::custom - Change the fields in any other way
+    //
+    // ARGUMENTS:
+    //  migrate: impl Fn(&mut Vec<Field>) -> Result<(), Box<dyn Error>> - Changes the fields
+    pub fn custom(mut self, migrate: impl Fn(&mut Vec<Field>) -> Result<(), Box<dyn Error>> + 'static) -> Migration
+    {
+        self.steps.push(MigrationStep::Custom(Rc::new(migrate)));
+
+        return self;
+    }
+
+    // dbio::dbstruct::Migration::apply - Make every step of the migration to the fields of an entry
     //
     // ARGUMENTS:
-    // fields: &Vec<Field> - A vector of fields to check
-    pub fn meets(&self, fields: &Vec<Field>) -> bool
+    //  fields: &mut Vec<Field> - The fields of the entry
+    pub fn apply(&self, fields: &mut Vec<Field>) -> Result<(), Box<dyn Error>>
     {
-        for field in fields
+        for step in &self.steps
         {
-            match self.requirements.binary_search_by(|req| req.field_id.cmp(&field.id))
+            match step
             {
-                Ok(index) =>
+                MigrationStep::Add { field_id, value } =>
                 {
-                    if !self.requirements[index].meets(field)
+                    if !fields.iter().any(|field| field.id == *field_id)
                     {
-                        return false;
+                        fields.push(Field::new(field_id, value.clone()));
                     }
                 }
-                Err(_) =>
+                MigrationStep::Remove(field_id) =>
+                {
+                    fields.retain(|field| field.id != *field_id);
+                }
+                MigrationStep::Rename { from, to } =>
+                {
+                    for field in fields.iter_mut().filter(|field| field.id == *from)
+                    {
+                        field.id = to.clone();
+                    }
+                }
+                MigrationStep::Retype { field_id, convert } =>
+                {
+                    for field in fields.iter_mut().filter(|field| field.id == *field_id)
+                    {
+                        field.value = convert(&field.value)?;
+                    }
+                }
+                MigrationStep::Custom(migrate) =>
                 {
-                    return false;
+                    migrate(fields)?;
                 }
             }
         }
 
-        return true;
+        return Ok(());
+    }
+}
+
+
+
+// Functions!
+//
+
+
+
+// dbio::dbstruct::type_null - Get the null of the type a discriminant belongs to, which stands in for the type on disk
+//
+// ARGUMENTS:
+//  field_type: Discriminant<Type> - The type
+fn type_null(field_type: Discriminant<Type>) -> Type
+{
+    for sort_class in *b"ISBFDXTALOR"
+    {
+        if let Some(null) = Type::null(sort_class)
+        {
+            if std::mem::discriminant(&null) == field_type
+            {
+                return null;
+            }
+        }
+    }
+
+    panic!("Type without a sort class! You shouldn't see this!");
+}
+
+// dbio::dbstruct::find_value - Find the value of a field by ID, failing if it isn't there
+//
+// ARGUMENTS:
+//  fields: &[Field] - The fields to search
+//  id: &str - The ID of the field
+fn find_value<'a>(fields: &'a [Field], id: &str) -> Result<&'a Type, Box<dyn Error>>
+{
+    match fields.iter().find(|field| field.id == id)
+    {
+        Some(field) => return Ok(&field.value),
+        None => bail!("Schema is missing the {} field!", id),
+    }
+}
+
+// dbio::dbstruct::find_string - Find the value of a string field by ID, failing if it isn't there or isn't a string
+//
+// ARGUMENTS:
+//  fields: &[Field] - The fields to search
+//  id: &str - The ID of the field
+fn find_string(fields: &[Field], id: &str) -> Result<String, Box<dyn Error>>
+{
+    match find_value(fields, id)?
+    {
+        Type::S(Some(string)) => return Ok(string.as_str().to_string()),
+        _ => bail!("Schema field {} is not a string!", id),
     }
 }
 
@@ -124,6 +1131,7 @@ mod tests
         let req = Requirement::new("id", disc);
         assert_eq!(req.field_id, "id");
         assert_eq!(req.field_type, disc);
+        assert!(req.nullable);
     }
 
     // dbio::dbstruct::tests::test_requirement_meets() - Tests the requirement meets function
@@ -148,6 +1156,21 @@ mod tests
         assert!(!req.meets(&field)); // Test fails because the field is not the same type
     }
 
+    // dbio::dbstruct::tests::test_requirement_nullable() - Tests that nulls only meet nullable requirements
+    //
+    #[test]
+    fn test_requirement_nullable()
+    {
+        let disc = std::mem::discriminant(&Type::S(None));
+        let nullable = Requirement::new("id", disc);
+        let not_null = Requirement::new("id", disc).not_null();
+
+        assert!(nullable.meets(&Field::new("id", Type::S(None))));
+        assert!(!not_null.meets(&Field::new("id", Type::S(None))));
+        assert!(not_null.meets(&Field::new("id", Type::S(Some(S::new("Test"))))));
+        assert!(!nullable.meets(&Field::new("id", Type::I(None)))); // Nulls still have a type
+    }
+
     // dbio::dbstruct::tests::test_requirement_doesnt_meet_id() - Tests the requirement doesn't meet function, when the ID doesn't match
     //
     #[test]
@@ -169,9 +1192,13 @@ mod tests
         let mut requirements = Vec::new();
         requirements.push(req);
         let requirements2 = requirements.clone();
-        let structure = Structure::new("id", requirements);
+        let structure = Structure::new("id", requirements).unwrap();
         assert_eq!(structure.id, "id");
         assert_eq!(structure.requirements, requirements2);
+
+        // Field IDs have to fit their one byte length
+        assert!(Structure::new("id", vec![Requirement::new(&"x".repeat(255), disc)]).is_ok());
+        assert!(Structure::new("id", vec![Requirement::new(&"x".repeat(256), disc)]).is_err());
     }
 
     // dbio::dbstruct::tests::test_structure_meets() - Tests the structure meets function
@@ -184,7 +1211,7 @@ mod tests
         let field = Field::new("id", Type::S(Some(S::new("Test"))));
         let mut requirements = Vec::new();
         requirements.push(req);
-        let structure = Structure::new("id", requirements);
+        let structure = Structure::new("id", requirements).unwrap();
         let mut fields = Vec::new();
         fields.push(field);
         assert!(structure.meets(&fields));
@@ -200,7 +1227,7 @@ mod tests
         let field = Field::new("id", Type::I(Some(I::new(10))));
         let mut requirements = Vec::new();
         requirements.push(req);
-        let structure = Structure::new("id", requirements);
+        let structure = Structure::new("id", requirements).unwrap();
         let mut fields = Vec::new();
         fields.push(field);
         assert!(!structure.meets(&fields)); // Test fails because the field is not the same type
@@ -216,9 +1243,229 @@ mod tests
         let field = Field::new("id2", Type::S(Some(S::new("Test"))));
         let mut requirements = Vec::new();
         requirements.push(req);
-        let structure = Structure::new("id", requirements);
+        let structure = Structure::new("id", requirements).unwrap();
         let mut fields = Vec::new();
         fields.push(field);
         assert!(!structure.meets(&fields)); // Test fails because the field is not the same ID
     }
-}
\ No newline at end of file
+
+    // dbio::dbstruct::tests::test_requirement_nested() - Tests requirements on list elements and nested documents
+    //
+    #[test]
+    fn test_requirement_nested()
+    {
+        let address = Structure::new("address", vec![Requirement::new("city", std::mem::discriminant(&Type::S(None)))]).unwrap();
+        let tags = Requirement::new("tags", std::mem::discriminant(&Type::List(None))).elements(std::mem::discriminant(&Type::S(None)));
+        let home = Requirement::new("home", std::mem::discriminant(&Type::Doc(None))).structure(address.clone());
+        let homes = Requirement::new("homes", std::mem::discriminant(&Type::List(None))).elements(std::mem::discriminant(&Type::Doc(None))).structure(address);
+
+        let city = |city: &str| Type::Doc(Some(vec![Field::new("city", Type::S(Some(S::new(city))))]));
+
+        assert!(tags.meets(&Field::new("tags", Type::List(Some(vec![Type::S(Some(S::new("a"))), Type::S(None)])))));
+        assert!(!tags.meets(&Field::new("tags", Type::List(Some(vec![Type::S(Some(S::new("a"))), Type::I(Some(I::new(1)))])))));
+
+        assert!(home.meets(&Field::new("home", city("Paris"))));
+        assert!(!home.meets(&Field::new("home", Type::Doc(Some(vec![Field::new("town", Type::S(Some(S::new("Paris"))))])))));
+
+        assert!(homes.meets(&Field::new("homes", Type::List(Some(vec![city("Paris"), city("Rome")])))));
+        assert!(!homes.meets(&Field::new("homes", Type::List(Some(vec![city("Paris"), Type::Doc(Some(vec![Field::new("city", Type::I(None))]))])))));
+    }
+
+    // dbio::dbstruct::tests::test_structure_check() - Tests that structure errors name the field and what is wrong with it
+    //
+    #[test]
+    fn test_structure_check()
+    {
+        let address = Structure::new("address", vec![Requirement::new("city", std::mem::discriminant(&Type::S(None))).not_null()]).unwrap();
+        let structure = Structure::new("person", vec!
+        [
+            Requirement::new("name", std::mem::discriminant(&Type::S(None))).not_null(),
+            Requirement::new("age", std::mem::discriminant(&Type::I(None))),
+            Requirement::new("homes", std::mem::discriminant(&Type::List(None))).elements(std::mem::discriminant(&Type::Doc(None))).structure(address),
+        ]).unwrap();
+
+        let name = Field::new("name", Type::S(Some(S::new("Test"))));
+        let city = |city: &str| Type::Doc(Some(vec![Field::new("city", Type::S(Some(S::new(city))))]));
+
+        assert_eq!(structure.check(std::slice::from_ref(&name)), Ok(()));
+        assert_eq!(structure.check(&[name.clone(), Field::new("age", Type::I(None))]), Ok(()));
+
+        assert_eq!(structure.check(&[]), Err(StructureError::Missing { field_id: "name".to_string() }));
+        assert_eq!(structure.check(&[Field::new("name", Type::S(None))]), Err(StructureError::WrongType { field_id: "name".to_string() }));
+        assert_eq!(structure.check(&[name.clone(), Field::new("age", Type::S(None))]), Err(StructureError::WrongType { field_id: "age".to_string() }));
+        assert_eq!(structure.check(&[name.clone(), Field::new("email", Type::S(None))]), Err(StructureError::NotAllowed { field_id: "email".to_string() }));
+
+        // Nested errors are named by their path
+        let homes = |homes: Vec<Type>| Field::new("homes", Type::List(Some(homes)));
+        assert_eq!(structure.check(&[name.clone(), homes(vec![city("Paris"), city("Rome")])]), Ok(()));
+        assert_eq!(structure.check(&[name.clone(), homes(vec![city("Paris"), Type::S(None)])]), Err(StructureError::WrongType { field_id: "homes.1".to_string() }));
+        assert_eq!(structure.check(&[name.clone(), homes(vec![city("Paris"), Type::Doc(Some(vec![]))])]), Err(StructureError::Missing { field_id: "homes.1.city".to_string() }));
+
+        assert_eq!(structure.check(&[name.clone(), name.clone()]), Err(StructureError::Repeated { field_id: "name".to_string() }));
+        assert_eq!(structure.check(&[name.clone(), homes(vec![Type::Doc(Some(vec![Field::new("city", Type::S(Some(S::new("Paris")))), Field::new("city", Type::S(Some(S::new("Rome"))))]))])]), Err(StructureError::Repeated { field_id: "homes.0.city".to_string() }));
+
+        let error = structure.check(&[name, homes(vec![Type::Doc(Some(vec![Field::new("town", Type::S(None))]))])]).unwrap_err();
+        assert_eq!(error.field_id(), "homes.0.town");
+        assert_eq!(error.to_string(), "Field homes.0.town is not allowed!");
+    }
+
+    // dbio::dbstruct::tests::test_structure_presence() - Tests required, optional and defaulted fields
+    //
+    #[test]
+    fn test_structure_presence()
+    {
+        let address = Structure::new("address", vec!
+        [
+            Requirement::new("city", std::mem::discriminant(&Type::S(None))).required(),
+            Requirement::new("country", std::mem::discriminant(&Type::S(None))).default(Type::S(Some(S::new("France")))),
+        ]).unwrap();
+        let structure = Structure::new("person", vec!
+        [
+            Requirement::new("name", std::mem::discriminant(&Type::S(None))).required(),
+            Requirement::new("email", std::mem::discriminant(&Type::S(None))).required(),
+            Requirement::new("age", std::mem::discriminant(&Type::I(None))),
+            Requirement::new("active", std::mem::discriminant(&Type::B(None))).not_null().default(Type::B(Some(B::new(true)))),
+            Requirement::new("home", std::mem::discriminant(&Type::Doc(None))).structure(address),
+        ]).unwrap();
+
+        let name = Field::new("name", Type::S(Some(S::new("Test"))));
+        let email = Field::new("email", Type::S(None));
+
+        // Required fields have to be there, even when they may be null
+        assert_eq!(structure.missing(&[]), vec!["email", "name"]);
+        assert_eq!(structure.missing(std::slice::from_ref(&name)), vec!["email"]);
+        assert!(!structure.meets(std::slice::from_ref(&name)));
+        assert_eq!(structure.check(std::slice::from_ref(&email)), Err(StructureError::Missing { field_id: "name".to_string() }));
+        assert!(structure.meets(&[name.clone(), email.clone()]));
+
+        // Defaults are filled in, nested ones included, and leave fields that are there alone
+        let mut fields = vec![name.clone(), email.clone(), Field::new("home", Type::Doc(Some(vec![Field::new("city", Type::S(Some(S::new("Paris"))))])))];
+        structure.fill_defaults(&mut fields);
+        assert_eq!(fields.len(), 4);
+        assert_eq!(fields[3], Field::new("active", Type::B(Some(B::new(true)))));
+        assert_eq!(fields[2].value, Type::Doc(Some(vec![Field::new("city", Type::S(Some(S::new("Paris")))), Field::new("country", Type::S(Some(S::new("France"))))])));
+        assert!(structure.meets(&fields));
+
+        let mut fields = vec![name, email, Field::new("active", Type::B(Some(B::new(false))))];
+        structure.fill_defaults(&mut fields);
+        assert_eq!(fields.len(), 3);
+        assert_eq!(fields[2], Field::new("active", Type::B(Some(B::new(false)))));
+    }
+
+    // dbio::dbstruct::tests::test_requirement_constraints() - Tests that constraint violations name the failing constraint
+    //
+    #[test]
+    fn test_requirement_constraints()
+    {
+        let even = Constraint::custom("even", |value| matches!(value, Type::I(Some(integer)) if integer.to_i64().is_some_and(|integer| integer % 2 == 0)));
+        let structure = Structure::new("person", vec!
+        [
+            Requirement::new("age", std::mem::discriminant(&Type::I(None))).constrain(Constraint::Min(I::new(0))).constrain(Constraint::Max(I::new(150))).constrain(even.clone()),
+            Requirement::new("name", std::mem::discriminant(&Type::S(None))).constrain(Constraint::Length { min: 1, max: 8 }),
+            Requirement::new("email", std::mem::discriminant(&Type::S(None))).constrain(Constraint::pattern(r"^[^@]+@[^@]+$").unwrap()),
+            Requirement::new("role", std::mem::discriminant(&Type::S(None))).constrain(Constraint::OneOf(vec![Type::S(Some(S::new("admin"))), Type::S(Some(S::new("user")))])),
+        ]).unwrap();
+
+        let age = |age: i64| Field::new("age", Type::I(Some(I::new(age))));
+        let string = |id: &str, string: &str| Field::new(id, Type::S(Some(S::new(string))));
+        let failing = |field: Field, constraint: Constraint| Err(StructureError::Constraint { field_id: field.id.clone(), constraint: constraint });
+
+        assert_eq!(structure.check(&[age(0), string("name", "Tést"), string("email", "a@b"), string("role", "user")]), Ok(()));
+        assert_eq!(structure.check(&[Field::new("age", Type::I(None)), Field::new("name", Type::S(None))]), Ok(())); // Nulls aren't constrained
+
+        assert_eq!(structure.check(&[age(-2)]), failing(age(-2), Constraint::Min(I::new(0))));
+        assert_eq!(structure.check(&[age(152)]), failing(age(152), Constraint::Max(I::new(150))));
+        assert_eq!(structure.check(&[age(3)]), failing(age(3), even));
+        assert_eq!(structure.check(&[string("name", "")]), failing(string("name", ""), Constraint::Length { min: 1, max: 8 }));
+        assert_eq!(structure.check(&[string("name", "Abcdefghi")]), failing(string("name", "Abcdefghi"), Constraint::Length { min: 1, max: 8 }));
+        assert!(!structure.meets(&[string("email", "a@b@c")]));
+        assert!(!structure.meets(&[string("role", "root")]));
+
+        let error = structure.check(&[string("email", "ab")]).unwrap_err();
+        assert_eq!(error.to_string(), "Field email fails constraint matching /^[^@]+@[^@]+$/!");
+
+        assert!(Constraint::pattern("(").is_err());
+    }
+
+    // dbio::dbstruct::tests::test_structure_fields() - Tests that structures survive being turned into fields and back
+    //
+    #[test]
+    fn test_structure_fields()
+    {
+        let address = Structure::new("address", vec!
+        [
+            Requirement::new("city", std::mem::discriminant(&Type::S(None))).required().constrain(Constraint::Length { min: 1, max: usize::MAX }),
+            Requirement::new("zip", std::mem::discriminant(&Type::S(None))).constrain(Constraint::custom("zip", |_| true)),
+        ]).unwrap();
+        let structure = Structure::new("person", vec!
+        [
+            Requirement::new("name", std::mem::discriminant(&Type::S(None))).not_null().required().unique().constrain(Constraint::pattern("^[A-Z]").unwrap()),
+            Requirement::new("age", std::mem::discriminant(&Type::I(None))).default(Type::I(None)).constrain(Constraint::Min(I::new(0))).constrain(Constraint::Max(I::new(150))),
+            Requirement::new("role", std::mem::discriminant(&Type::S(None))).default(Type::S(Some(S::new("user")))).constrain(Constraint::OneOf(vec![Type::S(Some(S::new("admin"))), Type::S(Some(S::new("user")))])),
+            Requirement::new("homes", std::mem::discriminant(&Type::List(None))).elements(std::mem::discriminant(&Type::Doc(None))).structure(address),
+            Requirement::new("friends", std::mem::discriminant(&Type::List(None))).elements(std::mem::discriminant(&Type::Ref(None))).references("person"),
+        ]).unwrap();
+
+        // Go through bytes too, the way a schema chunk does
+        let bytes: Vec<u8> = structure.to_fields().iter().flat_map(|field| field.to_bytes().unwrap()).collect();
+        let mut rebuilt = Structure::from_fields(&Field::from_stream(&bytes).unwrap()).unwrap();
+
+        // Everything but the closure of the custom constraint comes back
+        let zip = |structure: &Structure| structure.requirements()[2].structure.as_ref().unwrap().requirements()[1].constraints[0].clone();
+        assert_ne!(rebuilt, structure);
+        assert_eq!(rebuilt.to_fields(), structure.to_fields());
+
+        let homes = |zip: &str| vec![Field::new("homes", Type::List(Some(vec![Type::Doc(Some(vec![Field::new("city", Type::S(Some(S::new("Paris")))), Field::new("zip", Type::S(Some(S::new(zip))))]))])))];
+        let name = Field::new("name", Type::S(Some(S::new("Test"))));
+        assert!(!rebuilt.meets(&[name.clone()].into_iter().chain(homes("75001")).collect::<Vec<Field>>()));
+
+        rebuilt.bind("zip", |value| matches!(value, Type::S(Some(zip)) if zip.as_str().len() == 5));
+        assert!(matches!(zip(&rebuilt), Constraint::Custom(name, _) if name == "zip"));
+        assert!(rebuilt.meets(&[name.clone()].into_iter().chain(homes("75001")).collect::<Vec<Field>>()));
+        assert!(!rebuilt.meets(&[name].into_iter().chain(homes("750")).collect::<Vec<Field>>()));
+
+        assert!(Structure::from_fields(&[Field::new("id", Type::S(Some(S::new("broken"))))]).is_err());
+    }
+
+    // dbio::dbstruct::tests::test_migration_apply() - Tests every kind of migration step
+    //
+    #[test]
+    fn test_migration_apply()
+    {
+        assert!(Migration::new(2, 2).is_err());
+        assert!(Migration::new(3, 2).is_err());
+        assert!(Structure::new("people", vec![]).unwrap().versioned(0).is_err());
+
+        let migration = Migration::new(1, 2).unwrap()
+            .rename("nick", "name")
+            .remove("legacy")
+            .add("active", Type::B(Some(B::new(true))))
+            .add("name", Type::S(None))
+            .retype("age", |value| match value
+            {
+                Type::S(Some(age)) => return Ok(Type::I(Some(age.as_str().parse::<I>()?))),
+                _ => return Ok(Type::I(None)),
+            })
+            .custom(|fields| { fields.sort_by(|a, b| a.id.cmp(&b.id)); return Ok(()); });
+
+        let mut fields = vec!
+        [
+            Field::new("nick", Type::S(Some(S::new("Test")))),
+            Field::new("legacy", Type::I(None)),
+            Field::new("age", Type::S(Some(S::new("42")))),
+        ];
+
+        migration.apply(&mut fields).unwrap();
+
+        assert_eq!(fields, vec!
+        [
+            Field::new("active", Type::B(Some(B::new(true)))),
+            Field::new("age", Type::I(Some(I::new(42)))),
+            Field::new("name", Type::S(Some(S::new("Test")))),
+        ]);
+
+        let mut fields = vec![Field::new("age", Type::S(Some(S::new("forty"))))];
+        assert!(migration.apply(&mut fields).is_err());
+        assert_eq!((migration.from_version(), migration.to_version()), (1, 2));
+    }
+}
@@ -1,12 +1,12 @@
 // To be implemented...
 
-use std::io::Write;
-use crate::Field;
-use crate::dbio::dbfield::FieldCmp;
-use std::io::Read;
+use crate::dbio::dbfield::{Field, FieldCmp};
+use crate::dbio::dbchunk::{ChunkyFile, EntryChunk, JournalPatch};
+use crate::dbio::dblist::Entry;
+use crate::apetypes::Type;
 use std::error::Error;
-use std::io::Seek;
-use std::fs::File;
+use std::ops::Bound;
+use simple_error::*;
 
 pub mod LAZY_AVL_CONST
 {
@@ -14,20 +14,61 @@ pub mod LAZY_AVL_CONST
     pub const BF_OFFSET: u64 = 0;
     pub const LC_OFFSET: u64 = 1;
     pub const RC_OFFSET: u64 = 9;
+    pub const NODE_HEADSZ: usize = 17; // The balance byte and both child pointers
 }
 
+// dbio::dbtree::Node - The tree metadata at the start of a field, see LazyAVL::read_node()
+//
+#[derive(Clone)]
+struct Node
+{
+    balance: i64, // The height of the right subtree minus the height of the left subtree
+    left_child: u64,
+    right_child: u64,
+}
+
+impl Node
+{
+    // dbio::dbtree::Node::to_bytes - Encode the node the way it starts a field
+    //
+    fn to_bytes(&self) -> Vec<u8>
+    {
+        let balance = i8::try_from(self.balance).expect("Balance doesn't fit in its byte, you shouldn't see this!");
+
+        let mut data = balance.to_be_bytes().to_vec();
+        data.extend_from_slice(&self.left_child.to_be_bytes());
+        data.extend_from_slice(&self.right_child.to_be_bytes());
+
+        return data;
+    }
+}
+
+// dbio::dbtree::LazyAVL - An AVL tree of fields, stored in the fields themselves
+//
+// Every field starts with a balance byte and pointers to its left and right children. Greater fields go left, so walking
+// the tree left to right gives the fields from greatest to least. The tree is only rebalanced once a subtree's balance
+// goes past the laze, a laze of 0 or 1 keeps a strict AVL tree while greater ones trade lookups for fewer rotations.
+//
+// Changes to nodes are staged and made all at once by commit(), through a journal if they take more than one write, so
+// an insert or removal cut short is either finished when the file is next opened or never happened.
 pub struct LazyAVL
 {
-    file: File,
+    file: ChunkyFile,
     pub head: u64,
     laze: u8,
+    journal: u64, // The journal changes go through, 0 until one is needed
+    journal_capacity: usize, // How much the journal holds, 0 until it is read
+    pending: Vec<(u64, Node)>, // Staged changes, by the insertion point of the field
+    pending_head: Option<u64>, // Staged change of the head
+    pending_writes: Vec<JournalPatch>, // Other writes to make along with the staged changes
+    head_store: u64, // Where in the file the head is kept, 0 if it is only kept in memory
 }
 
 impl LazyAVL
 {
-    pub fn new(file: File, head: u64, laze: u8) -> Self
+    pub fn new(file: ChunkyFile, head: u64, laze: u8) -> Self
     {
-        if(laze > LAZY_AVL_CONST::LAZE_MAX)
+        if laze > LAZY_AVL_CONST::LAZE_MAX
         {
             panic!("There was an attempt to create a VERY lazy avl tree, you should not see this!");
         }
@@ -36,75 +77,1574 @@ impl LazyAVL
         {
             file,
             head,
-            laze
+            laze,
+            journal: 0,
+            journal_capacity: 0,
+            pending: Vec::new(),
+            pending_head: None,
+            pending_writes: Vec::new(),
+            head_store: 0,
         };
     }
 
+    // dbio::dbtree::LazyAVL::with_journal - Use an existing journal for changes, instead of adding one to the file when needed
+    //
+    // ARGUMENTS:
+    //  journal_pos: u64 - The position of the journal, see ChunkyFile::add_journal()
+    pub fn with_journal(mut self, journal_pos: u64) -> Self
+    {
+        self.journal = journal_pos;
+        self.journal_capacity = 0;
+
+        return self;
+    }
+
+    // dbio::dbtree::LazyAVL::laze - Get how far the tree may lean before it is rebalanced, see max_balance()
+    //
+    pub fn laze(&self) -> u8
+    {
+        return self.laze;
+    }
+
+    // dbio::dbtree::LazyAVL::max_balance - Get how imbalanced a subtree may be before it is rebalanced
+    //
+    pub fn max_balance(&self) -> i64
+    {
+        return std::cmp::max(self.laze, 1) as i64;
+    }
+
+    // dbio::dbtree::LazyAVL::with_head_store - Keep the head in the file, changing it along with the rest of the tree
+    //
+    // ARGUMENTS:
+    //  head_store: u64 - Where in the file the head is kept as a u64, such as in a list chunk
+    pub fn with_head_store(mut self, head_store: u64) -> Self
+    {
+        self.head_store = head_store;
+
+        return self;
+    }
+
+    // dbio::dbtree::LazyAVL::set_head_store - Start keeping the head in the file, without writing it there yet
+    //
+    // ARGUMENTS:
+    //  head_store: u64 - Where in the file the head is kept as a u64, or 0 to only keep it in memory
+    pub fn set_head_store(&mut self, head_store: u64)
+    {
+        self.head_store = head_store;
+    }
+
+    // dbio::dbtree::LazyAVL::read_node - Read the balance and children of a field, without the rest of the field
+    //
+    // Staged changes are seen before they are committed.
+    //
+    // ARGUMENTS:
+    //  field_pos: u64 - The insertion point of the field
+    fn read_node(&mut self, field_pos: u64) -> Result<Node, Box<dyn Error>>
+    {
+        if let Some((_, node)) = self.pending.iter().find(|(pending_pos, _)| *pending_pos == field_pos)
+        {
+            return Ok(node.clone());
+        }
+
+        let data = self.file.read_at(field_pos, LAZY_AVL_CONST::NODE_HEADSZ)?;
+
+        if data.len() != LAZY_AVL_CONST::NODE_HEADSZ
+        {
+            bail!("Field at {} is too short to be a tree node!", field_pos);
+        }
+
+        return Ok
+        (
+            Node
+            {
+                balance: data[0] as i8 as i64,
+                left_child: u64::from_be_bytes(data[1..9].try_into().expect("Slice of incorrect size when reading a child, you shouldn't see this!")),
+                right_child: u64::from_be_bytes(data[9..17].try_into().expect("Slice of incorrect size when reading a child, you shouldn't see this!")),
+            }
+        );
+    }
+
+    // dbio::dbtree::LazyAVL::stage_node - Stage a change to the balance and children of a field, see commit()
+    //
+    // ARGUMENTS:
+    //  field_pos: u64 - The insertion point of the field
+    //  node: Node - The new balance and children
+    fn stage_node(&mut self, field_pos: u64, node: Node)
+    {
+        match self.pending.iter_mut().find(|(pending_pos, _)| *pending_pos == field_pos)
+        {
+            Some((_, pending_node)) => *pending_node = node,
+            None => self.pending.push((field_pos, node)),
+        }
+    }
+
+    // dbio::dbtree::LazyAVL::stage_head - Stage a change of the head, see commit()
+    //
+    // ARGUMENTS:
+    //  new_head: u64 - The insertion point of the new head, or 0 for an empty tree
+    fn stage_head(&mut self, new_head: u64)
+    {
+        self.pending_head = Some(new_head);
+    }
+
+    // dbio::dbtree::LazyAVL::stage_write - Stage a write to make along with the next change to the tree, see commit()
+    //
+    // ARGUMENTS:
+    //  pos: u64 - The position in the file to write at, inside the data of a chain
+    //  data: Vec<u8> - The bytes to write
+    pub fn stage_write(&mut self, pos: u64, data: Vec<u8>)
+    {
+        self.pending_writes.push((pos, data));
+    }
+
+    // dbio::dbtree::LazyAVL::commit - Make every staged change, all at once
+    //
+    // A single write inside one chunk is made directly, anything else is recorded in the journal first so it can be
+    // finished by ChunkyFile::replay_journals() if it is cut short.
+    pub fn commit(&mut self) -> Result<(), Box<dyn Error>>
+    {
+        let (patches, pending_head) = self.take_staged();
+
+        if patches.len() == 1 && ChunkyFile::within_chunk(patches[0].0, patches[0].1.len())
+        {
+            self.file.write_at(patches[0].0, &patches[0].1)?;
+        }
+        else if !patches.is_empty()
+        {
+            let journal_size = ChunkyFile::journal_size(&patches);
+
+            if self.journal != 0 && self.journal_capacity == 0
+            {
+                self.journal_capacity = self.file.journal_capacity(self.journal)?;
+            }
+
+            // Outgrown journals are left behind, disarmed
+            if self.journal == 0 || self.journal_capacity < journal_size
+            {
+                self.journal = self.file.add_journal(journal_size)?;
+                self.journal_capacity = self.file.journal_capacity(self.journal)?;
+            }
+
+            self.file.write_journal(self.journal, &patches)?;
+            self.file.replay_journal(self.journal)?;
+        }
+
+        if let Some(new_head) = pending_head
+        {
+            self.head = new_head;
+        }
+
+        return Ok(());
+    }
+
+    // dbio::dbtree::LazyAVL::commit_with - Make every staged change of this tree and of other trees in the same file, all at once
+    //
+    // The changes of the other trees go through the journal of this one, so either every tree changes or none of them do.
+    //
+    // ARGUMENTS:
+    //  others: &mut [&mut LazyAVL] - The other trees, which may have nothing staged
+    pub fn commit_with(&mut self, others: &mut [&mut LazyAVL]) -> Result<(), Box<dyn Error>>
+    {
+        let mut pending_heads = Vec::<Option<u64>>::with_capacity(others.len());
+
+        for other in others.iter_mut()
+        {
+            let (mut patches, pending_head) = other.take_staged();
+
+            self.pending_writes.append(&mut patches);
+            pending_heads.push(pending_head);
+        }
+
+        self.commit()?;
+
+        for (other, pending_head) in others.iter_mut().zip(pending_heads)
+        {
+            if let Some(new_head) = pending_head
+            {
+                other.head = new_head;
+            }
+        }
+
+        return Ok(());
+    }
+
+    // dbio::dbtree::LazyAVL::take_staged - Take every staged change as writes to the file, along with the staged head if there is one
+    //
+    fn take_staged(&mut self) -> (Vec<JournalPatch>, Option<u64>)
+    {
+        let mut patches: Vec<JournalPatch> = self.pending.drain(..).map(|(field_pos, node)| (field_pos, node.to_bytes())).collect();
+        let pending_head = self.pending_head.take();
+
+        if let Some(new_head) = pending_head.filter(|_| self.head_store != 0)
+        {
+            patches.push((self.head_store, new_head.to_be_bytes().to_vec()));
+        }
+
+        patches.append(&mut self.pending_writes);
+
+        return (patches, pending_head);
+    }
+
+    // dbio::dbtree::LazyAVL::discard - Throw away every staged change
+    //
+    pub fn discard(&mut self)
+    {
+        self.pending.clear();
+        self.pending_head = None;
+        self.pending_writes.clear();
+    }
+
+    // dbio::dbtree::LazyAVL::field_change_balance - Stage a change to the balance of a field, see commit()
+    //
+    // ARGUMENTS:
+    //  field_pos: u64 - The insertion point of the field
+    //  new_balance: i64 - The new balance, which has to fit in the balance byte
+    pub fn field_change_balance(&mut self, field_pos: u64, new_balance: i64) -> Result<(), Box<dyn Error>>
+    {
+        if i8::try_from(new_balance).is_err()
+        {
+            bail!("Balance {} of the field at {} doesn't fit in its balance byte!", new_balance, field_pos);
+        }
+
+        let mut node = self.read_node(field_pos)?;
+        node.balance = new_balance;
+        self.stage_node(field_pos, node);
+
+        return Ok(());
+    }
+
+    // dbio::dbtree::LazyAVL::field_change_left_child - Stage a change to the left child of a field, see commit()
+    //
+    // ARGUMENTS:
+    //  field_pos: u64 - The insertion point of the field
+    //  new_child: u64 - The insertion point of the new child, or 0 for none
     pub fn field_change_left_child(&mut self, field_pos: u64, new_child: u64) -> Result<(), Box<dyn Error>>
     {
-        let new_child_data = new_child.to_be_bytes();
-        self.file.seek(std::io::SeekFrom::Start(field_pos + LAZY_AVL_CONST::LC_OFFSET))?;
-        self.file.write_all(&new_child_data)?;
+        let mut node = self.read_node(field_pos)?;
+        node.left_child = new_child;
+        self.stage_node(field_pos, node);
 
         return Ok(());
     }
 
+    // dbio::dbtree::LazyAVL::field_change_right_child - Stage a change to the right child of a field, see commit()
+    //
+    // ARGUMENTS:
+    //  field_pos: u64 - The insertion point of the field
+    //  new_child: u64 - The insertion point of the new child, or 0 for none
     pub fn field_change_right_child(&mut self, field_pos: u64, new_child: u64) -> Result<(), Box<dyn Error>>
     {
-        let new_child_data = new_child.to_be_bytes();
-        self.file.seek(std::io::SeekFrom::Start(field_pos + LAZY_AVL_CONST::RC_OFFSET))?;
-        self.file.write_all(&new_child_data)?;
+        let mut node = self.read_node(field_pos)?;
+        node.right_child = new_child;
+        self.stage_node(field_pos, node);
 
         return Ok(());
     }
 
+    // dbio::dbtree::LazyAVL::change_child - Stage pointing a parent, or the head if there is none, at a new child
+    //
+    // ARGUMENTS:
+    //  parent: Option<(u64, bool)> - The insertion point of the parent, and whether the child is on its right
+    //  new_child: u64 - The insertion point of the new child, or 0 for none
+    fn change_child(&mut self, parent: Option<(u64, bool)>, new_child: u64) -> Result<(), Box<dyn Error>>
+    {
+        match parent
+        {
+            Some((parent_pos, true)) => self.field_change_right_child(parent_pos, new_child)?,
+            Some((parent_pos, false)) => self.field_change_left_child(parent_pos, new_child)?,
+            None => self.stage_head(new_child),
+        }
+
+        return Ok(());
+    }
+
+    // dbio::dbtree::LazyAVL::insert - Link a field into the tree, rebalancing it on the way back up
+    //
+    // ARGUMENTS:
+    //  field_pos: u64 - The insertion point of the field, which must not be in the tree already
     pub fn insert(&mut self, field_pos: u64) -> Result<(), Box<dyn Error>>
     {
-        let mut field_data: [u8;256] = [0; 256];
-        let mut node_history: Vec<u64> = Vec::new();
-        self.file.seek(std::io::SeekFrom::Start(field_pos))?;
-        self.file.read(&mut field_data)?;
-        let field_to_insert = Field::from_bytes(&field_data)?;
+        let result = self.stage_insert(field_pos).and_then(|_| self.commit());
+
+        if result.is_err()
+        {
+            self.discard();
+        }
+
+        return result;
+    }
+
+    // dbio::dbtree::LazyAVL::stage_insert - Stage every change needed to insert a field, see insert()
+    //
+    // Only one insertion or removal can be staged at a time, then it has to be committed or discarded.
+    //
+    // ARGUMENTS:
+    //  field_pos: u64 - The insertion point of the field
+    pub fn stage_insert(&mut self, field_pos: u64) -> Result<(), Box<dyn Error>>
+    {
+        // Anything left over from where the field was before has to go, the field becomes a leaf
+        let leaf = self.read_node(field_pos)?;
+
+        if leaf.balance != 0 || leaf.left_child != 0 || leaf.right_child != 0
+        {
+            self.stage_node(field_pos, Node { balance: 0, left_child: 0, right_child: 0 });
+        }
+
+        if self.head == 0
+        {
+            self.stage_head(field_pos);
+
+            return Ok(());
+        }
+
+        let mut node_history: Vec<(u64, bool)> = Vec::new(); // Every node passed, and whether we went right from it
+        let field_to_insert = self.file.read_field(field_pos)?;
         
         let mut current_node_pos = self.head;
-        let mut greater_than: bool = false;
 
         while current_node_pos != 0
         {
-            self.file.seek(std::io::SeekFrom::Start(current_node_pos))?;
-            self.file.read(&mut field_data)?;
+            let current_node = self.file.read_field(current_node_pos)?;
+
+            let greater_than = match current_node.cmp(&field_to_insert)?
+            {
+                FieldCmp::Equal | FieldCmp::LessThan => false,
+                FieldCmp::GreaterThan => true,
+            };
+
+            node_history.push((current_node_pos, greater_than));
+
+            current_node_pos = if greater_than { current_node.right_child } else { current_node.left_child };
+        }
+
+        self.change_child(node_history.last().copied(), field_pos)?;
+
+        // The new leaf grew its side of its parent by one
+        self.retrace(node_history, 1)?;
+        
+        return Ok(());
+    }
+
+    // dbio::dbtree::LazyAVL::remove - Unlink a field from the tree, rebalancing it on the way back up, returning whether it was in the tree
+    //
+    // A field with two children swaps places with its in-order successor, the next field walking the tree left to right,
+    // which never has a left child and so is easy to unlink. Removing a field that isn't in the tree changes nothing.
+    //
+    // ARGUMENTS:
+    //  field_pos: u64 - The insertion point of the field
+    pub fn remove(&mut self, field_pos: u64) -> Result<bool, Box<dyn Error>>
+    {
+        let result = self.stage_remove(field_pos).and_then(|removed|
+        {
+            self.commit()?;
+            Ok(removed)
+        });
+
+        if result.is_err()
+        {
+            self.discard();
+        }
+
+        return result;
+    }
+
+    // dbio::dbtree::LazyAVL::stage_remove - Stage every change needed to remove a field, see remove()
+    //
+    // Like stage_insert(), the removal has to be committed or discarded before anything else is staged.
+    //
+    // ARGUMENTS:
+    //  field_pos: u64 - The insertion point of the field
+    pub fn stage_remove(&mut self, field_pos: u64) -> Result<bool, Box<dyn Error>>
+    {
+        let mut node_history = Vec::<(u64, bool)>::new();
+        let field_to_remove = self.file.read_field(field_pos)?;
+        let head = self.head;
+
+        if !self.path_to(head, field_pos, &field_to_remove, &mut node_history)?
+        {
+            return Ok(false);
+        }
+
+        let node = self.read_node(field_pos)?;
+        let parent = node_history.last().copied();
+
+        if node.left_child == 0 || node.right_child == 0
+        {
+            // With one child or none, the child takes the field's place
+            let child_pos = if node.left_child == 0 { node.right_child } else { node.left_child };
+            self.change_child(parent, child_pos)?;
 
-            node_history.push(current_node_pos);
+            self.retrace(node_history, -1)?;
 
-            let current_node = Field::from_bytes(&field_data)?;
+            return Ok(true);
+        }
+
+        // Otherwise find the successor, the leftmost field of the right subtree
+        let mut successor_history = Vec::<(u64, bool)>::new();
+        let mut successor_pos = node.right_child;
+
+        loop
+        {
+            let successor = self.read_node(successor_pos)?;
 
-            current_node_pos = match current_node.cmp(&field_to_insert)?
+            if successor.left_child == 0
             {
-                FieldCmp::Equal | FieldCmp::LessThan =>
-                {
-                    greater_than = false;
-                    current_node.left_child
-                }
+                break;
+            }
 
-                FieldCmp::GreaterThan =>
-                {
-                    greater_than = true;
-                    current_node.right_child
-                }
+            successor_history.push((successor_pos, false));
+            successor_pos = successor.left_child;
+        }
+
+        let successor = self.read_node(successor_pos)?;
+
+        // Unlink the successor from where it was, unless that was right under the field
+        let right_child = match successor_history.last()
+        {
+            Some((successor_parent_pos, _)) =>
+            {
+                self.field_change_left_child(*successor_parent_pos, successor.right_child)?;
+                node.right_child
             }
+            None => successor.right_child,
+        };
+
+        // Then put it in the field's place
+        self.stage_node(successor_pos, Node { balance: node.balance, left_child: node.left_child, right_child: right_child });
+        self.change_child(parent, successor_pos)?;
+
+        // The successor's old spot lost a level, which is on the left of its old parent or on the right of the successor
+        // itself, and everything above that now goes through the successor
+        node_history.push((successor_pos, true));
+        node_history.extend(successor_history);
+
+        self.retrace(node_history, -1)?;
+
+        return Ok(true);
+    }
+
+    // dbio::dbtree::LazyAVL::path_to - Find the path from a node down to a field, returning whether the field was found
+    //
+    // Equal fields can be on either side of each other, so both sides are searched when a node is equal to the field.
+    //
+    // ARGUMENTS:
+    //  node_pos: u64 - The node to start from, or 0 for an empty subtree
+    //  field_pos: u64 - The insertion point of the field to find
+    //  field: &Field - The field to find
+    //  node_history: &mut Vec<(u64, bool)> - The path so far, which the path to the parent of the field is added to
+    fn path_to(&mut self, node_pos: u64, field_pos: u64, field: &Field, node_history: &mut Vec<(u64, bool)>) -> Result<bool, Box<dyn Error>>
+    {
+        if node_pos == 0
+        {
+            return Ok(false);
+        }
+
+        if node_pos == field_pos
+        {
+            return Ok(true);
         }
 
-        current_node_pos = node_history.pop().unwrap();
+        let node = self.file.read_field(node_pos)?;
+
+        let sides: &[bool] = match node.cmp(field)?
+        {
+            FieldCmp::Equal => &[false, true],
+            FieldCmp::LessThan => &[false],
+            FieldCmp::GreaterThan => &[true],
+        };
 
-        if greater_than
+        for went_right in sides
         {
-            self.field_change_right_child(current_node_pos, field_pos)?;
+            node_history.push((node_pos, *went_right));
+
+            if self.path_to(if *went_right { node.right_child } else { node.left_child }, field_pos, field, node_history)?
+            {
+                return Ok(true);
+            }
+
+            node_history.pop();
         }
-        else
+
+        return Ok(false);
+    }
+
+    // dbio::dbtree::LazyAVL::retrace - Walk back up the tree after a subtree changed height, fixing balances and rotating
+    //
+    // ARGUMENTS:
+    //  node_history: Vec<(u64, bool)> - The path from the head to the parent of the changed subtree, and which way it went
+    //  height_change: i64 - How much the height of the changed subtree went up or down by
+    fn retrace(&mut self, mut node_history: Vec<(u64, bool)>, mut height_change: i64) -> Result<(), Box<dyn Error>>
+    {
+        while height_change != 0
         {
-            self.field_change_left_child(current_node_pos, field_pos)?;
+            let (node_pos, went_right) = match node_history.pop()
+            {
+                Some(step) => step,
+                None => break,
+            };
+
+            let node = self.read_node(node_pos)?;
+            let (balance, mut node_change) = LazyAVL::child_height_change(node.balance, went_right, height_change);
+
+            if balance.abs() <= self.max_balance()
+            {
+                self.field_change_balance(node_pos, balance)?;
+                height_change = node_change;
+
+                continue;
+            }
+
+            let (new_root, rotation_change) = self.rebalance(node_pos, balance)?;
+            node_change += rotation_change;
+
+            // Whatever pointed at the old root of the subtree has to point at the new one
+            self.change_child(node_history.last().copied(), new_root)?;
+
+            height_change = node_change;
         }
-        
+
         return Ok(());
     }
-}
\ No newline at end of file
+
+    // dbio::dbtree::LazyAVL::child_height_change - Work out a node's new balance and height change when a child's height changes
+    //
+    // ARGUMENTS:
+    //  balance: i64 - The balance of the node
+    //  right: bool - Whether the right child changed, otherwise the left one did
+    //  change: i64 - How much the child's height changed by
+    fn child_height_change(balance: i64, right: bool, change: i64) -> (i64, i64)
+    {
+        // Measure heights from the left child, so the right child's height is the balance
+        let old_height = std::cmp::max(0, balance);
+
+        if right
+        {
+            return (balance + change, std::cmp::max(0, balance + change) - old_height);
+        }
+
+        return (balance - change, std::cmp::max(change, balance) - old_height);
+    }
+
+    // dbio::dbtree::LazyAVL::rebalance - Rotate a subtree that got too imbalanced, returning its new root and height change
+    //
+    // ARGUMENTS:
+    //  node_pos: u64 - The root of the subtree
+    //  balance: i64 - The balance of the root, which hasn't been written yet
+    fn rebalance(&mut self, node_pos: u64, mut balance: i64) -> Result<(u64, i64), Box<dyn Error>>
+    {
+        let node = self.read_node(node_pos)?;
+        let mut height_change = 0;
+
+        if balance > 0
+        {
+            // A right child leaning left needs turning first, making it a double rotation
+            let child_balance = self.read_node(node.right_child)?.balance;
+
+            if child_balance < 0
+            {
+                let (child, child_change) = self.rotate_right(node.right_child, child_balance)?;
+                self.field_change_right_child(node_pos, child)?;
+
+                let (new_balance, node_change) = LazyAVL::child_height_change(balance, true, child_change);
+                balance = new_balance;
+                height_change += node_change;
+            }
+
+            let (new_root, rotation_change) = self.rotate_left(node_pos, balance)?;
+
+            return Ok((new_root, height_change + rotation_change));
+        }
+
+        let child_balance = self.read_node(node.left_child)?.balance;
+
+        if child_balance > 0
+        {
+            let (child, child_change) = self.rotate_left(node.left_child, child_balance)?;
+            self.field_change_left_child(node_pos, child)?;
+
+            let (new_balance, node_change) = LazyAVL::child_height_change(balance, false, child_change);
+            balance = new_balance;
+            height_change += node_change;
+        }
+
+        let (new_root, rotation_change) = self.rotate_right(node_pos, balance)?;
+
+        return Ok((new_root, height_change + rotation_change));
+    }
+
+    // dbio::dbtree::LazyAVL::rotate_left - Make a node's right child the root of its subtree, returning it and the height change
+    //
+    // ARGUMENTS:
+    //  node_pos: u64 - The root of the subtree
+    //  balance: i64 - The balance of the root, which may not have been written yet
+    fn rotate_left(&mut self, node_pos: u64, balance: i64) -> Result<(u64, i64), Box<dyn Error>>
+    {
+        let node = self.read_node(node_pos)?;
+        let child_pos = node.right_child;
+        let child = self.read_node(child_pos)?;
+
+        // Heights are measured from the child's left subtree, which moves across to the node
+        let child_height = 1 + std::cmp::max(0, child.balance);
+        let node_left_height = child_height - balance;
+        let old_height = 1 + std::cmp::max(node_left_height, child_height);
+
+        let node_balance = -node_left_height;
+        let node_height = 1 + std::cmp::max(node_left_height, 0);
+        let child_balance = child.balance - node_height;
+        let new_height = 1 + std::cmp::max(node_height, child.balance);
+
+        self.field_change_right_child(node_pos, child.left_child)?;
+        self.field_change_left_child(child_pos, node_pos)?;
+        self.field_change_balance(node_pos, node_balance)?;
+        self.field_change_balance(child_pos, child_balance)?;
+
+        return Ok((child_pos, new_height - old_height));
+    }
+
+    // dbio::dbtree::LazyAVL::rotate_right - Make a node's left child the root of its subtree, returning it and the height change
+    //
+    // ARGUMENTS:
+    //  node_pos: u64 - The root of the subtree
+    //  balance: i64 - The balance of the root, which may not have been written yet
+    fn rotate_right(&mut self, node_pos: u64, balance: i64) -> Result<(u64, i64), Box<dyn Error>>
+    {
+        let node = self.read_node(node_pos)?;
+        let child_pos = node.left_child;
+        let child = self.read_node(child_pos)?;
+
+        // Heights are measured from the child's right subtree, which moves across to the node
+        let child_left_height = -child.balance;
+        let child_height = 1 + std::cmp::max(child_left_height, 0);
+        let node_right_height = child_height + balance;
+        let old_height = 1 + std::cmp::max(child_height, node_right_height);
+
+        let node_balance = node_right_height;
+        let node_height = 1 + std::cmp::max(0, node_right_height);
+        let child_balance = node_height - child_left_height;
+        let new_height = 1 + std::cmp::max(child_left_height, node_height);
+
+        self.field_change_left_child(node_pos, child.right_child)?;
+        self.field_change_right_child(child_pos, node_pos)?;
+        self.field_change_balance(node_pos, node_balance)?;
+        self.field_change_balance(child_pos, child_balance)?;
+
+        return Ok((child_pos, new_height - old_height));
+    }
+
+    // dbio::dbtree::LazyAVL::search - Find a field in the tree equal to the probe, returning its insertion point
+    //
+    // ARGUMENTS:
+    //  probe: &Field - A field with the ID and value to look for
+    pub fn search(&mut self, probe: &Field) -> Result<Option<u64>, Box<dyn Error>>
+    {
+        let mut current_node_pos = self.head;
+
+        // Walk the tree the same way insert() does
+        while current_node_pos != 0
+        {
+            let current_node = self.file.read_field(current_node_pos)?;
+
+            current_node_pos = match current_node.cmp(probe)?
+            {
+                FieldCmp::Equal =>
+                {
+                    return Ok(Some(current_node_pos));
+                }
+
+                FieldCmp::LessThan =>
+                {
+                    current_node.left_child
+                }
+
+                FieldCmp::GreaterThan =>
+                {
+                    current_node.right_child
+                }
+            }
+        }
+
+        return Ok(None);
+    }
+
+    // dbio::dbtree::LazyAVL::find - Find every field in the tree with the given ID and value, returning their insertion points in tree order
+    //
+    // Equal fields can end up on both sides of each other after a rotation, so only the subtrees that can't hold a match
+    // are skipped.
+    //
+    // ARGUMENTS:
+    //  id: &str - The ID of the field
+    //  value: &Type - The value of the field
+    pub fn find(&mut self, id: &str, value: &Type) -> Result<Vec<u64>, Box<dyn Error>>
+    {
+        let probe = Field::new(id, value.clone());
+        let mut positions = Vec::<u64>::new();
+        let mut node_history: Vec<(u64, u64)> = Vec::new(); // Matches still to be taken, along with their right child
+        let mut current_node_pos = self.head;
+
+        while current_node_pos != 0 || !node_history.is_empty()
+        {
+            // Same walk as positions(), but only going down the sides a match could be on
+            while current_node_pos != 0
+            {
+                let current_node = self.file.read_field(current_node_pos)?;
+
+                current_node_pos = match current_node.cmp(&probe)?
+                {
+                    FieldCmp::Equal =>
+                    {
+                        node_history.push((current_node_pos, current_node.right_child));
+                        current_node.left_child
+                    }
+
+                    FieldCmp::LessThan =>
+                    {
+                        current_node.left_child
+                    }
+
+                    FieldCmp::GreaterThan =>
+                    {
+                        current_node.right_child
+                    }
+                };
+            }
+
+            if let Some((node_pos, right_child)) = node_history.pop()
+            {
+                positions.push(node_pos);
+                current_node_pos = right_child;
+            }
+        }
+
+        return Ok(positions);
+    }
+
+    // dbio::dbtree::LazyAVL::find_entry - Find a field in the tree with the given ID and value, returning the entry it belongs to
+    //
+    // The entry is returned as it was written, see List::get_entry() for upcasting it to the current structure.
+    //
+    // ARGUMENTS:
+    //  id: &str - The ID of the field
+    //  value: &Type - The value of the field
+    pub fn find_entry(&mut self, id: &str, value: &Type) -> Result<Option<Entry>, Box<dyn Error>>
+    {
+        let field_pos = match self.search(&Field::new(id, value.clone()))?
+        {
+            Some(field_pos) => field_pos,
+            None => return Ok(None),
+        };
+
+        return Ok(Some(Entry::from_chunk(self.entry_chunk(field_pos)?)));
+    }
+
+    // dbio::dbtree::LazyAVL::entry_chunk - Read the entry chunk a field in the tree belongs to
+    //
+    // ARGUMENTS:
+    //  field_pos: u64 - The insertion point of the field
+    pub fn entry_chunk(&mut self, field_pos: u64) -> Result<EntryChunk, Box<dyn Error>>
+    {
+        let chunk_pos = self.file.chain_start(field_pos)?;
+        let (_, entry_chunk) = self.file.read_entry_chunk(chunk_pos)?;
+
+        return Ok(entry_chunk);
+    }
+
+    // dbio::dbtree::LazyAVL::cursor - Get a cursor over the tree, which has to be seeked before it points at anything
+    //
+    pub fn cursor(&mut self) -> Cursor<'_>
+    {
+        return Cursor
+        {
+            tree: self,
+            path: Vec::<u64>::new(),
+        };
+    }
+
+    // dbio::dbtree::LazyAVL::range - Stream the insertion points of every field with an ID whose value is between two bounds, from least to greatest
+    //
+    // ARGUMENTS:
+    //  id: &str - The ID of the fields
+    //  lower: Bound<&Type> - The least value, if any
+    //  upper: Bound<&Type> - The greatest value, if any
+    pub fn range(&mut self, id: &str, lower: Bound<&Type>, upper: Bound<&Type>) -> Result<Range<'_>, Box<dyn Error>>
+    {
+        let mut cursor = self.cursor();
+
+        match lower
+        {
+            Bound::Included(value) => cursor.seek(&Field::new(id, value.clone()), true)?,
+            Bound::Excluded(value) => cursor.seek(&Field::new(id, value.clone()), false)?,
+            Bound::Unbounded => cursor.seek_by(true, |field| Ok(field.id.as_str() >= id))?,
+        };
+
+        return Ok
+        (
+            Range
+            {
+                cursor: cursor,
+                id: id.to_string(),
+                upper: upper.cloned(),
+                done: false,
+            }
+        );
+    }
+
+    // dbio::dbtree::LazyAVL::positions - Get the insertion point of every field in the tree, in tree order
+    //
+    pub fn positions(&mut self) -> Result<Vec<u64>, Box<dyn Error>>
+    {
+        let mut positions = Vec::<u64>::new();
+        let mut node_history: Vec<(u64, u64)> = Vec::new(); // Nodes still to be taken, along with their right child
+        let mut current_node_pos = self.head;
+
+        while current_node_pos != 0 || !node_history.is_empty()
+        {
+            // Go as far left as possible, then take the node and go right once
+            while current_node_pos != 0
+            {
+                let current_node = self.file.read_field(current_node_pos)?;
+
+                node_history.push((current_node_pos, current_node.right_child));
+                current_node_pos = current_node.left_child;
+            }
+
+            let (node_pos, right_child) = node_history.pop().expect("Node history empty while walking the tree! You shouldn't see this!");
+            positions.push(node_pos);
+
+            current_node_pos = right_child;
+        }
+
+        return Ok(positions);
+    }
+
+    // dbio::dbtree::LazyAVL::check - Walk the whole tree checking its order and every balance byte, returning its height
+    //
+    // Meant for tests and recovery tools, it reads every field in the tree.
+    pub fn check(&mut self) -> Result<u64, Box<dyn Error>>
+    {
+        let head = self.head;
+
+        return self.check_subtree(head, None, None);
+    }
+
+    // dbio::dbtree::LazyAVL::check_subtree - Check a subtree, see check()
+    //
+    // ARGUMENTS:
+    //  node_pos: u64 - The root of the subtree, or 0 for an empty one
+    //  lower: Option<&Field> - A field every field in the subtree must be greater than or equal to
+    //  upper: Option<&Field> - A field every field in the subtree must be less than or equal to
+    fn check_subtree(&mut self, node_pos: u64, lower: Option<&Field>, upper: Option<&Field>) -> Result<u64, Box<dyn Error>>
+    {
+        if node_pos == 0
+        {
+            return Ok(0);
+        }
+
+        let node = self.file.read_field(node_pos)?;
+
+        if lower.is_some_and(|lower| matches!(node.cmp(lower), Ok(FieldCmp::LessThan))) || upper.is_some_and(|upper| matches!(node.cmp(upper), Ok(FieldCmp::GreaterThan)))
+        {
+            bail!("Field at {} is out of order!", node_pos);
+        }
+
+        // Greater fields go left
+        let left_height = self.check_subtree(node.left_child, Some(&node), upper)?;
+        let right_height = self.check_subtree(node.right_child, lower, Some(&node))?;
+        let balance = right_height as i64 - left_height as i64;
+
+        if balance != node.avl_balace as i64
+        {
+            bail!("Field at {} has balance {} but its subtrees differ by {}!", node_pos, node.avl_balace, balance);
+        }
+
+        if balance.abs() > self.max_balance()
+        {
+            bail!("Field at {} is more imbalanced than the laze allows!", node_pos);
+        }
+
+        return Ok(1 + std::cmp::max(left_height, right_height));
+    }
+}
+
+// dbio::dbtree::Cursor - A position in a LazyAVL that can move to the next greater or lesser field
+//
+// The cursor keeps the path from the head down to its field, so moving only reads the fields along the way. It borrows
+// the tree, so the tree can't change under it.
+pub struct Cursor<'a>
+{
+    tree: &'a mut LazyAVL,
+    path: Vec<u64>, // From the head to the current field, empty if the cursor isn't on a field
+}
+
+impl Cursor<'_>
+{
+    // dbio::dbtree::Cursor::position - Get the insertion point of the field the cursor is on, if it is on one
+    //
+    pub fn position(&self) -> Option<u64>
+    {
+        return self.path.last().copied();
+    }
+
+    // dbio::dbtree::Cursor::field - Read the field the cursor is on, if it is on one
+    //
+    pub fn field(&mut self) -> Result<Option<Field>, Box<dyn Error>>
+    {
+        return match self.position()
+        {
+            Some(field_pos) => Ok(Some(self.tree.file.read_field(field_pos)?)),
+            None => Ok(None),
+        };
+    }
+
+    // dbio::dbtree::Cursor::seek_first - Move the cursor to the least field in the tree
+    //
+    pub fn seek_first(&mut self) -> Result<Option<u64>, Box<dyn Error>>
+    {
+        return self.seek_by(true, |_| Ok(true));
+    }
+
+    // dbio::dbtree::Cursor::seek_last - Move the cursor to the greatest field in the tree
+    //
+    pub fn seek_last(&mut self) -> Result<Option<u64>, Box<dyn Error>>
+    {
+        return self.seek_by(false, |_| Ok(true));
+    }
+
+    // dbio::dbtree::Cursor::seek - Move the cursor to the least field after the probe, or equal to it if inclusive
+    //
+    // ARGUMENTS:
+    //  probe: &Field - A field with the ID and value to seek to
+    //  inclusive: bool - Whether a field equal to the probe can be landed on
+    pub fn seek(&mut self, probe: &Field, inclusive: bool) -> Result<Option<u64>, Box<dyn Error>>
+    {
+        return self.seek_by(true, |field| Ok(match field.cmp(probe)?
+        {
+            FieldCmp::GreaterThan => true,
+            FieldCmp::Equal => inclusive,
+            FieldCmp::LessThan => false,
+        }));
+    }
+
+    // dbio::dbtree::Cursor::seek_back - Move the cursor to the greatest field before the probe, or equal to it if inclusive
+    //
+    // ARGUMENTS:
+    //  probe: &Field - A field with the ID and value to seek to
+    //  inclusive: bool - Whether a field equal to the probe can be landed on
+    pub fn seek_back(&mut self, probe: &Field, inclusive: bool) -> Result<Option<u64>, Box<dyn Error>>
+    {
+        return self.seek_by(false, |field| Ok(match field.cmp(probe)?
+        {
+            FieldCmp::LessThan => true,
+            FieldCmp::Equal => inclusive,
+            FieldCmp::GreaterThan => false,
+        }));
+    }
+
+    // dbio::dbtree::Cursor::seek_by - Move the cursor to the least field that is past a target, or the greatest one if going back
+    //
+    // ARGUMENTS:
+    //  forwards: bool - Whether fields get past the target going up or going down
+    //  past: FnMut(&Field) -> Result<bool> - Whether a field is past the target, which has to flip only once going through the tree
+    fn seek_by<P>(&mut self, forwards: bool, mut past: P) -> Result<Option<u64>, Box<dyn Error>>
+    where
+        P: FnMut(&Field) -> Result<bool, Box<dyn Error>>
+    {
+        let mut candidate_depth: Option<usize> = None;
+        let mut current_node_pos = self.tree.head;
+
+        self.path.clear();
+
+        while current_node_pos != 0
+        {
+            let current_node = self.tree.file.read_field(current_node_pos)?;
+            self.path.push(current_node_pos);
+
+            // Greater fields go left, so going forwards past fields lead right to find lesser ones still past the target
+            let go_right = if past(&current_node)?
+            {
+                candidate_depth = Some(self.path.len());
+                forwards
+            }
+            else
+            {
+                !forwards
+            };
+
+            current_node_pos = if go_right { current_node.right_child } else { current_node.left_child };
+        }
+
+        self.path.truncate(candidate_depth.unwrap_or(0));
+
+        return Ok(self.position());
+    }
+
+    // dbio::dbtree::Cursor::next - Move the cursor to the next greater field, returning its insertion point
+    //
+    // Once the cursor runs off the end it isn't on a field anymore, and has to be seeked again.
+    #[allow(clippy::should_implement_trait)] // Reading the file can fail, which Iterator::next() has no room for
+    pub fn next(&mut self) -> Result<Option<u64>, Box<dyn Error>>
+    {
+        return self.step(true);
+    }
+
+    // dbio::dbtree::Cursor::prev - Move the cursor to the next lesser field, returning its insertion point
+    //
+    // Once the cursor runs off the end it isn't on a field anymore, and has to be seeked again.
+    pub fn prev(&mut self) -> Result<Option<u64>, Box<dyn Error>>
+    {
+        return self.step(false);
+    }
+
+    // dbio::dbtree::Cursor::step - Move the cursor to the next greater or lesser field
+    //
+    // ARGUMENTS:
+    //  forwards: bool - Whether to move to a greater field
+    fn step(&mut self, forwards: bool) -> Result<Option<u64>, Box<dyn Error>>
+    {
+        // Going forwards means going left, as greater fields go left
+        let toward = |node: &Node| if forwards { node.left_child } else { node.right_child };
+        let away = |node: &Node| if forwards { node.right_child } else { node.left_child };
+
+        let node_pos = match self.position()
+        {
+            Some(node_pos) => node_pos,
+            None => return Ok(None),
+        };
+
+        // With a subtree that way the next field is the closest one in it
+        let mut child_pos = toward(&self.tree.read_node(node_pos)?);
+
+        if child_pos != 0
+        {
+            while child_pos != 0
+            {
+                self.path.push(child_pos);
+                child_pos = away(&self.tree.read_node(child_pos)?);
+            }
+
+            return Ok(self.position());
+        }
+
+        // Otherwise it's the first parent we came up to from the other side
+        while let Some(child_pos) = self.path.pop()
+        {
+            let parent_pos = match self.position()
+            {
+                Some(parent_pos) => parent_pos,
+                None => break,
+            };
+
+            if away(&self.tree.read_node(parent_pos)?) == child_pos
+            {
+                return Ok(Some(parent_pos));
+            }
+        }
+
+        return Ok(None);
+    }
+}
+
+// dbio::dbtree::Range - The insertion points of fields between two bounds, see LazyAVL::range()
+//
+// Fields are only read as the range is iterated, so it can be stopped early or paged through with take().
+pub struct Range<'a>
+{
+    cursor: Cursor<'a>,
+    id: String,
+    upper: Bound<Type>,
+    done: bool,
+}
+
+impl Range<'_>
+{
+    // dbio::dbtree::Range::in_bounds - Check if the field the cursor is on is still below the upper bound
+    //
+    fn in_bounds(&mut self) -> Result<bool, Box<dyn Error>>
+    {
+        let field = match self.cursor.field()?
+        {
+            Some(field) => field,
+            None => return Ok(false),
+        };
+
+        return match &self.upper
+        {
+            Bound::Included(value) => Ok(field.cmp(&Field::new(&self.id, value.clone()))? != FieldCmp::GreaterThan),
+            Bound::Excluded(value) => Ok(field.cmp(&Field::new(&self.id, value.clone()))? == FieldCmp::LessThan),
+            Bound::Unbounded => Ok(field.id == self.id),
+        };
+    }
+}
+
+impl Iterator for Range<'_>
+{
+    type Item = Result<u64, Box<dyn Error>>;
+
+    fn next(&mut self) -> Option<Self::Item>
+    {
+        if self.done
+        {
+            return None;
+        }
+
+        let field_pos = match self.in_bounds()
+        {
+            Ok(true) => self.cursor.position().expect("Cursor in bounds without a position! You shouldn't see this!"),
+            Ok(false) =>
+            {
+                self.done = true;
+                return None;
+            }
+            Err(error) =>
+            {
+                self.done = true;
+                return Some(Err(error));
+            }
+        };
+
+        if let Err(error) = self.cursor.next()
+        {
+            self.done = true;
+            return Some(Err(error));
+        }
+
+        return Some(Ok(field_pos));
+    }
+}
+
+// Tests!
+//
+
+#[cfg(test)]
+mod tests
+{
+    use std::fs::remove_file;
+    use crate::dbio::dbchunk::DbHeadChunk;
+    use crate::apetypes::I;
+    use crate::dbio::dbuuid::UuidV4;
+    use super::*;
+
+    // dbio::dbtree::tests::build_tree() - Create a fresh database file and insert an entry for every number into a tree
+    //
+    // ARGUMENTS:
+    //  file_name: &str - The file to create, removing any leftovers
+    //  laze: u8 - The laze of the tree
+    //  numbers: &[i64] - The numbers to insert, in order
+    fn build_tree(file_name: &str, laze: u8, numbers: &[i64]) -> LazyAVL
+    {
+        let _ = remove_file(file_name);
+
+        let mut db_file = ChunkyFile::create(file_name, DbHeadChunk::new("test", "tester")).unwrap();
+        let mut tree = LazyAVL::new(db_file.try_clone().unwrap(), 0, laze);
+
+        for number in numbers
+        {
+            let entry = Entry::new(UuidV4::new(), vec![Field::new("n", Type::I(Some(I::new(*number))))]).unwrap();
+
+            for insertion_point in db_file.add_entry_chunk(EntryChunk::new(entry)).unwrap()
+            {
+                tree.insert(insertion_point).unwrap();
+            }
+        }
+
+        return tree;
+    }
+
+    // dbio::dbtree::tests::tree_numbers() - Read every number in a tree, in tree order
+    //
+    fn tree_numbers(tree: &mut LazyAVL) -> Vec<i64>
+    {
+        let mut numbers = Vec::<i64>::new();
+
+        for position in tree.positions().unwrap()
+        {
+            match tree.file.read_field(position).unwrap().value
+            {
+                Type::I(Some(number)) => numbers.push(number.to_i64().unwrap()),
+                _ => panic!("Tree holds something other than a number!"),
+            }
+        }
+
+        return numbers;
+    }
+
+    #[test]
+    fn test_lazy_avl_sorted_inserts()
+    {
+        let file_name = "test_lazy_avl_sorted_inserts.db";
+        let numbers: Vec<i64> = (0..200).collect();
+        let mut tree = build_tree(file_name, 0, &numbers);
+
+        // A strict AVL tree of 200 fields is at most 1.44 * log2(201) high
+        let height = tree.check().unwrap();
+        assert!(height <= 10, "Tree of 200 sorted inserts is {} high", height);
+
+        let mut expected = numbers.clone();
+        expected.reverse();
+        assert_eq!(tree_numbers(&mut tree), expected);
+
+        // Descending inserts mirror the rotations
+        let numbers: Vec<i64> = (0..200).rev().collect();
+        let mut tree = build_tree(file_name, 1, &numbers);
+        assert!(tree.check().unwrap() <= 10);
+        assert_eq!(tree_numbers(&mut tree), numbers);
+
+        remove_file(file_name).unwrap();
+    }
+
+    #[test]
+    fn test_lazy_avl_laze()
+    {
+        let file_name = "test_lazy_avl_laze.db";
+        let numbers: Vec<i64> = (0..200).collect();
+
+        let mut strict_tree = build_tree(file_name, 0, &numbers);
+        let strict_height = strict_tree.check().unwrap();
+
+        let mut lazy_tree = build_tree(file_name, 4, &numbers);
+        let lazy_height = lazy_tree.check().unwrap();
+
+        // Lazier trees may be taller, but are still far from a list
+        assert!(lazy_height >= strict_height);
+        assert!(lazy_height < 40, "Lazy tree of 200 sorted inserts is {} high", lazy_height);
+
+        remove_file(file_name).unwrap();
+    }
+
+    #[test]
+    fn test_lazy_avl_random_inserts()
+    {
+        let file_name = "test_lazy_avl_random_inserts.db";
+        let mut numbers = Vec::<i64>::new();
+        let mut seed: u64 = 0x2545F4914F6CDD1D;
+
+        // Xorshift, with plenty of duplicates thanks to the modulo
+        for _ in 0..300
+        {
+            seed ^= seed << 13;
+            seed ^= seed >> 7;
+            seed ^= seed << 17;
+            numbers.push((seed % 100) as i64 - 50);
+        }
+
+        for laze in [0, 2, 7]
+        {
+            let mut tree = build_tree(file_name, laze, &numbers);
+            tree.check().unwrap();
+
+            let mut expected = numbers.clone();
+            expected.sort_by(|a, b| b.cmp(a));
+            assert_eq!(tree_numbers(&mut tree), expected);
+        }
+
+        remove_file(file_name).unwrap();
+    }
+
+    #[test]
+    fn test_lazy_avl_find()
+    {
+        let file_name = "test_lazy_avl_find.db";
+        let numbers: Vec<i64> = (0..150).map(|i| i % 30).collect();
+
+        for laze in [0, 5]
+        {
+            let mut tree = build_tree(file_name, laze, &numbers);
+
+            for number in 0..30
+            {
+                let positions = tree.find("n", &Type::I(Some(I::new(number)))).unwrap();
+                assert_eq!(positions.len(), 5);
+
+                for position in positions
+                {
+                    assert_eq!(tree.file.read_field(position).unwrap().value, Type::I(Some(I::new(number))));
+                }
+            }
+
+            assert!(tree.find("n", &Type::I(Some(I::new(30)))).unwrap().is_empty());
+            assert!(tree.find("m", &Type::I(Some(I::new(3)))).unwrap().is_empty());
+
+            let entry = tree.find_entry("n", &Type::I(Some(I::new(12)))).unwrap().unwrap();
+            assert_eq!(entry.get_field("n").unwrap().value, Type::I(Some(I::new(12))));
+            assert!(tree.find_entry("n", &Type::I(Some(I::new(-1)))).unwrap().is_none());
+        }
+
+        remove_file(file_name).unwrap();
+    }
+
+    #[test]
+    fn test_lazy_avl_cursor()
+    {
+        let file_name = "test_lazy_avl_cursor.db";
+        let numbers: Vec<i64> = (0..100).map(|i| (i * 37) % 100).collect();
+        let mut tree = build_tree(file_name, 3, &numbers);
+
+        // Tree order is greatest first, so going forwards is the tree order backwards
+        let mut positions = tree.positions().unwrap();
+        positions.reverse();
+
+        let mut cursor = tree.cursor();
+        assert_eq!(cursor.next().unwrap(), None);
+
+        let mut forwards = vec![cursor.seek_first().unwrap().unwrap()];
+
+        while let Some(position) = cursor.next().unwrap()
+        {
+            forwards.push(position);
+        }
+
+        assert_eq!(forwards, positions);
+        assert_eq!(cursor.position(), None);
+
+        let mut backwards = vec![cursor.seek_last().unwrap().unwrap()];
+
+        while let Some(position) = cursor.prev().unwrap()
+        {
+            backwards.push(position);
+        }
+
+        backwards.reverse();
+        assert_eq!(backwards, positions);
+
+        // Seeking lands between fields, and the cursor can turn around
+        let number = |cursor: &mut Cursor| cursor.field().unwrap().unwrap().value;
+        let n = |number: i64| Field::new("n", Type::I(Some(I::new(number))));
+
+        cursor.seek(&n(40), true).unwrap();
+        assert_eq!(number(&mut cursor), Type::I(Some(I::new(40))));
+        cursor.seek(&n(40), false).unwrap();
+        assert_eq!(number(&mut cursor), Type::I(Some(I::new(41))));
+        cursor.prev().unwrap();
+        cursor.prev().unwrap();
+        assert_eq!(number(&mut cursor), Type::I(Some(I::new(39))));
+        cursor.seek_back(&n(40), false).unwrap();
+        assert_eq!(number(&mut cursor), Type::I(Some(I::new(39))));
+        cursor.seek_back(&n(40), true).unwrap();
+        assert_eq!(number(&mut cursor), Type::I(Some(I::new(40))));
+
+        assert_eq!(cursor.seek(&n(99), false).unwrap(), None);
+        assert_eq!(cursor.seek_back(&n(0), false).unwrap(), None);
+
+        remove_file(file_name).unwrap();
+    }
+
+    #[test]
+    fn test_lazy_avl_range()
+    {
+        let file_name = "test_lazy_avl_range.db";
+        let numbers: Vec<i64> = (0..120).map(|i| (i * 7) % 60).collect();
+        let mut tree = build_tree(file_name, 0, &numbers);
+
+        let mut range_numbers = |lower: Bound<i64>, upper: Bound<i64>| -> Vec<i64>
+        {
+            let lower = lower.map(|number| Type::I(Some(I::new(number))));
+            let upper = upper.map(|number| Type::I(Some(I::new(number))));
+            let range = tree.range("n", lower.as_ref(), upper.as_ref()).unwrap();
+            let positions: Vec<u64> = range.map(|position| position.unwrap()).collect();
+
+            return positions.iter().map(|position| match tree.file.read_field(*position).unwrap().value
+            {
+                Type::I(Some(number)) => number.to_i64().unwrap(),
+                _ => panic!("Tree holds something other than a number!"),
+            }).collect();
+        };
+
+        let doubled = |numbers: std::ops::Range<i64>| -> Vec<i64> { numbers.flat_map(|number| [number, number]).collect() };
+
+        assert_eq!(range_numbers(Bound::Included(30), Bound::Included(40)), doubled(30..41));
+        assert_eq!(range_numbers(Bound::Excluded(30), Bound::Excluded(40)), doubled(31..40));
+        assert_eq!(range_numbers(Bound::Included(30), Bound::Excluded(40)), doubled(30..40));
+        assert_eq!(range_numbers(Bound::Unbounded, Bound::Excluded(3)), doubled(0..3));
+        assert_eq!(range_numbers(Bound::Excluded(56), Bound::Unbounded), doubled(57..60));
+        assert_eq!(range_numbers(Bound::Unbounded, Bound::Unbounded), doubled(0..60));
+        assert_eq!(range_numbers(Bound::Included(40), Bound::Included(30)), Vec::<i64>::new());
+        assert_eq!(range_numbers(Bound::Excluded(59), Bound::Unbounded), Vec::<i64>::new());
+
+        // Ranges are lazy, so a page only reads what it needs
+        let lower = Type::I(Some(I::new(10)));
+        let page: Vec<u64> = tree.range("n", Bound::Included(&lower), Bound::Unbounded).unwrap().take(5).map(|position| position.unwrap()).collect();
+        assert_eq!(page.len(), 5);
+        assert!(tree.range("m", Bound::Unbounded, Bound::Unbounded).unwrap().next().is_none());
+
+        remove_file(file_name).unwrap();
+    }
+
+    #[test]
+    fn test_lazy_avl_remove()
+    {
+        let file_name = "test_lazy_avl_remove.db";
+        let mut numbers = Vec::<i64>::new();
+        let mut order = Vec::<usize>::new();
+        let mut seed: u64 = 0x9E3779B97F4A7C15;
+
+        for i in 0..200
+        {
+            seed ^= seed << 13;
+            seed ^= seed >> 7;
+            seed ^= seed << 17;
+            numbers.push((seed % 80) as i64);
+            order.push(i);
+        }
+
+        // Remove in an order unrelated to the insert order
+        order.sort_by_key(|i| (numbers[*i] * 31 + *i as i64) % 97);
+
+        for laze in [0, 3]
+        {
+            let mut tree = build_tree(file_name, laze, &numbers);
+            let mut positions = tree.positions().unwrap();
+            positions.sort();
+
+            // Insertion points go up with the insert order, so they line up with the numbers
+            let mut remaining = numbers.clone();
+
+            for (removed, i) in order.iter().enumerate()
+            {
+                assert!(tree.remove(positions[*i]).unwrap());
+                assert!(!tree.remove(positions[*i]).unwrap());
+
+                remaining.remove(remaining.iter().position(|number| *number == numbers[*i]).unwrap());
+
+                if removed % 10 == 0
+                {
+                    tree.check().unwrap();
+
+                    let mut expected = remaining.clone();
+                    expected.sort_by(|a, b| b.cmp(a));
+                    assert_eq!(tree_numbers(&mut tree), expected);
+                }
+            }
+
+            assert_eq!(tree.head, 0);
+
+            // The tree can be filled again afterwards
+            for position in &positions[..20]
+            {
+                tree.insert(*position).unwrap();
+            }
+
+            tree.check().unwrap();
+        }
+
+        remove_file(file_name).unwrap();
+    }
+
+    #[test]
+    fn test_lazy_avl_remove_interrupted()
+    {
+        let file_name = "test_lazy_avl_remove_interrupted.db";
+        let numbers: Vec<i64> = (0..60).collect();
+
+        // Cut the removal short after every number of writes, including before any and after all of them
+        for writes_made in 0..8
+        {
+            let mut tree = build_tree(file_name, 0, &numbers);
+            let mut positions = tree.positions().unwrap();
+            positions.sort();
+
+            // Removing the head swaps it with its successor, which takes several writes
+            let head = tree.head;
+            assert!(tree.stage_remove(head).unwrap());
+
+            let patches: Vec<JournalPatch> = tree.pending.iter().map(|(field_pos, node)| (*field_pos, node.to_bytes())).collect();
+            let new_head = tree.pending_head.unwrap_or(head);
+            assert!(patches.len() >= 2);
+
+            let journal = tree.file.add_journal(ChunkyFile::journal_size(&patches)).unwrap();
+            tree.file.write_journal(journal, &patches).unwrap();
+
+            for (field_pos, data) in patches.iter().take(writes_made)
+            {
+                tree.file.write_at(*field_pos, data).unwrap();
+            }
+
+            drop(tree);
+
+            // Opening the file finishes the removal
+            let mut tree = LazyAVL::new(ChunkyFile::open(file_name).unwrap(), new_head, 0);
+            tree.check().unwrap();
+
+            let mut expected: Vec<i64> = numbers.iter().copied().filter(|number| positions[*number as usize] != head).collect();
+            expected.reverse();
+            assert_eq!(tree_numbers(&mut tree), expected);
+            assert_eq!(tree.file.replay_journals().unwrap(), 0);
+        }
+
+        remove_file(file_name).unwrap();
+    }
+
+    #[test]
+    fn test_lazy_avl_commit_with()
+    {
+        let file_name = "test_lazy_avl_commit_with.db";
+        let _ = remove_file(file_name);
+
+        let mut db_file = ChunkyFile::create(file_name, DbHeadChunk::new("test", "tester")).unwrap();
+        let journal = db_file.add_journal(1024).unwrap();
+        let mut ascending = LazyAVL::new(db_file.try_clone().unwrap(), 0, 0).with_journal(journal);
+        let mut descending = LazyAVL::new(db_file.try_clone().unwrap(), 0, 0).with_journal(journal);
+        let number = |n: i64| Type::I(Some(I::new(n)));
+
+        // Each entry goes into both trees in one commit
+        for n in 0..40
+        {
+            let entry = Entry::new(UuidV4::new(), vec![Field::new("up", number(n)), Field::new("down", number(-n))]).unwrap();
+            let insertion_points = db_file.add_entry_chunk(EntryChunk::new(entry)).unwrap();
+
+            ascending.stage_insert(insertion_points[0]).unwrap();
+            descending.stage_insert(insertion_points[1]).unwrap();
+            ascending.commit_with(&mut [&mut descending]).unwrap();
+        }
+
+        ascending.check().unwrap();
+        descending.check().unwrap();
+        assert_eq!(tree_numbers(&mut ascending), (0..40).rev().collect::<Vec<i64>>());
+        assert_eq!(tree_numbers(&mut descending), (-39..=0).rev().collect::<Vec<i64>>());
+
+        // Cut short before any of the writes are made, opening the file finishes them in both trees
+        let entry = Entry::new(UuidV4::new(), vec![Field::new("up", number(40)), Field::new("down", number(-40))]).unwrap();
+        let insertion_points = db_file.add_entry_chunk(EntryChunk::new(entry)).unwrap();
+
+        ascending.stage_insert(insertion_points[0]).unwrap();
+        descending.stage_insert(insertion_points[1]).unwrap();
+
+        let (mut patches, ascending_head) = ascending.take_staged();
+        let (mut descending_patches, descending_head) = descending.take_staged();
+        patches.append(&mut descending_patches);
+        db_file.write_journal(journal, &patches).unwrap();
+
+        let ascending_head = ascending_head.unwrap_or(ascending.head);
+        let descending_head = descending_head.unwrap_or(descending.head);
+        drop(ascending);
+        drop(descending);
+        drop(db_file);
+
+        let db_file = ChunkyFile::open(file_name).unwrap();
+        let mut ascending = LazyAVL::new(db_file.try_clone().unwrap(), ascending_head, 0);
+        let mut descending = LazyAVL::new(db_file, descending_head, 0);
+
+        ascending.check().unwrap();
+        descending.check().unwrap();
+        assert_eq!(tree_numbers(&mut ascending), (0..=40).rev().collect::<Vec<i64>>());
+        assert_eq!(tree_numbers(&mut descending), (-40..=0).rev().collect::<Vec<i64>>());
+        drop(ascending);
+        drop(descending);
+
+        remove_file(file_name).unwrap();
+    }
+
+    #[test]
+    fn test_lazy_avl_check_balance()
+    {
+        let file_name = "test_lazy_avl_check_balance.db";
+        let mut tree = build_tree(file_name, 0, &[3, 2, 1]);
+        tree.check().unwrap();
+
+        // Lie about the balance of the head
+        let head = tree.head;
+        tree.field_change_balance(head, 1).unwrap();
+        tree.commit().unwrap();
+        assert!(tree.check().is_err());
+
+        remove_file(file_name).unwrap();
+    }
+}
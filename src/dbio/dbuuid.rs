@@ -2,6 +2,9 @@
 
 
 use uuid::Uuid; // Use the uuid library
+use std::error::Error;
+use std::fmt;
+use simple_error::*;
 
 
 
@@ -12,7 +15,7 @@ use uuid::Uuid; // Use the uuid library
 
 // dbuuid::UuidV4 - apedb uuid v4
 //
-#[derive(Debug, Clone, PartialEq)]
+#[derive(Debug, Clone, PartialEq, PartialOrd)]
 pub struct UuidV4
 {
     uuid: Uuid,
@@ -36,6 +39,43 @@ impl UuidV4
     {
         return self.uuid.as_bytes().to_vec();
     }
+
+    // dbuuid::UuidV4::from_bytes - convert bytes back to a UUID
+    //
+    // ARGUMENTS:
+    //  bytes: &[u8] - The 16 bytes of the UUID
+    pub fn from_bytes(bytes: &[u8]) -> Result<UuidV4, Box<dyn Error>>
+    {
+        let uuid = match Uuid::from_slice(bytes)
+        {
+            Ok(uuid) => uuid,
+            Err(_) => bail!("UUID is {} bytes long, expected 16!", bytes.len()),
+        };
+
+        return Ok
+        (
+            UuidV4
+            {
+                uuid: uuid,
+            }
+        );
+    }
+}
+
+impl fmt::Display for UuidV4
+{
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result
+    {
+        return write!(f, "{}", self.uuid);
+    }
+}
+
+impl Default for UuidV4
+{
+    fn default() -> Self
+    {
+        return UuidV4::new();
+    }
 }
 
 // dbuuid::UuidV4Cache - cache of UUIDs
@@ -120,6 +160,17 @@ mod tests
         assert_ne!(uuid, UuidV4::new()); // Check that the uuid is unique
     }
 
+    // dbio::dbuuid::test_uuid_v4_bytes - test converting a uuid to bytes and back
+    //
+    #[test]
+    fn test_uuid_v4_bytes()
+    {
+        let uuid = UuidV4::new();
+
+        assert_eq!(UuidV4::from_bytes(&uuid.to_bytes()).unwrap(), uuid);
+        assert!(UuidV4::from_bytes(&[0; 15]).is_err());
+    }
+
     // dbio::dbuuid::test_uuid_v4_cache_new - test the uuid v4 cache creation
     //
     #[test]
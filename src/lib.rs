@@ -0,0 +1,9 @@
+// lib.rs - The ApeDB library, main.rs is a small test program built on top of it
+
+// Lints that clash with the project's code style (explicit returns, explicit field init, etc.)
+#![allow(clippy::needless_return, clippy::redundant_field_names, clippy::vec_init_then_push, clippy::len_zero, clippy::manual_find)]
+// Constant modules such as CHUNK_TYPE are upper case on purpose
+#![allow(non_snake_case)]
+
+pub mod dbio;
+pub mod apetypes;
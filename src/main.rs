@@ -1,18 +1,18 @@
-mod dbio;
-mod apetypes;
+// Lints that clash with the project's code style (explicit returns, explicit field init, etc.)
+#![allow(clippy::needless_return, clippy::redundant_field_names)]
 
-//use crate::apetypes::*;
-//use crate::dbio::dbfield::*;
-use crate::dbio::dbuuid::UuidV4;
-use crate::dbio::dblist::Entry;
-use crate::apetypes::*;
-use crate::dbio::dbfield::Field;
-use crate::dbio::dbchunk::*;
+use apedb::dbio::dbuuid::UuidV4;
+use apedb::dbio::dblist::Entry;
+use apedb::apetypes::*;
+use apedb::dbio::dbfield::Field;
+use apedb::dbio::dbchunk::*;
 
 // Test function, not made to be pretty...
 fn main()
 {
-    let mut chunky_db = match ChunkyFile::create("test.apedb")
+    let db_chunk = DbHeadChunk::new("Ape Database!", "root");
+
+    let mut chunky_db = match ChunkyFile::create("test.apedb", db_chunk)
     {
         Ok(chunky) =>
         {
@@ -24,8 +24,6 @@ fn main()
         }
     };
 
-    let db_chunk = DbHeadChunk::new("Ape Database!", "root");
-
     let mut numbers = String::new();
 
     let mut i = 0;